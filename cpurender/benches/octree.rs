@@ -0,0 +1,82 @@
+//! Compare dense grid traversal against sparse octree traversal, on a 512³ terrain.
+//!
+//! Run with `cargo bench --bench octree`.
+
+use cpurender::{
+    camera::Camera,
+    octree::VoxelOctree,
+    voxel::VoxelGrid,
+    re::vek::*,
+};
+
+use std::time::{Duration, Instant};
+
+const SCENE_SIZE: usize = 512;
+const VIEW_SIZE: usize = 256;
+
+/// Rolling hills, with a few floating blocks above them.
+fn terrain() -> VoxelGrid<bool> {
+    let size = Vec3::broadcast(SCENE_SIZE);
+    VoxelGrid::from_fn(size, |xyz| {
+        let p = xyz.map(|c| c as f32);
+        let height = 96.0
+            + (p.x / 37.0).sin() * 24.0
+            + (p.z / 53.0).cos() * 24.0
+            + (p.x / 11.0 + p.z / 17.0).sin() * 6.0;
+        let block = xyz.map(|c| c % 128 < 12).reduce_and() && xyz.y > 200;
+        p.y < height || block
+    })
+}
+
+/// Trace a ray for every pixel of a view, returning the elapsed time and number of hits.
+fn time_frame(mut first_hit: impl FnMut(Vec2<i32>) -> bool) -> (Duration, usize) {
+    let start = Instant::now();
+    let mut hits = 0;
+    for y in 0..VIEW_SIZE as i32 {
+        for x in 0..VIEW_SIZE as i32 {
+            hits += first_hit(Vec2::new(x, y)) as usize;
+        }
+    }
+    (start.elapsed(), hits)
+}
+
+fn main() {
+    let start = Instant::now();
+    let grid = terrain();
+    println!("built {}³ grid in {:?}", SCENE_SIZE, start.elapsed());
+
+    let start = Instant::now();
+    let octree = VoxelOctree::from_grid(&grid, |&solid| !solid);
+    println!("built octree with {} nodes in {:?}", octree.node_count(), start.elapsed());
+
+    let s = SCENE_SIZE as f32;
+    let views = [
+        ("overhead", Vec3::new(s / 2.0, s * 1.2, s / 2.0), Vec3::new(s / 2.0, 0.0, s / 2.0 + 1.0)),
+        ("horizon", Vec3::new(-10.0, 160.0, -10.0), Vec3::new(s, 90.0, s)),
+        ("inside", Vec3::new(s / 2.0, 140.0, s / 2.0), Vec3::new(s, 100.0, 0.0)),
+    ];
+
+    for &(name, position, target) in &views {
+        let camera = Camera::perspective(VIEW_SIZE, VIEW_SIZE, 70f32.to_radians())
+            .at(position)
+            .looking_at(target, Vec3::unit_y());
+
+        let (dense, dense_hits) = time_frame(|xy| {
+            grid.trace(camera.ray_for_pixel(xy), f32::INFINITY)
+                .any(|(_, &solid)| solid)
+        });
+        let (sparse, sparse_hits) = time_frame(|xy| {
+            octree.first_hit(camera.ray_for_pixel(xy), f32::INFINITY).is_some()
+        });
+        assert_eq!(dense_hits, sparse_hits);
+
+        println!(
+            "{:>8}: dense {:>10.2?}, octree {:>10.2?} ({:.1}x), {} hits",
+            name,
+            dense,
+            sparse,
+            dense.as_secs_f64() / sparse.as_secs_f64(),
+            dense_hits,
+        );
+    }
+}
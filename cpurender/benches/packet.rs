@@ -0,0 +1,156 @@
+//! Compare the scalar DDA voxel tracer against a port which traces packets of rays at once.
+//!
+//! Run with `cargo bench --bench packet`.
+
+use cpurender::{
+    camera::Camera,
+    frag::{render_packets_with, render_with, Execution},
+    packet::*,
+    voxel::VoxelGrid,
+    re::vek::*,
+};
+
+use std::time::{Duration, Instant};
+
+const SCENE_SIZE: usize = 64;
+const VIEW_SIZE: usize = 400;
+
+/// Checkerboard lattice of single voxels.
+fn lattice() -> VoxelGrid<bool> {
+    VoxelGrid::from_fn(Vec3::broadcast(SCENE_SIZE), |xyz| {
+        xyz.map(|c| c % 4 == 0).reduce_and() || (xyz.y == 0 && (xyz.x + xyz.z) % 2 == 0)
+    })
+}
+
+/// Gray level for a hit distance, or black for a miss.
+fn depth_color(distance: Option<f32>) -> Rgba<u8> {
+    match distance {
+        Some(d) => {
+            let gray = (255.0 - d * 2.0).max(16.0) as u8;
+            Rgba::new(gray, gray, gray, 255)
+        },
+        None => Rgba::black(),
+    }
+}
+
+/// Trace a packet of rays through the grid, returning which lanes hit a solid voxel, and
+/// the distance at which they entered it.
+///
+/// This is the same digital differential analyzer as `VoxelGrid::trace`, with each lane
+/// stepping independently and masked out once it hits or leaves the grid.
+fn trace_packet<const N: usize>(
+    grid: &VoxelGrid<bool>,
+    origin: Vec3<F32s<N>>,
+    dir: Vec3<F32s<N>>,
+    mut active: Mask<N>,
+) -> (Mask<N>, F32s<N>) {
+    let size = grid.size().map(|c| c as i32);
+    let inv_dir = dir.map(F32s::recip);
+
+    // slab test against the grid bounds
+    let near = origin.map(|c| -c) * inv_dir;
+    let far = (splat3(size.map(|c| c as f32)) - origin) * inv_dir;
+    let (near, far) = (near.map2(far, F32s::min), near.map2(far, F32s::max));
+    let enter = near.x.max(near.y).max(near.z).max(F32s::splat(0.0));
+    let exit = far.x.min(far.y).min(far.z);
+    active &= enter.simd_le(exit);
+
+    // starting voxel, nudged inside in case the ray starts on the boundary
+    let start = origin + dir * (enter + 0.0001);
+    let mut voxel = Vec3::new(0, 1, 2).map(|a| {
+        start[a].floor().to_i32().max(I32s::splat(0)).min(I32s::splat(size[a] - 1))
+    });
+
+    let positive = dir.map(|c| c.simd_gt(F32s::splat(0.0)));
+    let step = positive.map(|p| p.select(I32s::splat(1), I32s::splat(-1)));
+    let delta = inv_dir.map(F32s::abs);
+    let mut t_max = Vec3::new(0, 1, 2).map(|a| {
+        let boundary = (voxel[a] + positive[a].select(I32s::splat(1), I32s::splat(0))).to_f32();
+        let t = (boundary - origin[a]) * inv_dir[a];
+        dir[a].simd_eq(F32s::splat(0.0)).select(F32s::splat(f32::INFINITY), t)
+    });
+
+    let mut distance = enter;
+    let mut hit = Mask::splat(false);
+    while active.any() {
+        // look up voxels one lane at a time, since there's no gather
+        let solid = Mask::from_fn(|i| active.lane(i) && grid.get(lane3(voxel, i)) == Some(&true));
+        hit |= solid;
+        active &= !solid;
+
+        // step each lane along whichever axis reaches its next boundary first
+        let x = t_max.x.simd_le(t_max.y) & t_max.x.simd_le(t_max.z);
+        let y = !x & t_max.y.simd_le(t_max.z);
+        let z = !x & !y;
+        for (a, &axis) in [x, y, z].iter().enumerate() {
+            let axis = axis & active;
+            distance = axis.select(t_max[a], distance);
+            voxel[a] += axis.select(step[a], I32s::splat(0));
+            t_max[a] += axis.select(delta[a], F32s::splat(0.0));
+            active &= voxel[a].simd_ge(I32s::splat(0)) & voxel[a].simd_lt(I32s::splat(size[a]));
+        }
+    }
+    (hit, distance)
+}
+
+/// Render a view with the packet tracer.
+fn render_packet_view<const N: usize>(
+    grid: &VoxelGrid<bool>,
+    camera: &Camera,
+) -> (Duration, cpurender::canvas::Canvas)
+    where
+        Lanes<N>: SupportedLanes {
+    let start = Instant::now();
+    let execution = Execution::Sequential;
+    let canvas = render_packets_with(VIEW_SIZE, VIEW_SIZE, &execution, |p: PixelPacket<N>| {
+        let rays: [_; N] = std::array::from_fn(|i| camera.ray_for_pixel(p.lane(i)));
+        let origin = Vec3::new(0, 1, 2).map(|a| F32s::from_fn(|i| rays[i].origin[a]));
+        let dir = Vec3::new(0, 1, 2).map(|a| F32s::from_fn(|i| rays[i].dir[a]));
+        let (hit, distance) = trace_packet(grid, origin, dir, p.mask);
+        std::array::from_fn(|i| depth_color(Some(distance.lane(i)).filter(|_| hit.lane(i))))
+    });
+    (start.elapsed(), canvas)
+}
+
+fn main() {
+    let grid = lattice();
+    let s = SCENE_SIZE as f32;
+    let views = [
+        ("outside", Vec3::new(-20.0, s * 1.3, -20.0), Vec3::broadcast(s / 2.0)),
+        ("inside", Vec3::new(s / 2.0 + 0.5, 6.5, 2.5), Vec3::new(s / 3.0, 2.0, s)),
+    ];
+
+    for &(name, position, target) in &views {
+        let camera = Camera::perspective(VIEW_SIZE, VIEW_SIZE, 70f32.to_radians())
+            .at(position)
+            .looking_at(target, Vec3::unit_y());
+
+        // both single-threaded, to compare per-core throughput
+        let start = Instant::now();
+        let scalar = render_with(VIEW_SIZE, VIEW_SIZE, &Execution::Sequential, |xy| {
+            let hit = grid.trace(camera.ray_for_pixel(xy), f32::INFINITY)
+                .find(|&(_, &solid)| solid);
+            depth_color(hit.map(|(hit, _)| hit.distance))
+        });
+        let scalar_time = start.elapsed();
+        println!("{:>8}: scalar    {:>10.2?}", name, scalar_time);
+
+        let packets = [
+            render_packet_view::<4>(&grid, &camera),
+            render_packet_view::<8>(&grid, &camera),
+            render_packet_view::<16>(&grid, &camera),
+        ];
+        for (&(time, ref canvas), width) in packets.iter().zip(&[4, 8, 16]) {
+            let comparison = cpurender::golden::compare(&scalar, canvas, 2);
+            println!(
+                "{:>8}: packet {:>2} {:>10.2?} ({:.1}x), {} differing pixels",
+                name,
+                width,
+                time,
+                scalar_time.as_secs_f64() / time.as_secs_f64(),
+                comparison.differing,
+            );
+            assert!(comparison.differing < VIEW_SIZE * VIEW_SIZE / 1000);
+        }
+    }
+}
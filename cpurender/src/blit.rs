@@ -0,0 +1,166 @@
+use crate::canvas::{blend_over, Canvas};
+
+use std::convert::TryFrom;
+
+use image::RgbaImage;
+use vek::*;
+
+/// Porter-Duff compositing operators, for combining a source color with a destination
+/// color.
+///
+/// "In" and "out" keep the part of one inside or outside the other, and "atop" keeps the
+/// part of one inside the other, over the other.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Composite {
+    Clear,
+    Src,
+    Dst,
+    /// Normal alpha blending.
+    #[default]
+    SrcOver,
+    DstOver,
+    SrcIn,
+    DstIn,
+    SrcOut,
+    DstOut,
+    SrcAtop,
+    DstAtop,
+    Xor,
+    /// Adds the colors, saturating, for glows and light.
+    Plus,
+}
+
+impl Composite {
+    /// Composite two colors which aren't premultiplied.
+    pub fn apply(self, src: Rgba<u8>, dst: Rgba<u8>) -> Rgba<u8> {
+        match self {
+            Composite::SrcOver => return blend_over(src, dst),
+            Composite::Src => return src,
+            Composite::Dst => return dst,
+            _ => (),
+        }
+
+        // the fraction of each premultiplied color to keep
+        let src_a = src.a as f32 / 255.0;
+        let dst_a = dst.a as f32 / 255.0;
+        let (src_f, dst_f) = match self {
+            Composite::Clear => (0.0, 0.0),
+            Composite::DstOver => (1.0 - dst_a, 1.0),
+            Composite::SrcIn => (dst_a, 0.0),
+            Composite::DstIn => (0.0, src_a),
+            Composite::SrcOut => (1.0 - dst_a, 0.0),
+            Composite::DstOut => (0.0, 1.0 - src_a),
+            Composite::SrcAtop => (dst_a, 1.0 - src_a),
+            Composite::DstAtop => (1.0 - dst_a, src_a),
+            Composite::Xor => (1.0 - dst_a, 1.0 - src_a),
+            Composite::Plus => (1.0, 1.0),
+            Composite::Src | Composite::Dst | Composite::SrcOver => unreachable!(),
+        };
+
+        let a = (src_a * src_f + dst_a * dst_f).min(1.0);
+        if a <= 0.0 {
+            return Rgba::zero();
+        }
+        let src_rgb = src.rgb().map(|c| c as f32 / 255.0);
+        let dst_rgb = dst.rgb().map(|c| c as f32 / 255.0);
+        let rgb = (src_rgb * (src_a * src_f) + dst_rgb * (dst_a * dst_f)) / a;
+        Rgba::from_translucent(rgb, a).map(|c| (c.min(1.0) * 255.0 + 0.5) as u8)
+    }
+}
+
+/// How to blit an image.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BlitOptions {
+    /// The part of the image to copy, such as one sprite of a sprite sheet, or the whole
+    /// image if absent.
+    ///
+    /// This is in image pixels, with the origin at the image's top-left corner.
+    pub source: Option<Rect<u32, u32>>,
+    /// Mirror left to right.
+    pub flip_x: bool,
+    /// Mirror top to bottom.
+    pub flip_y: bool,
+    /// Size of each image pixel, in canvas pixels.
+    pub scale: usize,
+    pub composite: Composite,
+    /// Multiplied with each image pixel, so white leaves them as they are.
+    pub tint: Rgba<u8>,
+}
+
+impl Default for BlitOptions {
+    fn default() -> Self {
+        BlitOptions {
+            source: None,
+            flip_x: false,
+            flip_y: false,
+            scale: 1,
+            composite: Composite::SrcOver,
+            tint: Rgba::white(),
+        }
+    }
+}
+
+/// Copy an image, or part of it, into a canvas, compositing it with the existing pixels.
+///
+/// `pos` is where the bottom-left corner of the image goes, as with `Canvas::draw_over`.
+/// The image appears upright, and whatever falls outside the canvas is clipped.
+///
+/// Only canvas pixels covered by the image are composited. Operators which would affect
+/// the destination outside the source, such as `Clear`, `Src` and `SrcIn`, leave the rest
+/// of the canvas as it is.
+pub fn blit(canvas: &mut Canvas, image: &RgbaImage, pos: Vec2<i32>, options: &BlitOptions) {
+    // source rectangle, clipped to the image
+    let image_size = Vec2::new(image.width(), image.height());
+    let source = options.source.unwrap_or(Rect::new(0, 0, image_size.x, image_size.y));
+    let source_min = Vec2::new(source.x, source.y);
+    let source_max = source_min.map2(Vec2::new(source.w, source.h), u32::saturating_add);
+    let source_min = source_min.map2(image_size, u32::min).map(|c| c as usize);
+    let source_max = source_max.map2(image_size, u32::min).map(|c| c as usize);
+    let source_size = source_max - source_min;
+
+    // destination rectangle, clipped to the canvas, in 64 bits so huge scales can't
+    // overflow, and saturating since anything that large is clipped anyway
+    let scale = options.scale.max(1);
+    let size = source_size.map(|c| {
+        c.checked_mul(scale)
+            .and_then(|c| i64::try_from(c).ok())
+            .unwrap_or(i64::MAX)
+    });
+    let pos = pos.map(i64::from);
+    let min = pos.map(|c| c.max(0));
+    let max = pos.map2(size, i64::saturating_add)
+        .map2(canvas.size(), |c, canvas| c.min(canvas as i64));
+    if min.x >= max.x || min.y >= max.y {
+        return;
+    }
+
+    let x_size = canvas.x_size();
+    let image_x_size = image.width() as usize;
+    let raw: &[u8] = image;
+    let pixels = canvas.pixels_mut();
+    for y in min.y..max.y {
+        // image rows go down, from the top of the destination
+        let row = (y - pos.y) as usize / scale;
+        let row = if options.flip_y { row } else { source_size.y - 1 - row };
+        let image_row = (source_min.y + row) * image_x_size;
+        let canvas_row = &mut pixels[y as usize * x_size..(y as usize + 1) * x_size];
+
+        for x in min.x..max.x {
+            let column = (x - pos.x) as usize / scale;
+            let column = if options.flip_x { source_size.x - 1 - column } else { column };
+            let i = (image_row + source_min.x + column) * 4;
+            let mut src = Rgba::new(raw[i], raw[i + 1], raw[i + 2], raw[i + 3]);
+            if options.tint != Rgba::white() {
+                src = src.map2(options.tint, |c, t| ((c as u16 * t as u16 + 127) / 255) as u8);
+            }
+
+            let dst = &mut canvas_row[x as usize];
+            *dst = match (options.composite, src.a) {
+                // the common cases for sprites, quickly
+                (Composite::SrcOver, 0x00) => continue,
+                (Composite::SrcOver, 0xFF) => src,
+                (composite, _) => composite.apply(src, *dst),
+            };
+        }
+    }
+}
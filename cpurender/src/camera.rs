@@ -0,0 +1,254 @@
+use crate::ray::Ray;
+
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use vek::*;
+
+/// How a camera maps canvas positions to rays.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    /// Rays diverge from the camera position, with a vertical field of view in radians.
+    Perspective { fov_y: f32 },
+    /// Rays are parallel, covering a vertical extent in world units.
+    Orthographic { height: f32 },
+    /// Equidistant fisheye, with a field of view in radians across the height of the canvas.
+    Fisheye { fov: f32 },
+    /// Full 360 degree panorama, with longitude along x and latitude along y.
+    Equirectangular,
+}
+
+/// A camera, which generates a ray for each pixel of the canvas.
+///
+/// We use left-handed y-up coordinates: an unrotated camera looks down +z, with +x to its
+/// right. Like the canvas, pixel y coordinates increase upwards.
+///
+/// Everything which doesn't vary per-pixel is precomputed when the camera is configured,
+/// so it should be constructed once per frame and then shared between fragments.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Camera {
+    position: Vec3<f32>,
+    orientation: Quaternion<f32>,
+    projection: Projection,
+    canvas_size: Vec2<f32>,
+    aspect: f32,
+
+    // precomputed
+    right: Vec3<f32>,
+    up: Vec3<f32>,
+    forward: Vec3<f32>,
+    /// Multiplied by normalized device coordinates, depending on the projection.
+    scale: Vec2<f32>,
+}
+
+impl Camera {
+    /// Create a camera at the origin looking down +z, with an aspect ratio matching the
+    /// canvas.
+    pub fn new(x_size: usize, y_size: usize, projection: Projection) -> Self {
+        let mut camera = Camera {
+            position: Vec3::zero(),
+            orientation: Quaternion::identity(),
+            projection,
+            canvas_size: Vec2::new(x_size as f32, y_size as f32),
+            aspect: x_size as f32 / y_size as f32,
+            right: Vec3::unit_x(),
+            up: Vec3::unit_y(),
+            forward: Vec3::unit_z(),
+            scale: Vec2::one(),
+        };
+        camera.precompute();
+        camera
+    }
+
+    /// Create a perspective camera with a vertical field of view in radians.
+    pub fn perspective(x_size: usize, y_size: usize, fov_y: f32) -> Self {
+        Camera::new(x_size, y_size, Projection::Perspective { fov_y })
+    }
+
+    /// Create an orthographic camera covering some height in world units.
+    pub fn orthographic(x_size: usize, y_size: usize, height: f32) -> Self {
+        Camera::new(x_size, y_size, Projection::Orthographic { height })
+    }
+
+    /// Create an equidistant fisheye camera with a field of view in radians.
+    pub fn fisheye(x_size: usize, y_size: usize, fov: f32) -> Self {
+        Camera::new(x_size, y_size, Projection::Fisheye { fov })
+    }
+
+    /// Create an equirectangular panorama camera.
+    pub fn equirectangular(x_size: usize, y_size: usize) -> Self {
+        Camera::new(x_size, y_size, Projection::Equirectangular)
+    }
+
+    pub fn position(&self) -> Vec3<f32> {
+        self.position
+    }
+
+    pub fn orientation(&self) -> Quaternion<f32> {
+        self.orientation
+    }
+
+    pub fn projection(&self) -> Projection {
+        self.projection
+    }
+
+    /// Width divided by height.
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    /// Unit vector the camera is looking along.
+    pub fn forward(&self) -> Vec3<f32> {
+        self.forward
+    }
+
+    /// Unit vector to the camera's right.
+    pub fn right(&self) -> Vec3<f32> {
+        self.right
+    }
+
+    /// Unit vector to the camera's top.
+    pub fn up(&self) -> Vec3<f32> {
+        self.up
+    }
+
+    /// Builder-style setter for the position.
+    pub fn at(mut self, position: Vec3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    /// Builder-style setter for the orientation.
+    pub fn oriented(mut self, orientation: Quaternion<f32>) -> Self {
+        self.orientation = orientation.normalized();
+        self.precompute();
+        self
+    }
+
+    /// Builder-style setter for the projection.
+    pub fn projected(mut self, projection: Projection) -> Self {
+        self.projection = projection;
+        self.precompute();
+        self
+    }
+
+    /// Builder-style setter for the aspect ratio, overriding the canvas's.
+    pub fn with_aspect(mut self, aspect: f32) -> Self {
+        self.aspect = aspect;
+        self.precompute();
+        self
+    }
+
+    /// Orient the camera to look along a direction, keeping its top towards `up`.
+    pub fn looking_in(self, direction: Vec3<f32>, up: Vec3<f32>) -> Self {
+        self.oriented(look_rotation(direction, up))
+    }
+
+    /// Orient the camera to look at a point, keeping its top towards `up`.
+    pub fn looking_at(self, target: Vec3<f32>, up: Vec3<f32>) -> Self {
+        let direction = target - self.position;
+        self.looking_in(direction, up)
+    }
+
+    /// Orient the camera by yaw about the y axis, then pitch upwards, in radians.
+    pub fn with_yaw_pitch(self, yaw: f32, pitch: f32) -> Self {
+        self.oriented(Quaternion::rotation_y(yaw) * Quaternion::rotation_x(-pitch))
+    }
+
+    /// World-space ray through the center of a pixel.
+    pub fn ray_for_pixel(&self, xy: Vec2<i32>) -> Ray {
+        let ndc = (xy.map(|c| c as f32) + Vec2::broadcast(0.5)) / self.canvas_size
+            * 2.0
+            - Vec2::one();
+        self.ray_for_ndc(ndc)
+    }
+
+    /// World-space ray through a point in normalized device coordinates, where the canvas
+    /// spans `[-1, 1]` on both axes.
+    pub fn ray_for_ndc(&self, ndc: Vec2<f32>) -> Ray {
+        let p = ndc * self.scale;
+        match self.projection {
+            Projection::Perspective { .. } => {
+                let dir = self.forward + self.right * p.x + self.up * p.y;
+                Ray::new(self.position, dir.normalized())
+            },
+            Projection::Orthographic { .. } => {
+                let origin = self.position + self.right * p.x + self.up * p.y;
+                Ray::new(origin, self.forward)
+            },
+            Projection::Fisheye { .. } => {
+                // angle from the forward axis is proportional to distance from the center
+                let theta = p.magnitude();
+                let dir = if theta > 0.0 {
+                    let sideways = (self.right * p.x + self.up * p.y) / theta;
+                    self.forward * theta.cos() + sideways * theta.sin()
+                } else {
+                    self.forward
+                };
+                Ray::new(self.position, dir)
+            },
+            Projection::Equirectangular => {
+                let (lon, lat) = (p.x, p.y);
+                let dir = self.forward * lat.cos() * lon.cos()
+                    + self.right * lat.cos() * lon.sin()
+                    + self.up * lat.sin();
+                Ray::new(self.position, dir)
+            },
+        }
+    }
+
+    fn precompute(&mut self) {
+        self.right = self.orientation * Vec3::unit_x();
+        self.up = self.orientation * Vec3::unit_y();
+        self.forward = self.orientation * Vec3::unit_z();
+        self.scale = match self.projection {
+            Projection::Perspective { fov_y } => {
+                let half = (fov_y / 2.0).tan();
+                Vec2::new(half * self.aspect, half)
+            },
+            Projection::Orthographic { height } => {
+                Vec2::new(height / 2.0 * self.aspect, height / 2.0)
+            },
+            Projection::Fisheye { fov } => Vec2::new(fov / 2.0 * self.aspect, fov / 2.0),
+            Projection::Equirectangular => Vec2::new(PI, FRAC_PI_2),
+        };
+    }
+}
+
+/// Rotation which turns +z towards a direction, and +y as close to `up` as possible.
+pub fn look_rotation(direction: Vec3<f32>, up: Vec3<f32>) -> Quaternion<f32> {
+    let forward = direction.normalized();
+    let mut right = up.cross(forward);
+    if right.magnitude_squared() < 1e-12 {
+        // looking straight along up, so any perpendicular right vector will do
+        let helper = if forward.x.abs() < 0.9 { Vec3::unit_x() } else { Vec3::unit_z() };
+        right = helper - forward * helper.dot(forward);
+    }
+    let right = right.normalized();
+    let up = forward.cross(right);
+
+    quaternion_from_basis(right, up, forward)
+}
+
+/// Convert an orthonormal basis (the images of +x, +y and +z) to a rotation.
+fn quaternion_from_basis(x: Vec3<f32>, y: Vec3<f32>, z: Vec3<f32>) -> Quaternion<f32> {
+    // rotation matrix entries, with columns being the basis vectors
+    let (m00, m01, m02) = (x.x, y.x, z.x);
+    let (m10, m11, m12) = (x.y, y.y, z.y);
+    let (m20, m21, m22) = (x.z, y.z, z.z);
+
+    let trace = m00 + m11 + m22;
+    let q = if trace > 0.0 {
+        let s = (trace + 1.0).sqrt() * 2.0;
+        Quaternion::from_xyzw((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s)
+    } else if m00 > m11 && m00 > m22 {
+        let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+        Quaternion::from_xyzw(0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s)
+    } else if m11 > m22 {
+        let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+        Quaternion::from_xyzw((m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s)
+    } else {
+        let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+        Quaternion::from_xyzw((m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s)
+    };
+    q.normalized()
+}
@@ -0,0 +1,182 @@
+use crate::Paint;
+
+use crossbeam::queue::SegQueue;
+use image::RgbaImage;
+use vek::*;
+
+/// A CPU-side buffer of pixels, which can be painted into a window.
+///
+/// Like the window, the origin is the bottom-left corner and the y axis points up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+    x_size: usize,
+    y_size: usize,
+    pixels: Vec<Rgba<u8>>,
+}
+
+impl Canvas {
+    /// Create a fully transparent canvas.
+    pub fn new(x_size: usize, y_size: usize) -> Self {
+        Canvas::filled(x_size, y_size, Rgba::zero())
+    }
+
+    /// Create a canvas filled with a single color.
+    pub fn filled(x_size: usize, y_size: usize, color: Rgba<u8>) -> Self {
+        Canvas {
+            x_size,
+            y_size,
+            pixels: vec![color; x_size * y_size],
+        }
+    }
+
+    /// Copy an image into a new canvas, flipping it so that it appears upright.
+    pub fn from_image(image: &RgbaImage) -> Self {
+        let x_size = image.width() as usize;
+        let y_size = image.height() as usize;
+        let mut canvas = Canvas::new(x_size, y_size);
+        for (x, y, p) in image.enumerate_pixels() {
+            let y = y_size - 1 - y as usize;
+            canvas.set(x as usize, y, Rgba::new(p[0], p[1], p[2], p[3]));
+        }
+        canvas
+    }
+
+    /// Copy this canvas into an image, flipping it so that it appears upright.
+    pub fn to_image(&self) -> RgbaImage {
+        RgbaImage::from_fn(self.x_size as u32, self.y_size as u32, |x, y| {
+            let y = self.y_size - 1 - y as usize;
+            let c = self.get(x as usize, y);
+            image::Rgba([c.r, c.g, c.b, c.a])
+        })
+    }
+
+    pub fn x_size(&self) -> usize {
+        self.x_size
+    }
+
+    pub fn y_size(&self) -> usize {
+        self.y_size
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        Vec2::new(self.x_size, self.y_size)
+    }
+
+    /// All pixels, in row-major order starting from the bottom row.
+    pub fn pixels(&self) -> &[Rgba<u8>] {
+        &self.pixels
+    }
+
+    /// All pixels, in row-major order starting from the bottom row.
+    pub fn pixels_mut(&mut self) -> &mut [Rgba<u8>] {
+        &mut self.pixels
+    }
+
+    /// Panics if out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Rgba<u8> {
+        assert!(x < self.x_size && y < self.y_size, "pixel out of bounds");
+        self.pixels[y * self.x_size + x]
+    }
+
+    /// Panics if out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, color: Rgba<u8>) {
+        assert!(x < self.x_size && y < self.y_size, "pixel out of bounds");
+        self.pixels[y * self.x_size + x] = color;
+    }
+
+    /// Get a pixel, or `None` if out of bounds.
+    pub fn try_get(&self, xy: Vec2<i32>) -> Option<Rgba<u8>> {
+        self.index(xy).map(|i| self.pixels[i])
+    }
+
+    /// Set a pixel, doing nothing if out of bounds.
+    pub fn put(&mut self, xy: Vec2<i32>, color: Rgba<u8>) {
+        if let Some(i) = self.index(xy) {
+            self.pixels[i] = color;
+        }
+    }
+
+    /// Alpha-blend a color over a pixel, doing nothing if out of bounds.
+    pub fn blend(&mut self, xy: Vec2<i32>, color: Rgba<u8>) {
+        if let Some(i) = self.index(xy) {
+            self.pixels[i] = blend_over(color, self.pixels[i]);
+        }
+    }
+
+    /// Set every pixel to a color.
+    pub fn fill(&mut self, color: Rgba<u8>) {
+        for p in &mut self.pixels {
+            *p = color;
+        }
+    }
+
+    /// Alpha-blend another canvas over this one, with its bottom-left corner at `offset`.
+    pub fn draw_over(&mut self, other: &Canvas, offset: Vec2<i32>) {
+        for y in 0..other.y_size {
+            for x in 0..other.x_size {
+                let src = other.get(x, y);
+                if src.a > 0 {
+                    self.blend(offset + Vec2::new(x as i32, y as i32), src);
+                }
+            }
+        }
+    }
+
+    /// Send every pixel to a paint queue.
+    pub fn paint(&self, queue: &SegQueue<Paint>) {
+        self.paint_where(queue, |_| true);
+    }
+
+    /// Send every pixel which is not fully transparent to a paint queue.
+    ///
+    /// This allows a mostly-transparent canvas to be used as an overlay layer.
+    pub fn paint_visible(&self, queue: &SegQueue<Paint>) {
+        self.paint_where(queue, |c| c.a > 0);
+    }
+
+    fn paint_where(&self, queue: &SegQueue<Paint>, filter: impl Fn(Rgba<u8>) -> bool) {
+        for y in 0..self.y_size {
+            for x in 0..self.x_size {
+                let c = self.get(x, y);
+                if filter(c) {
+                    queue.push(Paint {
+                        x,
+                        y,
+                        r: c.r,
+                        g: c.g,
+                        b: c.b,
+                        a: c.a,
+                    });
+                }
+            }
+        }
+    }
+
+    fn index(&self, xy: Vec2<i32>) -> Option<usize> {
+        if xy.x >= 0
+            && xy.y >= 0
+            && (xy.x as usize) < self.x_size
+            && (xy.y as usize) < self.y_size {
+            Some(xy.y as usize * self.x_size + xy.x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Porter-Duff "over" for non-premultiplied colors.
+pub fn blend_over(src: Rgba<u8>, dst: Rgba<u8>) -> Rgba<u8> {
+    if src.a == 0xFF {
+        return src;
+    }
+    if src.a == 0 {
+        return dst;
+    }
+
+    let src = src.map(|c| c as f32 / 255.0);
+    let dst = dst.map(|c| c as f32 / 255.0);
+    let a = src.a + dst.a * (1.0 - src.a);
+    let rgb = (src.rgb() * src.a + dst.rgb() * dst.a * (1.0 - src.a)) / a;
+    Rgba::new(rgb.r, rgb.g, rgb.b, a)
+        .map(|c| (c * 255.0 + 0.5) as u8)
+}
@@ -0,0 +1,505 @@
+use crate::{
+    canvas::Canvas,
+    frag::{self, window_caught_up},
+    open_window_with,
+    WindowConfig,
+};
+
+use std::{
+    mem,
+    sync::Arc,
+    time::Instant,
+};
+
+use rayon::prelude::*;
+use vek::*;
+
+/// The 8 cells surrounding a cell.
+pub const MOORE: [Vec2<i32>; 8] = [
+    Vec2 { x: -1, y: 1 }, Vec2 { x: 0, y: 1 }, Vec2 { x: 1, y: 1 },
+    Vec2 { x: -1, y: 0 }, Vec2 { x: 1, y: 0 },
+    Vec2 { x: -1, y: -1 }, Vec2 { x: 0, y: -1 }, Vec2 { x: 1, y: -1 },
+];
+
+/// The 4 cells sharing an edge with a cell.
+pub const VON_NEUMANN: [Vec2<i32>; 4] = [
+    Vec2 { x: 0, y: 1 },
+    Vec2 { x: -1, y: 0 }, Vec2 { x: 1, y: 0 },
+    Vec2 { x: 0, y: -1 },
+];
+
+/// What's beyond the edges of the grid.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Edges {
+    /// The grid wraps around, so opposite edges are adjacent.
+    #[default]
+    Toroidal,
+    /// Cells outside the grid are always in their default state.
+    Bounded,
+}
+
+/// How a cell changes from one generation to the next.
+pub trait Rule: Send + Sync {
+    type Cell: Copy + Default + PartialEq + Send + Sync;
+
+    /// The cell's state in the next generation, given its surroundings in this one.
+    fn next(&self, cells: &Neighborhood<Self::Cell>) -> Self::Cell;
+}
+
+/// A cell and its surroundings, as seen by a rule.
+pub struct Neighborhood<'a, C> {
+    cells: &'a [C],
+    size: Vec2<usize>,
+    edges: Edges,
+    xy: Vec2<i32>,
+    generation: u64,
+}
+
+impl<C: Copy + Default> Neighborhood<'_, C> {
+    pub fn center(&self) -> C {
+        self.get(Vec2::zero())
+    }
+
+    /// Position of the cell in the grid.
+    pub fn xy(&self) -> Vec2<i32> {
+        self.xy
+    }
+
+    /// Number of generations before this one.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// The cell at some offset from this one.
+    pub fn get(&self, offset: Vec2<i32>) -> C {
+        let xy = self.xy + offset;
+        let size = self.size.map(|c| c as i32);
+        let xy = match self.edges {
+            Edges::Toroidal => xy.map2(size, i32::rem_euclid),
+            Edges::Bounded => {
+                let inside = xy.map2(size, |c, n| c >= 0 && c < n).reduce_and();
+                if !inside {
+                    return C::default();
+                }
+                xy
+            },
+        };
+        self.cells[xy.y as usize * self.size.x + xy.x as usize]
+    }
+
+    /// How many of the cells at the offsets of a stencil, such as `MOORE`, satisfy a
+    /// predicate.
+    pub fn count_where(&self, stencil: &[Vec2<i32>], f: impl Fn(C) -> bool) -> usize {
+        stencil.iter().filter(|&&offset| f(self.get(offset))).count()
+    }
+
+    /// How many of the cells at the offsets of a stencil are in some state.
+    pub fn count(&self, stencil: &[Vec2<i32>], cell: C) -> usize
+        where
+            C: PartialEq {
+        self.count_where(stencil, |c| c == cell)
+    }
+}
+
+/// A grid of cells, double-buffered, which advances a generation at a time according to a
+/// rule.
+///
+/// Like a canvas, the origin is the bottom-left corner and the y axis points up.
+#[derive(Clone, Debug)]
+pub struct Automaton<R: Rule> {
+    rule: R,
+    edges: Edges,
+    size: Vec2<usize>,
+    cells: Vec<R::Cell>,
+    next: Vec<R::Cell>,
+    generation: u64,
+}
+
+impl<R: Rule> Automaton<R> {
+    /// A toroidal grid with every cell in its default state.
+    pub fn new(rule: R, x_size: usize, y_size: usize) -> Self {
+        let cells = vec![R::Cell::default(); x_size * y_size];
+        Automaton {
+            rule,
+            edges: Edges::Toroidal,
+            size: Vec2::new(x_size, y_size),
+            next: cells.clone(),
+            cells,
+            generation: 0,
+        }
+    }
+
+    /// Builder-style setter for the edge behavior.
+    pub fn with_edges(mut self, edges: Edges) -> Self {
+        self.edges = edges;
+        self
+    }
+
+    /// Set every cell from its coordinates.
+    pub fn fill_with(&mut self, mut f: impl FnMut(Vec2<i32>) -> R::Cell) {
+        let x_size = self.size.x.max(1);
+        for (i, cell) in self.cells.iter_mut().enumerate() {
+            *cell = f(Vec2::new(i % x_size, i / x_size).map(|c| c as i32));
+        }
+    }
+
+    pub fn rule(&self) -> &R {
+        &self.rule
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    /// Number of generations stepped.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Cells in row-major order, from the bottom-left.
+    pub fn cells(&self) -> &[R::Cell] {
+        &self.cells
+    }
+
+    /// Get a cell, or `None` if out of bounds.
+    pub fn get(&self, xy: Vec2<i32>) -> Option<R::Cell> {
+        self.index(xy).map(|i| self.cells[i])
+    }
+
+    /// Panics if out of bounds.
+    pub fn set(&mut self, xy: Vec2<i32>, cell: R::Cell) {
+        let i = self.index(xy).expect("cell out of bounds");
+        self.cells[i] = cell;
+    }
+
+    /// Advance one generation, computing the cells in parallel with rayon.
+    pub fn step(&mut self) {
+        let x_size = self.size.x.max(1);
+        let (rule, cells, size, edges, generation) =
+            (&self.rule, &self.cells, self.size, self.edges, self.generation);
+        self.next
+            .par_chunks_mut(x_size)
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, cell) in row.iter_mut().enumerate() {
+                    *cell = rule.next(&Neighborhood {
+                        cells,
+                        size,
+                        edges,
+                        xy: Vec2::new(x as i32, y as i32),
+                        generation,
+                    });
+                }
+            });
+        mem::swap(&mut self.cells, &mut self.next);
+        self.generation += 1;
+    }
+
+    /// Draw each cell as a square of pixels.
+    pub fn render(&self, palette: &Palette<R::Cell>, scale: usize) -> Canvas {
+        let scale = scale.max(1);
+        let size = self.size * scale;
+        frag::render(size.x, size.y, |xy| {
+            let xy = xy.map(|c| c as usize / scale);
+            palette.color(self.cells[xy.y * self.size.x + xy.x])
+        })
+    }
+
+    fn index(&self, xy: Vec2<i32>) -> Option<usize> {
+        let inside = xy.map2(self.size, |c, n| c >= 0 && (c as usize) < n).reduce_and();
+        if inside {
+            Some(xy.y as usize * self.size.x + xy.x as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Colors of cell states.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette<C> {
+    entries: Vec<(C, Rgba<u8>)>,
+    default: Rgba<u8>,
+}
+
+impl<C: Copy + PartialEq> Palette<C> {
+    /// A palette which colors every state the same.
+    pub fn new(default: Rgba<u8>) -> Self {
+        Palette {
+            entries: Vec::new(),
+            default,
+        }
+    }
+
+    /// Builder-style setter for the color of a state.
+    pub fn with(mut self, cell: C, color: Rgba<u8>) -> Self {
+        match self.entries.iter_mut().find(|(c, _)| *c == cell) {
+            Some(entry) => entry.1 = color,
+            None => self.entries.push((cell, color)),
+        }
+        self
+    }
+
+    pub fn color(&self, cell: C) -> Rgba<u8> {
+        self.entries.iter()
+            .find(|(c, _)| *c == cell)
+            .map_or(self.default, |&(_, color)| color)
+    }
+}
+
+/// Display an automaton in a window, advancing a generation every frame until the window
+/// closes.
+pub fn open_automaton<R>(automaton: Automaton<R>, palette: Palette<R::Cell>, scale: usize)
+    where
+        R: Rule + 'static,
+        R::Cell: 'static {
+
+    open_automaton_with(automaton, palette, scale, WindowConfig::default())
+}
+
+/// Display an automaton in a window, with non-default configuration.
+///
+/// See `open_automaton`.
+pub fn open_automaton_with<R>(
+    mut automaton: Automaton<R>,
+    palette: Palette<R::Cell>,
+    scale: usize,
+    window: WindowConfig,
+)
+    where
+        R: Rule + 'static,
+        R::Cell: 'static {
+
+    let frame_stats = Arc::clone(&window.frame_stats);
+    let input = Arc::clone(&window.input);
+    let size = automaton.size() * scale.max(1);
+
+    open_window_with(
+        size.x,
+        size.y,
+        window,
+        move |queue| {
+            while !input.is_closed() {
+                if !window_caught_up(&queue, size.x, size.y) {
+                    continue;
+                }
+
+                let start = Instant::now();
+                automaton.render(&palette, scale).paint(&queue);
+                automaton.step();
+                frame_stats.record_frame(start.elapsed());
+            }
+        }
+    );
+}
+
+/// Rules like Conway's Life, where live cells with certain numbers of live neighbors
+/// survive, and dead cells with certain numbers of live neighbors are born.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct LifeLike {
+    /// Whether a dead cell with each number of live neighbors is born.
+    pub birth: [bool; 9],
+    /// Whether a live cell with each number of live neighbors survives.
+    pub survival: [bool; 9],
+}
+
+impl LifeLike {
+    /// Conway's Game of Life, B3/S23.
+    pub fn conway() -> Self {
+        LifeLike::parse("B3/S23").unwrap()
+    }
+
+    /// Parse a rule in B/S notation, such as `B36/S23` for HighLife.
+    pub fn parse(rule: &str) -> Option<Self> {
+        let (birth, survival) = rule.split_once('/')?;
+        let birth = birth.strip_prefix('B').or_else(|| birth.strip_prefix('b'))?;
+        let survival = survival.strip_prefix('S').or_else(|| survival.strip_prefix('s'))?;
+
+        let counts = |digits: &str| {
+            let mut counts = [false; 9];
+            for c in digits.chars() {
+                *counts.get_mut(c.to_digit(10)? as usize)? = true;
+            }
+            Some(counts)
+        };
+        Some(LifeLike {
+            birth: counts(birth)?,
+            survival: counts(survival)?,
+        })
+    }
+
+    /// Live cells white, on black.
+    pub fn palette() -> Palette<bool> {
+        Palette::new(Rgba::black()).with(true, Rgba::white())
+    }
+}
+
+impl Rule for LifeLike {
+    type Cell = bool;
+
+    fn next(&self, cells: &Neighborhood<bool>) -> bool {
+        let neighbors = cells.count(&MOORE, true);
+        if cells.center() {
+            self.survival[neighbors]
+        } else {
+            self.birth[neighbors]
+        }
+    }
+}
+
+/// State of a cell in Brian's Brain.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum BrainCell {
+    #[default]
+    Off,
+    Firing,
+    /// Just fired, and can't fire again yet.
+    Refractory,
+}
+
+/// Brian's Brain, where off cells with exactly two firing neighbors fire, and fired cells
+/// rest for a generation.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BriansBrain;
+
+impl BriansBrain {
+    pub fn palette() -> Palette<BrainCell> {
+        Palette::new(Rgba::black())
+            .with(BrainCell::Firing, Rgba::white())
+            .with(BrainCell::Refractory, Rgba::new(40, 80, 200, 255))
+    }
+}
+
+impl Rule for BriansBrain {
+    type Cell = BrainCell;
+
+    fn next(&self, cells: &Neighborhood<BrainCell>) -> BrainCell {
+        match cells.center() {
+            BrainCell::Off if cells.count(&MOORE, BrainCell::Firing) == 2 => BrainCell::Firing,
+            BrainCell::Off => BrainCell::Off,
+            BrainCell::Firing => BrainCell::Refractory,
+            BrainCell::Refractory => BrainCell::Off,
+        }
+    }
+}
+
+/// State of a cell in Wireworld.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum WireCell {
+    #[default]
+    Empty,
+    /// Front of an electron.
+    Head,
+    /// Back of an electron.
+    Tail,
+    Conductor,
+}
+
+/// Wireworld, where electrons travel along conductors, which can be built into logic gates.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Wireworld;
+
+impl Wireworld {
+    pub fn palette() -> Palette<WireCell> {
+        Palette::new(Rgba::black())
+            .with(WireCell::Head, Rgba::new(60, 120, 255, 255))
+            .with(WireCell::Tail, Rgba::new(255, 80, 40, 255))
+            .with(WireCell::Conductor, Rgba::new(230, 190, 40, 255))
+    }
+}
+
+impl Rule for Wireworld {
+    type Cell = WireCell;
+
+    fn next(&self, cells: &Neighborhood<WireCell>) -> WireCell {
+        match cells.center() {
+            WireCell::Empty => WireCell::Empty,
+            WireCell::Head => WireCell::Tail,
+            WireCell::Tail => WireCell::Conductor,
+            WireCell::Conductor => match cells.count(&MOORE, WireCell::Head) {
+                1 | 2 => WireCell::Head,
+                _ => WireCell::Conductor,
+            },
+        }
+    }
+}
+
+/// State of a cell in the falling sand rule.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Particle {
+    #[default]
+    Empty,
+    Sand,
+    /// Never moves, and holds up sand.
+    Wall,
+}
+
+/// Falling sand, where sand falls down, or slides diagonally down off other sand and
+/// walls, forming piles.
+///
+/// So that two grains never move into the same cell, sand only slides one way each
+/// generation, alternating between left and right. Sand is never created or destroyed,
+/// except by falling off a bounded edge.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct FallingSand;
+
+impl FallingSand {
+    pub fn palette() -> Palette<Particle> {
+        Palette::new(Rgba::new(20, 20, 30, 255))
+            .with(Particle::Sand, Rgba::new(220, 190, 110, 255))
+            .with(Particle::Wall, Rgba::new(110, 110, 120, 255))
+    }
+
+    /// Which way sand slides in some generation, along the x axis.
+    fn slide_direction(generation: u64) -> i32 {
+        if generation.is_multiple_of(2) { -1 } else { 1 }
+    }
+
+    /// Where the grain of sand at some offset moves this generation, as an offset, if it
+    /// moves.
+    fn destination(cells: &Neighborhood<Particle>, from: Vec2<i32>) -> Option<Vec2<i32>> {
+        let below = from - Vec2::unit_y();
+        if cells.get(below) == Particle::Empty {
+            return Some(below);
+        }
+
+        // slide, unless a grain is falling straight into the same cell
+        let side = FallingSand::slide_direction(cells.generation());
+        let diagonal = below + Vec2::new(side, 0);
+        let above_diagonal = from + Vec2::new(side, 0);
+        if cells.get(diagonal) == Particle::Empty && cells.get(above_diagonal) != Particle::Sand {
+            Some(diagonal)
+        } else {
+            None
+        }
+    }
+}
+
+impl Rule for FallingSand {
+    type Cell = Particle;
+
+    fn next(&self, cells: &Neighborhood<Particle>) -> Particle {
+        let here = Vec2::zero();
+        match cells.center() {
+            Particle::Wall => Particle::Wall,
+            Particle::Sand => match FallingSand::destination(cells, here) {
+                Some(_) => Particle::Empty,
+                None => Particle::Sand,
+            },
+            Particle::Empty => {
+                // grains can arrive from directly above, or diagonally above
+                let side = FallingSand::slide_direction(cells.generation());
+                let sources = [Vec2::new(0, 1), Vec2::new(-side, 1)];
+                let arriving = sources.iter().any(|&source| {
+                    cells.get(source) == Particle::Sand
+                        && FallingSand::destination(cells, source) == Some(here)
+                });
+                if arriving {
+                    Particle::Sand
+                } else {
+                    Particle::Empty
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,528 @@
+use crate::{
+    canvas::Canvas,
+    post::Effect,
+};
+
+use std::sync::OnceLock;
+
+use image::RgbaImage;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use vek::*;
+
+/// A 2D buffer of colors which can be dithered, such as a canvas or an image.
+///
+/// Coordinates are the buffer's own, so a canvas is dithered from the bottom row up, and an
+/// image from the top row down.
+pub trait Pixels {
+    fn size(&self) -> Vec2<usize>;
+
+    /// Panics if out of bounds.
+    fn pixel(&self, xy: Vec2<usize>) -> Rgba<u8>;
+
+    /// Panics if out of bounds.
+    fn set_pixel(&mut self, xy: Vec2<usize>, color: Rgba<u8>);
+
+    /// Every pixel, in row-major order.
+    fn colors(&self) -> Vec<Rgba<u8>> {
+        let size = self.size();
+        let mut colors = Vec::with_capacity(size.product());
+        for y in 0..size.y {
+            for x in 0..size.x {
+                colors.push(self.pixel(Vec2::new(x, y)));
+            }
+        }
+        colors
+    }
+}
+
+impl Pixels for Canvas {
+    fn size(&self) -> Vec2<usize> {
+        Canvas::size(self)
+    }
+
+    fn pixel(&self, xy: Vec2<usize>) -> Rgba<u8> {
+        self.get(xy.x, xy.y)
+    }
+
+    fn set_pixel(&mut self, xy: Vec2<usize>, color: Rgba<u8>) {
+        self.set(xy.x, xy.y, color)
+    }
+}
+
+impl Pixels for RgbaImage {
+    fn size(&self) -> Vec2<usize> {
+        Vec2::new(self.width() as usize, self.height() as usize)
+    }
+
+    fn pixel(&self, xy: Vec2<usize>) -> Rgba<u8> {
+        let p = self.get_pixel(xy.x as u32, xy.y as u32);
+        Rgba::new(p[0], p[1], p[2], p[3])
+    }
+
+    fn set_pixel(&mut self, xy: Vec2<usize>, color: Rgba<u8>) {
+        self.put_pixel(xy.x as u32, xy.y as u32, image::Rgba(color.into_array()));
+    }
+}
+
+/// The colors which quantization reduces an image to. Alpha is kept as it is.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Quantizer {
+    /// Some number of evenly spaced levels per channel, such as 2 for 1 bit per channel.
+    /// Fewer than 2 is treated as 2.
+    Levels(u8),
+    /// The colors of a palette, such as one from `median_cut` or `k_means`.
+    ///
+    /// Panics when quantizing if the palette is empty.
+    Palette(Vec<Rgba<u8>>),
+}
+
+impl Quantizer {
+    /// The nearest allowed color, without dithering.
+    pub fn quantize(&self, color: Rgba<u8>) -> Rgba<u8> {
+        with_rgb(color, self.nearest(to_f32(color)))
+    }
+
+    /// The nearest allowed color after offsetting it by an ordered dithering threshold in
+    /// `[0, 1)`, such as from `Bayer::threshold` or `blue_noise`.
+    ///
+    /// This is how to dither per fragment.
+    pub fn ordered(&self, color: Rgba<u8>, threshold: f32) -> Rgba<u8> {
+        let offset = (threshold - 0.5) * self.spacing();
+        with_rgb(color, self.nearest(to_f32(color) + offset))
+    }
+
+    /// The nearest allowed color to an RGB color in `[0, 255]`.
+    fn nearest(&self, rgb: Rgb<f32>) -> Rgb<f32> {
+        match self {
+            &Quantizer::Levels(levels) => {
+                let steps = (levels.max(2) - 1) as f32;
+                rgb.map(|c| (c.clamp(0.0, 255.0) / 255.0 * steps).round() / steps * 255.0)
+            },
+            Quantizer::Palette(colors) => {
+                let colors = colors.iter().map(|&c| to_f32(c));
+                colors.min_by(|&a, &b| {
+                    distance_squared(a, rgb).partial_cmp(&distance_squared(b, rgb)).unwrap()
+                }).expect("quantizing with an empty palette")
+            },
+        }
+    }
+
+    /// Typical distance between neighboring allowed values of a channel, which ordered
+    /// dithering spreads colors across.
+    fn spacing(&self) -> f32 {
+        match self {
+            &Quantizer::Levels(levels) => 255.0 / (levels.max(2) - 1) as f32,
+            // as if the colors were evenly spaced levels
+            Quantizer::Palette(colors) => 255.0 / ((colors.len() as f32).cbrt() - 1.0).max(1.0),
+        }
+    }
+}
+
+/// Bayer matrices for ordered dithering, which give a regular crosshatched pattern.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Bayer {
+    X2,
+    X4,
+    X8,
+}
+
+impl Bayer {
+    /// Width and height of the matrix.
+    pub fn size(self) -> usize {
+        match self {
+            Bayer::X2 => 2,
+            Bayer::X4 => 4,
+            Bayer::X8 => 8,
+        }
+    }
+
+    /// The threshold for a pixel in `[0, 1)`, with the matrix tiled across the image.
+    pub fn threshold(self, xy: Vec2<i32>) -> f32 {
+        let size = self.size() as i32;
+        let xy = xy.map(|c| c.rem_euclid(size) as usize);
+
+        // each bit of the coordinates picks a quadrant of the 2x2 matrix, and the lowest
+        // bits are the most significant
+        let mut rank = 0;
+        let mut bit = 1;
+        while bit < size as usize {
+            let quadrant = xy.map(|c| (c & bit != 0) as usize);
+            rank = rank * 4 + [[0, 2], [3, 1]][quadrant.y][quadrant.x];
+            bit *= 2;
+        }
+        (rank as f32 + 0.5) / (size * size) as f32
+    }
+}
+
+/// Width and height of the tile of blue noise.
+pub const BLUE_NOISE_SIZE: usize = 64;
+
+/// The blue noise threshold for a pixel in `[0, 1)`, with a tile of it repeated across the
+/// image.
+///
+/// Blue noise has no low frequencies, so dithering with it looks like fine, even grain
+/// with no visible pattern. The tile is generated the first time this is called.
+pub fn blue_noise(xy: Vec2<i32>) -> f32 {
+    static TILE: OnceLock<Vec<f32>> = OnceLock::new();
+    let tile = TILE.get_or_init(|| void_and_cluster(BLUE_NOISE_SIZE, 0xB1CE));
+    let xy = xy.map(|c| c.rem_euclid(BLUE_NOISE_SIZE as i32) as usize);
+    tile[xy.y * BLUE_NOISE_SIZE + xy.x]
+}
+
+/// Generate a tileable blue noise threshold map with Ulichney's void-and-cluster method.
+fn void_and_cluster(size: usize, seed: u64) -> Vec<f32> {
+    let len = size * size;
+    let mut pattern = Pattern::new(size);
+
+    // start with random points, and move them until they're evenly spread
+    let mut rng = StdRng::seed_from_u64(seed);
+    let initial = len / 10;
+    while pattern.count < initial {
+        let i = rng.gen_range(0, len);
+        if !pattern.ones[i] {
+            pattern.toggle(i);
+        }
+    }
+    for _ in 0..len {
+        let cluster = pattern.tightest_cluster();
+        pattern.toggle(cluster);
+        let void = pattern.largest_void();
+        pattern.toggle(void);
+        if void == cluster {
+            break;
+        }
+    }
+
+    // rank the initial points by removing them from the tightest cluster, and the rest by
+    // adding them to the largest void
+    let mut ranks = vec![0; len];
+    let mut removing = pattern.clone();
+    while removing.count > 0 {
+        let i = removing.tightest_cluster();
+        removing.toggle(i);
+        ranks[i] = removing.count;
+    }
+    while pattern.count < len {
+        let i = pattern.largest_void();
+        ranks[i] = pattern.count;
+        pattern.toggle(i);
+    }
+
+    ranks.iter().map(|&rank| (rank as f32 + 0.5) / len as f32).collect()
+}
+
+/// A binary pattern, and how crowded each of its pixels is by the ones, for
+/// `void_and_cluster`.
+#[derive(Clone)]
+struct Pattern {
+    size: usize,
+    ones: Vec<bool>,
+    count: usize,
+    /// Sum of a gaussian of the distance to each one, wrapping around the edges.
+    energy: Vec<f32>,
+    /// The gaussian, by offset.
+    kernel: Vec<f32>,
+}
+
+impl Pattern {
+    fn new(size: usize) -> Self {
+        let sigma: f32 = 1.5;
+        let kernel = (0..size * size)
+            .map(|i| {
+                let d = Vec2::new(i % size, i / size).map(|c| c.min(size - c) as f32);
+                (-d.magnitude_squared() / (2.0 * sigma * sigma)).exp()
+            })
+            .collect();
+        Pattern {
+            size,
+            ones: vec![false; size * size],
+            count: 0,
+            energy: vec![0.0; size * size],
+            kernel,
+        }
+    }
+
+    fn toggle(&mut self, i: usize) {
+        let sign = if self.ones[i] { -1.0 } else { 1.0 };
+        self.ones[i] = !self.ones[i];
+        if self.ones[i] {
+            self.count += 1;
+        } else {
+            self.count -= 1;
+        }
+
+        let size = self.size;
+        let (x, y) = (i % size, i / size);
+        for (j, energy) in self.energy.iter_mut().enumerate() {
+            let dx = (j % size + size - x) % size;
+            let dy = (j / size + size - y) % size;
+            *energy += sign * self.kernel[dy * size + dx];
+        }
+    }
+
+    /// The most crowded one.
+    fn tightest_cluster(&self) -> usize {
+        self.most(true, |a, b| a > b)
+    }
+
+    /// The least crowded zero.
+    ///
+    /// This is also the most crowded zero by the other zeros, since the energy from every
+    /// pixel together is the same everywhere.
+    fn largest_void(&self) -> usize {
+        self.most(false, |a, b| a < b)
+    }
+
+    fn most(&self, one: bool, better: impl Fn(f32, f32) -> bool) -> usize {
+        let mut best: Option<usize> = None;
+        for (i, &energy) in self.energy.iter().enumerate() {
+            if self.ones[i] == one && best.is_none_or(|b| better(energy, self.energy[b])) {
+                best = Some(i);
+            }
+        }
+        best.expect("no pixels to choose from")
+    }
+}
+
+/// How quantization error is spread to neighboring pixels: offset to the right and to the
+/// following rows, and the fraction of the error.
+type DiffusionKernel = [(i32, i32, f32)];
+
+const FLOYD_STEINBERG: &DiffusionKernel = &[
+    (1, 0, 7.0 / 16.0),
+    (-1, 1, 3.0 / 16.0),
+    (0, 1, 5.0 / 16.0),
+    (1, 1, 1.0 / 16.0),
+];
+
+/// Only three quarters of the error is spread, which keeps more contrast.
+const ATKINSON: &DiffusionKernel = &[
+    (1, 0, 1.0 / 8.0),
+    (2, 0, 1.0 / 8.0),
+    (-1, 1, 1.0 / 8.0),
+    (0, 1, 1.0 / 8.0),
+    (1, 1, 1.0 / 8.0),
+    (0, 2, 1.0 / 8.0),
+];
+
+/// How to distribute the error of quantizing colors.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Dither {
+    /// Quantize each pixel to the nearest color, without dithering.
+    Nearest,
+    /// Ordered dithering with a Bayer matrix.
+    Bayer(Bayer),
+    /// Ordered dithering with blue noise. See `blue_noise`.
+    BlueNoise,
+    /// Floyd-Steinberg error diffusion.
+    FloydSteinberg,
+    /// Atkinson error diffusion, as on the original Macintosh.
+    Atkinson,
+}
+
+impl Dither {
+    /// Quantize the pixels in place.
+    ///
+    /// Error diffusion goes through the pixels in order, so can't be done per fragment.
+    pub fn apply<P: Pixels + ?Sized>(self, pixels: &mut P, quantizer: &Quantizer) {
+        match self {
+            Dither::Nearest => map_pixels(pixels, |_, color| quantizer.quantize(color)),
+            Dither::Bayer(bayer) => map_pixels(pixels, |xy, color| {
+                quantizer.ordered(color, bayer.threshold(xy))
+            }),
+            Dither::BlueNoise => map_pixels(pixels, |xy, color| {
+                quantizer.ordered(color, blue_noise(xy))
+            }),
+            Dither::FloydSteinberg => diffuse(pixels, quantizer, FLOYD_STEINBERG),
+            Dither::Atkinson => diffuse(pixels, quantizer, ATKINSON),
+        }
+    }
+}
+
+fn map_pixels<P, F>(pixels: &mut P, f: F)
+    where
+        P: Pixels + ?Sized,
+        F: Fn(Vec2<i32>, Rgba<u8>) -> Rgba<u8> {
+
+    let size = pixels.size();
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let xy = Vec2::new(x, y);
+            let color = f(xy.map(|c| c as i32), pixels.pixel(xy));
+            pixels.set_pixel(xy, color);
+        }
+    }
+}
+
+fn diffuse<P>(pixels: &mut P, quantizer: &Quantizer, kernel: &DiffusionKernel)
+    where
+        P: Pixels + ?Sized {
+
+    let size = pixels.size();
+    let mut error = vec![Rgb::<f32>::zero(); size.product()];
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let xy = Vec2::new(x, y);
+            let color = pixels.pixel(xy);
+            let wanted = (to_f32(color) + error[y * size.x + x]).map(|c| c.clamp(0.0, 255.0));
+            let got = quantizer.nearest(wanted);
+            pixels.set_pixel(xy, with_rgb(color, got));
+
+            for &(dx, dy, weight) in kernel {
+                let (x, y) = (x as i32 + dx, y + dy as usize);
+                if x >= 0 && (x as usize) < size.x && y < size.y {
+                    error[y * size.x + x as usize] += (wanted - got) * weight;
+                }
+            }
+        }
+    }
+}
+
+/// Reduces a canvas's colors, with dithering, as a post-processing effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Quantize {
+    pub quantizer: Quantizer,
+    pub dither: Dither,
+}
+
+impl Quantize {
+    pub fn new(quantizer: Quantizer, dither: Dither) -> Self {
+        Quantize { quantizer, dither }
+    }
+}
+
+impl Effect for Quantize {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let mut canvas = canvas.clone();
+        self.dither.apply(&mut canvas, &self.quantizer);
+        canvas
+    }
+}
+
+/// Choose a palette of up to some number of colors for some colors, by repeatedly splitting
+/// the box of colors with the widest range in any channel at its median.
+///
+/// Fully transparent colors are ignored, and the palette colors are opaque.
+pub fn median_cut(colors: &[Rgba<u8>], palette_len: usize) -> Vec<Rgba<u8>> {
+    let colors: Vec<Rgb<u8>> = colors.iter()
+        .filter(|c| c.a > 0)
+        .map(|c| c.rgb())
+        .collect();
+    if colors.is_empty() || palette_len == 0 {
+        return Vec::new();
+    }
+
+    let mut boxes = vec![colors];
+    while boxes.len() < palette_len {
+        let (i, channel, range) = boxes.iter()
+            .enumerate()
+            .map(|(i, colors)| {
+                let (channel, range) = widest_channel(colors);
+                (i, channel, range)
+            })
+            .max_by_key(|&(_, _, range)| range)
+            .unwrap();
+        if range == 0 {
+            // every box is a single color
+            break;
+        }
+
+        let mut lower = boxes.swap_remove(i);
+        lower.sort_unstable_by_key(|c| c[channel]);
+        let upper = lower.split_off(lower.len() / 2);
+        boxes.push(lower);
+        boxes.push(upper);
+    }
+
+    boxes.iter()
+        .map(|colors| {
+            let sum = colors.iter().fold(Rgb::<u32>::zero(), |sum, &c| sum + c.map(u32::from));
+            let mean = sum.map(|c| ((c as f32 / colors.len() as f32).round()) as u8);
+            Rgba::from_opaque(mean)
+        })
+        .collect()
+}
+
+/// Which channel of some colors has the widest range, and the range.
+fn widest_channel(colors: &[Rgb<u8>]) -> (usize, u8) {
+    (0..3)
+        .map(|channel| {
+            let values = colors.iter().map(|c| c[channel]);
+            let range = values.clone().max().unwrap() - values.min().unwrap();
+            (channel, range)
+        })
+        .max_by_key(|&(_, range)| range)
+        .unwrap()
+}
+
+/// Choose a palette of up to some number of colors for some colors, with k-means
+/// clustering starting from the `median_cut` palette.
+///
+/// This is slower, but usually closer to the colors. It stops early if the palette stops
+/// changing. Fully transparent colors are ignored, and the palette colors are opaque.
+pub fn k_means(colors: &[Rgba<u8>], palette_len: usize, iterations: usize) -> Vec<Rgba<u8>> {
+    let mut centers: Vec<Rgb<f32>> = median_cut(colors, palette_len).into_iter()
+        .map(to_f32)
+        .collect();
+    if centers.is_empty() {
+        return Vec::new();
+    }
+    let colors: Vec<Rgb<f32>> = colors.iter()
+        .filter(|c| c.a > 0)
+        .map(|&c| to_f32(c))
+        .collect();
+
+    for _ in 0..iterations {
+        // sum and count of the colors nearest each center
+        let empty = || vec![(Rgb::<f32>::zero(), 0); centers.len()];
+        let sums = colors.par_iter()
+            .fold(empty, |mut sums, &color| {
+                let nearest = (0..centers.len())
+                    .min_by(|&a, &b| {
+                        let a = distance_squared(centers[a], color);
+                        a.partial_cmp(&distance_squared(centers[b], color)).unwrap()
+                    })
+                    .unwrap();
+                sums[nearest].0 += color;
+                sums[nearest].1 += 1;
+                sums
+            })
+            .reduce(empty, |mut a, b| {
+                for (a, b) in a.iter_mut().zip(b) {
+                    a.0 += b.0;
+                    a.1 += b.1;
+                }
+                a
+            });
+
+        let mut moved = false;
+        for (center, (sum, count)) in centers.iter_mut().zip(sums) {
+            if count > 0 {
+                let mean = sum / count as f32;
+                moved |= mean != *center;
+                *center = mean;
+            }
+        }
+        if !moved {
+            break;
+        }
+    }
+
+    centers.into_iter()
+        .map(|c| Rgba::from_opaque(c.map(|c| c.round() as u8)))
+        .collect()
+}
+
+fn to_f32(color: Rgba<u8>) -> Rgb<f32> {
+    color.rgb().map(|c| c as f32)
+}
+
+fn distance_squared(a: Rgb<f32>, b: Rgb<f32>) -> f32 {
+    (a - b).map(|c| c * c).sum()
+}
+
+/// A color with the alpha of another.
+fn with_rgb(color: Rgba<u8>, rgb: Rgb<f32>) -> Rgba<u8> {
+    Rgba::from_translucent(rgb.map(|c| c.round() as u8), color.a)
+}
@@ -1,10 +1,125 @@
 
-use crate::{open_window, Paint};
+use crate::{
+    canvas::Canvas,
+    graph::{BufferId, GraphError, RenderGraph},
+    indexed::IndexedCanvas,
+    packet::{Lanes, PixelPacket, SupportedLanes},
+    post::Effect,
+    texture::Wrap,
+    timestep::{Accumulator, FixedTimestep},
+    open_window_with,
+    WindowConfig,
+    Paint,
+    Input,
+};
 
-use rayon::prelude::*;
+use crossbeam::queue::SegQueue;
+use rayon::{prelude::*, ThreadPool, ThreadPoolBuilder};
 use vek::*;
 
-use std::time::{Instant, Duration};
+use std::{
+    mem,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How fragments are evaluated.
+#[derive(Clone, Debug, Default)]
+pub enum Execution {
+    /// In parallel on rayon's global thread pool.
+    #[default]
+    Global,
+    /// In parallel on a custom thread pool.
+    Pool(Arc<ThreadPool>),
+    /// In parallel on a new thread pool with some number of threads.
+    Threads(usize),
+    /// One at a time on the calling thread, in row-major order starting from the
+    /// bottom-left, for debugging and determinism.
+    Sequential,
+}
+
+impl Execution {
+    /// Build the thread pool if this is `Threads`, so that it can be reused across
+    /// renders.
+    pub fn resolve(self) -> Self {
+        match self {
+            Execution::Threads(threads) => {
+                let pool = ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .build()
+                    .expect("failed to build thread pool");
+                Execution::Pool(Arc::new(pool))
+            },
+            execution => execution,
+        }
+    }
+
+    /// Call a function for every pixel of the canvas.
+    fn for_each_fragment(&self, x_size: usize, y_size: usize, f: impl Fn(usize, usize) + Sync) {
+        let parallel = || (0..x_size).into_par_iter()
+            .flat_map(|x| (0..y_size).into_par_iter()
+                .map(move |y| (x, y)))
+            .for_each(|(x, y)| f(x, y));
+
+        match self {
+            Execution::Global => parallel(),
+            Execution::Pool(pool) => pool.install(parallel),
+            Execution::Threads(_) => self.clone().resolve().for_each_fragment(x_size, y_size, f),
+            Execution::Sequential => {
+                for y in 0..y_size {
+                    for x in 0..x_size {
+                        f(x, y);
+                    }
+                }
+            },
+        }
+    }
+}
+
+/// Configuration for launching a fragment window.
+#[derive(Clone, Debug, Default)]
+pub struct FragmentOptions {
+    pub execution: Execution,
+    pub window: WindowConfig,
+    /// Effect run on each frame before it's presented.
+    ///
+    /// With an effect, each frame is rendered into a canvas before any of it is shown,
+    /// rather than shown as fragments finish.
+    pub post: Option<Arc<dyn Effect>>,
+}
+
+impl FragmentOptions {
+    /// Builder-style setter to run on a custom thread pool.
+    pub fn with_pool(mut self, pool: Arc<ThreadPool>) -> Self {
+        self.execution = Execution::Pool(pool);
+        self
+    }
+
+    /// Builder-style setter to run on a new thread pool with some number of threads.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.execution = Execution::Threads(threads);
+        self
+    }
+
+    /// Builder-style setter to evaluate fragments one at a time, in a fixed order.
+    pub fn sequential(mut self) -> Self {
+        self.execution = Execution::Sequential;
+        self
+    }
+
+    /// Builder-style setter for the post-processing effect.
+    pub fn with_post(mut self, effect: impl Effect + 'static) -> Self {
+        self.post = Some(Arc::new(effect));
+        self
+    }
+
+    /// Builder-style setter for the window configuration.
+    pub fn with_window(mut self, window: WindowConfig) -> Self {
+        self.window = window;
+        self
+    }
+}
 
 /// Launch a window with the given function for computing a fragment color.
 ///
@@ -38,53 +153,760 @@ pub fn fragment_stateful<S, F>(
         F: Send + Sync + 'static,
         F: Fn(Vec2<i32>, &S) -> Rgba<u8> {
 
+    // delegate
+    fragment_stateful_with(
+        x_size,
+        y_size,
+        state,
+        FragmentOptions::default(),
+        fragment,
+    )
+}
+
+/// Launch a window with the given function for computing a fragment color, with
+/// non-default configuration.
+///
+/// See `fragment_stateful`.
+pub fn fragment_stateful_with<S, F>(
+    x_size: usize,
+    y_size: usize,
+    state: S,
+    options: FragmentOptions,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        F: Send + Sync + 'static,
+        F: Fn(Vec2<i32>, &S) -> Rgba<u8> {
+
+    run_frames(x_size, y_size, options, move |execution, paint| {
+        execution.for_each_fragment(x_size, y_size, |x, y| {
+            paint(x, y, fragment(Vec2::new(x as i32, y as i32), &state));
+        });
+    });
+}
+
+/// Launch a window with the given function for computing the colors of packets of `N`
+/// horizontally adjacent fragments at once. The fragment function will have read-access
+/// to some shared state.
+///
+/// This lets the fragment function process several pixels at once with SIMD, using the
+/// types in `packet`. `N` may be 4, 8 or 16. See `fragment_stateful`.
+pub fn fragment_packets<S, F, const N: usize>(
+    x_size: usize,
+    y_size: usize,
+    state: S,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        F: Send + Sync + 'static,
+        F: Fn(PixelPacket<N>, &S) -> [Rgba<u8>; N],
+        Lanes<N>: SupportedLanes {
+
+    // delegate
+    fragment_packets_with(
+        x_size,
+        y_size,
+        state,
+        FragmentOptions::default(),
+        fragment,
+    )
+}
+
+/// Launch a window with the given function for computing the colors of packets of
+/// fragments, with non-default configuration.
+///
+/// See `fragment_packets`.
+pub fn fragment_packets_with<S, F, const N: usize>(
+    x_size: usize,
+    y_size: usize,
+    state: S,
+    options: FragmentOptions,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        F: Send + Sync + 'static,
+        F: Fn(PixelPacket<N>, &S) -> [Rgba<u8>; N],
+        Lanes<N>: SupportedLanes {
+
+    run_frames(x_size, y_size, options, move |execution, paint| {
+        execution.for_each_fragment(x_size.div_ceil(N), y_size, |packet_x, y| {
+            let packet = PixelPacket::new(packet_x * N, y, x_size);
+            let colors = fragment(packet, &state);
+            for (i, &color) in colors.iter().enumerate().take(packet.mask.count()) {
+                paint(packet_x * N + i, y, color);
+            }
+        });
+    });
+}
+
+/// What an update hook knows about the frame it's preparing.
+#[derive(Clone, Debug)]
+pub struct FrameContext {
+    /// Number of frames before this one.
+    pub frame: u64,
+    /// Time since the first frame.
+    pub time: Duration,
+    /// Time since the previous frame, or zero for the first frame.
+    pub delta: Duration,
+    /// Canvas size in pixels.
+    pub size: Vec2<usize>,
+    /// Input to the window.
+    pub input: Input,
+}
+
+/// Counts frames, and times them for their `FrameContext`s.
+#[derive(Clone, Debug, Default)]
+pub struct FrameClock {
+    /// When the first and previous frames started.
+    started: Option<(Instant, Instant)>,
+    frame: u64,
+}
+
+impl FrameClock {
+    pub fn new() -> Self {
+        FrameClock::default()
+    }
+
+    /// Context for the next frame, which starts at some instant.
+    pub fn next_frame(&mut self, now: Instant, size: Vec2<usize>, input: Input) -> FrameContext {
+        let (first, previous) = self.started.unwrap_or((now, now));
+        self.started = Some((first, now));
+        let context = FrameContext {
+            frame: self.frame,
+            time: now.saturating_duration_since(first),
+            delta: now.saturating_duration_since(previous),
+            size,
+            input,
+        };
+        self.frame += 1;
+        context
+    }
+}
+
+/// Launch a window which runs an update/render loop on some state, until the window closes.
+///
+/// Before each frame, `update` is called with mutable access to the state, along with the
+/// frame's input, time and size. Then every fragment is computed in parallel with read
+/// access to the updated state.
+pub fn fragment_update<S, U, F>(
+    x_size: usize,
+    y_size: usize,
+    state: S,
+    update: U,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        U: FnMut(&mut S, &FrameContext) + Send + 'static,
+        F: Fn(Vec2<i32>, &S) -> Rgba<u8> + Send + Sync + 'static {
+
+    // delegate
+    fragment_update_with(
+        x_size,
+        y_size,
+        state,
+        FragmentOptions::default(),
+        update,
+        fragment,
+    )
+}
+
+/// Launch a window which runs an update/render loop on some state, with non-default
+/// configuration.
+///
+/// See `fragment_update`.
+pub fn fragment_update_with<S, U, F>(
+    x_size: usize,
+    y_size: usize,
+    mut state: S,
+    options: FragmentOptions,
+    mut update: U,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        U: FnMut(&mut S, &FrameContext) + Send + 'static,
+        F: Fn(Vec2<i32>, &S) -> Rgba<u8> + Send + Sync + 'static {
+
+    let FragmentOptions { execution, window, post } = options;
+    let frame_stats = Arc::clone(&window.frame_stats);
+    let input = Arc::clone(&window.input);
+
+    open_window_with(
+        x_size,
+        y_size,
+        window,
+        move |queue| {
+
+            let execution = execution.resolve();
+
+            let mut clock = FrameClock::new();
+
+            while !input.is_closed() {
+                if !window_caught_up(&queue, x_size, y_size) {
+                    continue;
+                }
+
+                let now = Instant::now();
+                let size = Vec2::new(x_size, y_size);
+                let context = clock.next_frame(now, size, input.snapshot());
+
+                update(&mut state, &context);
+
+                let post = post.as_deref();
+                paint_frame(&execution, x_size, y_size, post, &queue, |xy| fragment(xy, &state));
+
+                frame_stats.record_frame(now.elapsed());
+            }
+        }
+    );
+}
+
+/// What a simulation tick knows about itself.
+#[derive(Clone, Debug)]
+pub struct TickContext {
+    /// Number of ticks before this one.
+    pub tick: u64,
+    /// Simulated time per tick.
+    pub dt: Duration,
+    /// Input to the window since the previous tick.
+    ///
+    /// Input is collected once per frame, and given to the first tick run before that
+    /// frame. Later ticks before the same frame see the same keys held down, but no new
+    /// presses or mouse movement.
+    pub input: Input,
+}
+
+/// Launch a window which runs a simulation at a fixed timestep, and renders it as fast as
+/// it can, until the window closes.
+///
+/// Before each frame, `tick` is called as many times as needed to catch the simulation up
+/// with real time. Then every fragment is computed in parallel with read access to the
+/// state, and the fraction of a tick which has passed since the last one, for
+/// interpolation.
+///
+/// The simulation only depends on the number of ticks and the input, not on how long
+/// frames take to render. See `FixedTimestep` for what happens when rendering falls
+/// behind.
+///
+/// Panics if the timestep's tick is zero.
+pub fn fragment_fixed<S, T, F>(
+    x_size: usize,
+    y_size: usize,
+    state: S,
+    timestep: FixedTimestep,
+    tick: T,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        T: FnMut(&mut S, &TickContext) + Send + 'static,
+        F: Fn(Vec2<i32>, &S, f32) -> Rgba<u8> + Send + Sync + 'static {
+
+    // delegate
+    fragment_fixed_with(
+        x_size,
+        y_size,
+        state,
+        FragmentOptions::default(),
+        timestep,
+        tick,
+        fragment,
+    )
+}
+
+/// Launch a window which runs a simulation at a fixed timestep, with non-default
+/// configuration.
+///
+/// See `fragment_fixed`.
+pub fn fragment_fixed_with<S, T, F>(
+    x_size: usize,
+    y_size: usize,
+    mut state: S,
+    options: FragmentOptions,
+    timestep: FixedTimestep,
+    mut tick: T,
+    fragment: F,
+)
+    where
+        S: Send + Sync + 'static,
+        T: FnMut(&mut S, &TickContext) + Send + 'static,
+        F: Fn(Vec2<i32>, &S, f32) -> Rgba<u8> + Send + Sync + 'static {
+
+    let FragmentOptions { execution, window, post } = options;
+    let frame_stats = Arc::clone(&window.frame_stats);
+    let input = Arc::clone(&window.input);
+    let mut accumulator = Accumulator::new(timestep);
+
+    open_window_with(
+        x_size,
+        y_size,
+        window,
+        move |queue| {
+
+            let execution = execution.resolve();
+
+            let mut previous = Instant::now();
+
+            while !input.is_closed() {
+                if !window_caught_up(&queue, x_size, y_size) {
+                    continue;
+                }
+
+                let now = Instant::now();
+                let due = accumulator.advance(now - previous);
+                previous = now;
+
+                let first_tick = accumulator.ticks() - due as u64;
+                let mut tick_input = if due > 0 { input.snapshot() } else { Input::default() };
+                for i in 0..due as u64 {
+                    let context = TickContext {
+                        tick: first_tick + i,
+                        dt: timestep.tick,
+                        input: tick_input.clone(),
+                    };
+                    tick(&mut state, &context);
+                    tick_input.clear_accumulated();
+                }
+
+                let alpha = accumulator.alpha();
+                let post = post.as_deref();
+                paint_frame(&execution, x_size, y_size, post, &queue, |xy| {
+                    fragment(xy, &state, alpha)
+                });
+
+                frame_stats.record_frame(now.elapsed());
+            }
+        }
+    );
+}
+
+/// Launch a window which executes a render graph every frame, until the window closes,
+/// and displays one of its buffers. The window is the size of that buffer.
+///
+/// Returns an error without opening the window if the graph is invalid, or no pass writes
+/// the output buffer.
+pub fn fragment_graph(
+    graph: RenderGraph,
+    output: BufferId<Rgba<u8>>,
+) -> Result<(), GraphError> {
+    // delegate
+    fragment_graph_with(graph, output, FragmentOptions::default())
+}
+
+/// Launch a window which executes a render graph every frame, with non-default
+/// configuration.
+///
+/// The graph's passes always run on rayon's global thread pool, so the execution option is
+/// ignored. See `fragment_graph`.
+pub fn fragment_graph_with(
+    mut graph: RenderGraph,
+    output: BufferId<Rgba<u8>>,
+    options: FragmentOptions,
+) -> Result<(), GraphError> {
+    graph.compile_output(output)?;
+
+    let FragmentOptions { window, post, .. } = options;
+    let frame_stats = Arc::clone(&window.frame_stats);
+    let input = Arc::clone(&window.input);
+    let size = graph.buffer_size(output);
+
+    open_window_with(
+        size.x,
+        size.y,
+        window,
+        move |queue| {
+            while !input.is_closed() {
+                if !window_caught_up(&queue, size.x, size.y) {
+                    continue;
+                }
+
+                let start = Instant::now();
+                let canvas = graph.render(output).expect("graph was compiled");
+                match &post {
+                    Some(post) => post.apply(&canvas).paint(&queue),
+                    None => canvas.paint(&queue),
+                }
+                frame_stats.record_frame(start.elapsed());
+            }
+        }
+    );
+    Ok(())
+}
+
+/// The previous frame, as seen by a feedback fragment function.
+#[derive(Copy, Clone, Debug)]
+pub struct PreviousFrame<'a> {
+    canvas: &'a Canvas,
+    frame: u64,
+}
+
+impl<'a> PreviousFrame<'a> {
+    /// Get a pixel, wrapping coordinates outside the canvas around to the other side.
+    pub fn wrapped(&self, xy: Vec2<i32>) -> Rgba<u8> {
+        self.get_with(xy, Wrap::Repeat)
+    }
+
+    /// Get a pixel, clamping coordinates outside the canvas to its edges.
+    pub fn clamped(&self, xy: Vec2<i32>) -> Rgba<u8> {
+        self.get_with(xy, Wrap::Clamp)
+    }
+
+    /// Get a pixel, mapping coordinates outside the canvas back into it somehow.
+    pub fn get_with(&self, xy: Vec2<i32>, wrap: Wrap) -> Rgba<u8> {
+        let x = wrap.apply(xy.x, self.canvas.x_size());
+        let y = wrap.apply(xy.y, self.canvas.y_size());
+        self.canvas.get(x, y)
+    }
+
+    pub fn canvas(&self) -> &'a Canvas {
+        self.canvas
+    }
+
+    /// Number of frames before the one being drawn.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+/// Double-buffered canvases, for fragment functions which compute each frame from the
+/// previous one.
+#[derive(Clone, Debug)]
+pub struct Feedback {
+    current: Canvas,
+    next: Canvas,
+    frame: u64,
+}
+
+impl Feedback {
+    /// Start with some canvas as the previous frame.
+    pub fn new(initial: Canvas) -> Self {
+        Feedback {
+            next: initial.clone(),
+            current: initial,
+            frame: 0,
+        }
+    }
+
+    /// The latest frame.
+    pub fn current(&self) -> &Canvas {
+        &self.current
+    }
+
+    /// Number of frames drawn.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Draw the next frame, with read access to the current one.
+    ///
+    /// This uses rayon for parallelism.
+    pub fn step<F>(&mut self, fragment: F)
+        where
+            F: Fn(Vec2<i32>, &PreviousFrame) -> Rgba<u8> + Sync {
+
+        self.step_with(&Execution::Global, fragment)
+    }
+
+    /// Draw the next frame, with some execution strategy.
+    pub fn step_with<F>(&mut self, execution: &Execution, fragment: F)
+        where
+            F: Fn(Vec2<i32>, &PreviousFrame) -> Rgba<u8> + Sync {
+
+        let previous = PreviousFrame {
+            canvas: &self.current,
+            frame: self.frame,
+        };
+        for_each_row(&mut self.next, execution, |y, row| {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                *pixel = fragment(Vec2::new(x as i32, y as i32), &previous);
+            }
+        });
+        mem::swap(&mut self.current, &mut self.next);
+        self.frame += 1;
+    }
+}
+
+/// Launch a window with the given function for computing a fragment color from the
+/// previous frame, starting from some initial canvas, until the window closes.
+///
+/// The fragment function can read any pixel of the previous frame, which makes cellular
+/// automata, reaction-diffusion, trails and other ping-pong effects possible. The canvases
+/// are double-buffered automatically.
+pub fn fragment_feedback<F>(initial: Canvas, fragment: F)
+    where
+        F: Fn(Vec2<i32>, &PreviousFrame) -> Rgba<u8> + Send + Sync + 'static {
+
+    // delegate
+    fragment_feedback_with(initial, FragmentOptions::default(), fragment)
+}
+
+/// Launch a window with the given function for computing a fragment color from the
+/// previous frame, with non-default configuration.
+///
+/// Post-processing is applied to what's displayed, not to what the next frame reads. See
+/// `fragment_feedback`.
+pub fn fragment_feedback_with<F>(initial: Canvas, options: FragmentOptions, fragment: F)
+    where
+        F: Fn(Vec2<i32>, &PreviousFrame) -> Rgba<u8> + Send + Sync + 'static {
+
+    let FragmentOptions { execution, window, post } = options;
+    let frame_stats = Arc::clone(&window.frame_stats);
+    let input = Arc::clone(&window.input);
+    let size = initial.size();
+
+    open_window_with(
+        size.x,
+        size.y,
+        window,
+        move |queue| {
+
+            let execution = execution.resolve();
+            let mut feedback = Feedback::new(initial);
+
+            while !input.is_closed() {
+                if !window_caught_up(&queue, size.x, size.y) {
+                    continue;
+                }
+
+                let start = Instant::now();
+                feedback.step_with(&execution, &fragment);
+                match &post {
+                    Some(post) => post.apply(feedback.current()).paint(&queue),
+                    None => feedback.current().paint(&queue),
+                }
+                frame_stats.record_frame(start.elapsed());
+            }
+        }
+    );
+}
+
+/// Whether the window has displayed all but at most one frame of what's been painted, so
+/// that drawing doesn't get ahead of it. Sleeps briefly if not.
+pub(crate) fn window_caught_up(queue: &SegQueue<Paint>, x_size: usize, y_size: usize) -> bool {
+    if queue.len() > x_size * y_size {
+        thread::sleep(Duration::from_millis(1));
+        false
+    } else {
+        true
+    }
+}
+
+/// Compute every fragment, and send them to the window.
+///
+/// With a post-processing effect, the whole frame is rendered into a canvas first.
+fn paint_frame<F>(
+    execution: &Execution,
+    x_size: usize,
+    y_size: usize,
+    post: Option<&dyn Effect>,
+    queue: &SegQueue<Paint>,
+    fragment: F,
+)
+    where
+        F: Fn(Vec2<i32>) -> Rgba<u8> + Sync {
+
+    if let Some(post) = post {
+        post.apply(&render_with(x_size, y_size, execution, fragment)).paint(queue);
+        return;
+    }
+
+    execution.for_each_fragment(x_size, y_size, |x, y| {
+        let color = fragment(Vec2::new(x as i32, y as i32));
+        queue.push(Paint {
+            x,
+            y,
+            r: color.r,
+            g: color.g,
+            b: color.b,
+            a: color.a,
+        });
+    });
+}
+
+/// Open a window, and repeatedly draw frames into it in the drawing thread.
+///
+/// `draw_frame` is given a function to paint a pixel.
+fn run_frames<D>(x_size: usize, y_size: usize, options: FragmentOptions, draw_frame: D)
+    where
+        D: Fn(&Execution, &(dyn Fn(usize, usize, Rgba<u8>) + Sync)) + Send + 'static {
+
+    let FragmentOptions { execution, window, post } = options;
+
+    // frame times for the performance overlay
+    let frame_stats = Arc::clone(&window.frame_stats);
+
     // open window, drawing thread
-    open_window(
+    open_window_with(
         x_size,
         y_size,
+        window,
         move |queue| {
 
+            let execution = execution.resolve();
+
             let runs = 100;
-            let start = Instant::now();
 
             for i in 0..runs {
+                let run_start = Instant::now();
 
-                // parallel iter over fragments
-                (0..x_size).into_par_iter()
-                    .flat_map(|x| (0..y_size).into_par_iter()
-                        .map(move |y| (x, y)))
-                    //.collect::<Vec<_>>().into_iter() // sequential for debug
-                    .for_each(|(x, y)| {
-
-                        // paint
-                        let mut color = fragment(
-                            Vec2::new(x as i32, y as i32),
-                            &state,
-                        );
-
-                        if i % 2 == 1 {
-                            color.r = 0xFF - color.r;
-                            color.g = 0xFF - color.g;
-                            color.b = 0xFF - color.b;
-                        }
-
-                        queue.push(Paint {
-                            x,
-                            y,
-                            r: color.r,
-                            g: color.g,
-                            b: color.b,
-                            a: color.a,
-                        });
+                // with a post effect, the frame is collected before being painted
+                let frame = post.as_ref().map(|_| SegQueue::new());
+
+                // iter over fragments
+                draw_frame(&execution, &|x, y, mut color: Rgba<u8>| {
+
+                    // paint
+                    if i % 2 == 1 {
+                        color.r = 0xFF - color.r;
+                        color.g = 0xFF - color.g;
+                        color.b = 0xFF - color.b;
+                    }
+
+                    frame.as_ref().unwrap_or(&queue).push(Paint {
+                        x,
+                        y,
+                        r: color.r,
+                        g: color.g,
+                        b: color.b,
+                        a: color.a,
                     });
+                });
 
-                dbg!(i);
+                if let (Some(post), Some(frame)) = (&post, frame) {
+                    let mut canvas = Canvas::new(x_size, y_size);
+                    while let Ok(Paint { x, y, r, g, b, a }) = frame.pop() {
+                        canvas.set(x, y, Rgba::new(r, g, b, a));
+                    }
+                    post.apply(&canvas).paint(&queue);
+                }
+
+                frame_stats.record_frame(run_start.elapsed());
             }
+        }
+    );
+}
 
-            let end = Instant::now();
+/// Compute every fragment into a canvas, without opening a window.
+///
+/// This uses rayon for parallelism.
+pub fn render<F>(x_size: usize, y_size: usize, fragment: F) -> Canvas
+    where
+        F: Fn(Vec2<i32>) -> Rgba<u8> + Sync {
+
+    render_with(x_size, y_size, &Execution::Global, fragment)
+}
+
+/// Compute every fragment into a canvas, without opening a window, with some execution
+/// strategy.
+pub fn render_with<F>(x_size: usize, y_size: usize, execution: &Execution, fragment: F) -> Canvas
+    where
+        F: Fn(Vec2<i32>) -> Rgba<u8> + Sync {
 
-            let avg_time = (end - start) / runs;
-            dbg!(avg_time);
+    let mut canvas = Canvas::new(x_size, y_size);
+    for_each_row(&mut canvas, execution, |y, row| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = fragment(Vec2::new(x as i32, y as i32));
         }
-    );
-}
\ No newline at end of file
+    });
+    canvas
+}
+
+/// Compute every packet of fragments into a canvas, without opening a window.
+///
+/// See `fragment_packets`.
+pub fn render_packets<F, const N: usize>(x_size: usize, y_size: usize, fragment: F) -> Canvas
+    where
+        F: Fn(PixelPacket<N>) -> [Rgba<u8>; N] + Sync,
+        Lanes<N>: SupportedLanes {
+
+    render_packets_with(x_size, y_size, &Execution::Global, fragment)
+}
+
+/// Compute every packet of fragments into a canvas, without opening a window, with some
+/// execution strategy.
+pub fn render_packets_with<F, const N: usize>(
+    x_size: usize,
+    y_size: usize,
+    execution: &Execution,
+    fragment: F,
+) -> Canvas
+    where
+        F: Fn(PixelPacket<N>) -> [Rgba<u8>; N] + Sync,
+        Lanes<N>: SupportedLanes {
+
+    let mut canvas = Canvas::new(x_size, y_size);
+    for_each_row(&mut canvas, execution, |y, row| {
+        for (i, pixels) in row.chunks_mut(N).enumerate() {
+            let colors = fragment(PixelPacket::new(i * N, y, x_size));
+            pixels.copy_from_slice(&colors[..pixels.len()]);
+        }
+    });
+    canvas
+}
+
+/// Compute the palette index of every fragment into an indexed canvas, without opening a
+/// window.
+///
+/// This uses rayon for parallelism.
+pub fn render_indexed<F>(x_size: usize, y_size: usize, fragment: F) -> IndexedCanvas
+    where
+        F: Fn(Vec2<i32>) -> u8 + Sync {
+
+    render_indexed_with(x_size, y_size, &Execution::Global, fragment)
+}
+
+/// Compute the palette index of every fragment into an indexed canvas, without opening a
+/// window, with some execution strategy.
+pub fn render_indexed_with<F>(
+    x_size: usize,
+    y_size: usize,
+    execution: &Execution,
+    fragment: F,
+) -> IndexedCanvas
+    where
+        F: Fn(Vec2<i32>) -> u8 + Sync {
+
+    let mut canvas = IndexedCanvas::new(x_size, y_size);
+    for_each_row_of(canvas.pixels_mut(), x_size, execution, |y, row| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = fragment(Vec2::new(x as i32, y as i32));
+        }
+    });
+    canvas
+}
+
+/// Call a function for every row of a canvas.
+fn for_each_row<F>(canvas: &mut Canvas, execution: &Execution, fill_row: F)
+    where
+        F: Fn(usize, &mut [Rgba<u8>]) + Sync {
+
+    let x_size = canvas.x_size();
+    for_each_row_of(canvas.pixels_mut(), x_size, execution, fill_row)
+}
+
+/// Call a function for every row of a buffer of pixels of any type.
+fn for_each_row_of<T, F>(pixels: &mut [T], x_size: usize, execution: &Execution, fill_row: F)
+    where
+        T: Send,
+        F: Fn(usize, &mut [T]) + Sync {
+
+    let row_len = x_size.max(1);
+    match execution {
+        Execution::Global => {
+            pixels.par_chunks_mut(row_len).enumerate().for_each(|(y, row)| fill_row(y, row));
+        },
+        Execution::Pool(pool) => pool.install(|| {
+            pixels.par_chunks_mut(row_len).enumerate().for_each(|(y, row)| fill_row(y, row));
+        }),
+        Execution::Threads(_) => {
+            for_each_row_of(pixels, x_size, &execution.clone().resolve(), fill_row)
+        },
+        Execution::Sequential => {
+            pixels.chunks_mut(row_len).enumerate().for_each(|(y, row)| fill_row(y, row));
+        },
+    }
+}
@@ -0,0 +1,212 @@
+use crate::{
+    canvas::Canvas,
+    frag,
+};
+
+use std::{
+    env,
+    fs,
+    path::PathBuf,
+};
+
+use rand::{rngs::StdRng, SeedableRng};
+use vek::*;
+
+/// Environment variable which, when set to anything other than `0`, makes golden image
+/// checks overwrite the golden images with what was rendered.
+pub const BLESS_VAR: &str = "BLESS";
+
+/// A golden image regression test, which renders a fragment function headless and
+/// compares the result to a checked-in PNG.
+///
+/// Golden images live in `tests/golden/<name>.png` under the crate being tested. When a
+/// check fails, the expected, actual and diff images are written to
+/// `tests/golden/failures/`.
+#[derive(Clone, Debug)]
+pub struct Golden {
+    name: String,
+    x_size: usize,
+    y_size: usize,
+    seed: u64,
+    tolerance: u8,
+    max_differing: usize,
+    dir: PathBuf,
+}
+
+impl Golden {
+    /// A golden image with a name and size, a seed of 0, a tolerance of 2 per channel,
+    /// and no differing pixels allowed.
+    pub fn new(name: impl Into<String>, x_size: usize, y_size: usize) -> Self {
+        // cargo sets this when running tests
+        let manifest_dir = env::var_os("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        Golden {
+            name: name.into(),
+            x_size,
+            y_size,
+            seed: 0,
+            tolerance: 2,
+            max_differing: 0,
+            dir: manifest_dir.join("tests").join("golden"),
+        }
+    }
+
+    /// Builder-style setter for the seed of the fragments' random number generators.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builder-style setter for the maximum difference in any channel for pixels to count
+    /// as matching.
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Builder-style setter for how many pixels may differ before the check fails.
+    pub fn with_max_differing(mut self, max_differing: usize) -> Self {
+        self.max_differing = max_differing;
+        self
+    }
+
+    /// Builder-style setter for the directory of golden images.
+    pub fn in_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.dir = dir.into();
+        self
+    }
+
+    /// Path of the golden image.
+    pub fn path(&self) -> PathBuf {
+        self.dir.join(format!("{}.png", self.name))
+    }
+
+    /// Render a fragment function headless.
+    ///
+    /// Each fragment gets a random number generator seeded from the seed and its
+    /// position, so the result doesn't depend on scheduling.
+    pub fn render<F>(&self, fragment: F) -> Canvas
+        where
+            F: Fn(Vec2<i32>, &mut StdRng) -> Rgba<u8> + Sync {
+
+        let seed = self.seed;
+        let x_size = self.x_size as u64;
+        frag::render(self.x_size, self.y_size, |xy| {
+            let index = xy.y as u64 * x_size + xy.x as u64;
+            let mut rng = StdRng::seed_from_u64(seed ^ index.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            fragment(xy, &mut rng)
+        })
+    }
+
+    /// Render a fragment function headless, and check it against the golden image.
+    pub fn check_fragment<F>(&self, fragment: F)
+        where
+            F: Fn(Vec2<i32>, &mut StdRng) -> Rgba<u8> + Sync {
+
+        self.check(&self.render(fragment))
+    }
+
+    /// Check a canvas against the golden image, panicking if it doesn't match.
+    ///
+    /// If the `BLESS` environment variable is set, the golden image is overwritten
+    /// instead.
+    pub fn check(&self, actual: &Canvas) {
+        let path = self.path();
+
+        let bless = env::var(BLESS_VAR).map(|v| v != "0").unwrap_or(false);
+        if bless {
+            fs::create_dir_all(&self.dir).unwrap();
+            actual.to_image().save(&path).unwrap();
+            return;
+        }
+
+        let expected = match image::open(&path) {
+            Ok(image) => Canvas::from_image(&image.to_rgba()),
+            Err(e) => {
+                self.write_failure("actual", actual);
+                panic!(
+                    "failed to load golden image {}: {}\nrun with {}=1 to create it",
+                    path.display(), e, BLESS_VAR,
+                );
+            },
+        };
+
+        if expected.size() != actual.size() {
+            self.write_failure("expected", &expected);
+            self.write_failure("actual", actual);
+            panic!(
+                "golden image {} is {:?}, but rendered {:?}",
+                self.name, expected.size(), actual.size(),
+            );
+        }
+
+        let comparison = compare(&expected, actual, self.tolerance);
+        if comparison.differing > self.max_differing {
+            self.write_failure("expected", &expected);
+            self.write_failure("actual", actual);
+            self.write_failure("diff", &comparison.diff);
+            panic!(
+                "golden image {} has {} differing pixels, more than the {} allowed \
+                (max channel difference {})\nimages written to {}\nrun with {}=1 to \
+                accept the new image",
+                self.name,
+                comparison.differing,
+                self.max_differing,
+                comparison.max_difference,
+                self.dir.join("failures").display(),
+                BLESS_VAR,
+            );
+        }
+    }
+
+    fn write_failure(&self, kind: &str, canvas: &Canvas) {
+        let dir = self.dir.join("failures");
+        let path = dir.join(format!("{}.{}.png", self.name, kind));
+        let result = fs::create_dir_all(&dir)
+            .and_then(|()| canvas.to_image().save(&path));
+        if let Err(e) = result {
+            eprintln!("failed to write {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// The result of comparing two equally sized canvases.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    /// Number of pixels differing by more than the tolerance in any channel.
+    pub differing: usize,
+    /// Largest difference in any channel of any pixel.
+    pub max_difference: u8,
+    /// Differing pixels in red, over a faded copy of the expected image.
+    pub diff: Canvas,
+}
+
+/// Compare two equally sized canvases, with a per-channel tolerance.
+pub fn compare(expected: &Canvas, actual: &Canvas, tolerance: u8) -> Comparison {
+    assert_eq!(expected.size(), actual.size(), "compared canvases of different sizes");
+
+    let mut differing = 0;
+    let mut max_difference = 0;
+    let mut diff = Canvas::new(expected.x_size(), expected.y_size());
+    let pixels = expected.pixels().iter().zip(actual.pixels());
+    for ((&e, &a), d) in pixels.zip(diff.pixels_mut()) {
+        let difference = e
+            .map2(a, |e, a| (e as i16 - a as i16).unsigned_abs() as u8)
+            .reduce_max();
+        max_difference = max_difference.max(difference);
+        *d = if difference > tolerance {
+            differing += 1;
+            Rgba::new(255, 0, 0, 255)
+        } else {
+            let gray = ((e.r as u32 + e.g as u32 + e.b as u32) / 3 / 4) as u8;
+            Rgba::new(gray, gray, gray, 255)
+        };
+    }
+
+    Comparison {
+        differing,
+        max_difference,
+        diff,
+    }
+}
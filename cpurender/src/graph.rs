@@ -0,0 +1,397 @@
+use crate::canvas::Canvas;
+
+use std::{
+    any::Any,
+    error::Error,
+    fmt,
+    marker::PhantomData,
+};
+
+use rayon::prelude::*;
+use vek::*;
+
+/// A 2D buffer of pixels of any type, with the same layout as a canvas: row-major, from
+/// the bottom-left.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Buffer<T> {
+    size: Vec2<usize>,
+    pixels: Vec<T>,
+}
+
+impl<T> Buffer<T> {
+    /// Compute every pixel in parallel.
+    pub fn from_fn(size: Vec2<usize>, f: impl Fn(Vec2<i32>) -> T + Sync) -> Self
+        where
+            T: Send {
+        Buffer {
+            size,
+            pixels: (0..size.product())
+                .into_par_iter()
+                .map(|i| f(Vec2::new(i % size.x, i / size.x).map(|c| c as i32)))
+                .collect(),
+        }
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    pub fn pixels(&self) -> &[T] {
+        &self.pixels
+    }
+
+    /// Get a pixel, clamping the coordinates to the edges.
+    ///
+    /// Panics if the buffer is empty.
+    pub fn get(&self, xy: Vec2<i32>) -> &T {
+        let max = self.size.map(|c| c as i32 - 1);
+        let xy = xy.map2(max, |c, max| c.clamp(0, max.max(0)) as usize);
+        &self.pixels[xy.y * self.size.x + xy.x]
+    }
+}
+
+impl Buffer<Rgba<u8>> {
+    pub fn to_canvas(&self) -> Canvas {
+        let mut canvas = Canvas::new(self.size.x, self.size.y);
+        canvas.pixels_mut().copy_from_slice(&self.pixels);
+        canvas
+    }
+}
+
+/// Handle to a named buffer in a render graph, with pixels of type `T`.
+#[derive(Debug)]
+pub struct BufferId<T> {
+    index: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for BufferId<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BufferId<T> {}
+
+/// Error in the structure of a render graph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GraphError {
+    /// A buffer is written by more than one pass.
+    MultipleWriters { buffer: String },
+    /// A buffer is read, but no pass writes it.
+    ///
+    /// `pass` is the pass reading it, or `None` if it's the graph's output.
+    NeverWritten { buffer: String, pass: Option<String> },
+    /// Passes depend on each other in a cycle, so can't be ordered.
+    Cycle { passes: Vec<String> },
+}
+
+impl fmt::Display for GraphError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphError::MultipleWriters { buffer } => {
+                write!(f, "buffer {:?} is written by more than one pass", buffer)
+            },
+            GraphError::NeverWritten { buffer, pass: Some(pass) } => {
+                write!(f, "pass {:?} reads buffer {:?}, which no pass writes", pass, buffer)
+            },
+            GraphError::NeverWritten { buffer, pass: None } => {
+                write!(f, "output buffer {:?} is never written", buffer)
+            },
+            GraphError::Cycle { passes } => {
+                write!(f, "passes {:?} depend on each other in a cycle", passes)
+            },
+        }
+    }
+}
+
+impl Error for GraphError {}
+
+type AnyBuffer = Box<dyn Any + Send + Sync>;
+
+/// Storage for a buffer, between and during executions.
+struct Slot {
+    name: String,
+    size: Vec2<usize>,
+    current: Option<AnyBuffer>,
+    /// Contents from the previous execution.
+    previous: Option<AnyBuffer>,
+}
+
+/// Computes a pass's output buffer.
+type PassFn = Box<dyn Fn(&PassInputs, Vec2<usize>) -> AnyBuffer + Send + Sync>;
+
+struct Pass {
+    name: String,
+    reads: Vec<usize>,
+    reads_previous: Vec<usize>,
+    output: usize,
+    run: PassFn,
+}
+
+/// A set of named buffers, and passes which compute them from each other, executed in
+/// dependency order.
+///
+/// Each pass writes one buffer, computing every pixel of it in parallel. It can read any
+/// buffers it declares as inputs, which are written by earlier passes, and the previous
+/// execution's contents of any buffers it declares as previous inputs. Multiple render
+/// targets, such as a G-buffer, can be written as one buffer of a struct pixel type.
+pub struct RenderGraph {
+    size: Vec2<usize>,
+    slots: Vec<Slot>,
+    passes: Vec<Pass>,
+    /// Order of passes, if it's been computed since the graph last changed.
+    order: Option<Vec<usize>>,
+    executions: u64,
+}
+
+impl RenderGraph {
+    /// An empty graph, whose buffers default to some size.
+    pub fn new(x_size: usize, y_size: usize) -> Self {
+        RenderGraph {
+            size: Vec2::new(x_size, y_size),
+            slots: Vec::new(),
+            passes: Vec::new(),
+            order: None,
+            executions: 0,
+        }
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    /// Size of a buffer, which is the graph's size unless declared with `buffer_sized`.
+    pub fn buffer_size<T>(&self, id: BufferId<T>) -> Vec2<usize> {
+        self.slots[id.index].size
+    }
+
+    /// Declare a buffer the size of the graph.
+    pub fn buffer<T: Send + Sync + 'static>(&mut self, name: &str) -> BufferId<T> {
+        let size = self.size;
+        self.buffer_sized(name, size.x, size.y)
+    }
+
+    /// Declare a buffer of a different size to the graph, such as for working at a lower
+    /// resolution.
+    pub fn buffer_sized<T: Send + Sync + 'static>(
+        &mut self,
+        name: &str,
+        x_size: usize,
+        y_size: usize,
+    ) -> BufferId<T> {
+        self.slots.push(Slot {
+            name: name.to_owned(),
+            size: Vec2::new(x_size, y_size),
+            current: None,
+            previous: None,
+        });
+        BufferId {
+            index: self.slots.len() - 1,
+            marker: PhantomData,
+        }
+    }
+
+    /// Start declaring a pass. It's added to the graph when its output is given, with
+    /// `PassBuilder::writes`.
+    pub fn pass(&mut self, name: &str) -> PassBuilder<'_> {
+        PassBuilder {
+            graph: self,
+            name: name.to_owned(),
+            reads: Vec::new(),
+            reads_previous: Vec::new(),
+        }
+    }
+
+    /// Check the graph's structure, and work out what order to execute the passes in.
+    ///
+    /// This is done automatically by `execute`, but can be called earlier to catch errors.
+    pub fn compile(&mut self) -> Result<(), GraphError> {
+        if self.order.is_some() {
+            return Ok(());
+        }
+
+        let mut writers = vec![None; self.slots.len()];
+        for (p, pass) in self.passes.iter().enumerate() {
+            if writers[pass.output].replace(p).is_some() {
+                let buffer = self.slots[pass.output].name.clone();
+                return Err(GraphError::MultipleWriters { buffer });
+            }
+        }
+        for pass in &self.passes {
+            for &b in pass.reads.iter().chain(&pass.reads_previous) {
+                if writers[b].is_none() {
+                    return Err(GraphError::NeverWritten {
+                        buffer: self.slots[b].name.clone(),
+                        pass: Some(pass.name.clone()),
+                    });
+                }
+            }
+        }
+
+        // topological sort, preferring the order passes were declared in
+        let mut order = Vec::with_capacity(self.passes.len());
+        let mut done = vec![false; self.passes.len()];
+        while order.len() < self.passes.len() {
+            let ready = (0..self.passes.len()).find(|&p| {
+                !done[p] && self.passes[p].reads.iter().all(|&b| done[writers[b].unwrap()])
+            });
+            match ready {
+                Some(p) => {
+                    done[p] = true;
+                    order.push(p);
+                },
+                None => {
+                    let passes = (0..self.passes.len())
+                        .filter(|&p| !done[p])
+                        .map(|p| self.passes[p].name.clone())
+                        .collect();
+                    return Err(GraphError::Cycle { passes });
+                },
+            }
+        }
+
+        self.order = Some(order);
+        Ok(())
+    }
+
+    /// Names of the passes, in the order they execute.
+    pub fn pass_order(&mut self) -> Result<Vec<&str>, GraphError> {
+        self.compile()?;
+        let passes = &self.passes;
+        let order = self.order.as_ref().unwrap();
+        Ok(order.iter().map(|&p| passes[p].name.as_str()).collect())
+    }
+
+    /// Run every pass once, in dependency order.
+    pub fn execute(&mut self) -> Result<(), GraphError> {
+        self.compile()?;
+
+        for slot in &mut self.slots {
+            slot.previous = slot.current.take();
+        }
+        for &p in self.order.as_ref().unwrap() {
+            let pass = &self.passes[p];
+            let inputs = PassInputs {
+                pass,
+                slots: &self.slots,
+                frame: self.executions,
+            };
+            let output = (pass.run)(&inputs, self.slots[pass.output].size);
+            self.slots[pass.output].current = Some(output);
+        }
+        self.executions += 1;
+        Ok(())
+    }
+
+    /// Contents of a buffer from the latest execution, or `None` if it wasn't written.
+    pub fn get<T: 'static>(&self, id: BufferId<T>) -> Option<&Buffer<T>> {
+        downcast(&self.slots[id.index].current)
+    }
+
+    /// Check the graph's structure like `compile`, and that some pass writes an output
+    /// buffer.
+    pub fn compile_output<T>(&mut self, output: BufferId<T>) -> Result<(), GraphError> {
+        self.compile()?;
+        if self.passes.iter().all(|pass| pass.output != output.index) {
+            return Err(GraphError::NeverWritten {
+                buffer: self.slots[output.index].name.clone(),
+                pass: None,
+            });
+        }
+        Ok(())
+    }
+
+    /// Execute the graph, and copy an output buffer into a canvas of the buffer's size.
+    pub fn render(&mut self, output: BufferId<Rgba<u8>>) -> Result<Canvas, GraphError> {
+        self.compile_output(output)?;
+        self.execute()?;
+        Ok(self.get(output).expect("output was written").to_canvas())
+    }
+}
+
+fn downcast<T: 'static>(buffer: &Option<AnyBuffer>) -> Option<&Buffer<T>> {
+    buffer.as_ref().map(|b| b.downcast_ref().expect("buffer has the wrong type"))
+}
+
+/// Declares a pass's inputs. See `RenderGraph::pass`.
+pub struct PassBuilder<'a> {
+    graph: &'a mut RenderGraph,
+    name: String,
+    reads: Vec<usize>,
+    reads_previous: Vec<usize>,
+}
+
+impl PassBuilder<'_> {
+    /// Declare a buffer which this pass reads, after it's written in the same execution.
+    pub fn reads<T>(mut self, id: BufferId<T>) -> Self {
+        self.reads.push(id.index);
+        self
+    }
+
+    /// Declare a buffer which this pass reads as it was at the end of the previous
+    /// execution. This may be the pass's own output, for feedback effects.
+    pub fn reads_previous<T>(mut self, id: BufferId<T>) -> Self {
+        self.reads_previous.push(id.index);
+        self
+    }
+
+    /// Finish declaring the pass, with the buffer it writes and the function computing each
+    /// of its pixels, and add it to the graph.
+    pub fn writes<T, F>(self, output: BufferId<T>, fragment: F)
+        where
+            T: Send + Sync + 'static,
+            F: Fn(Vec2<i32>, &PassInputs) -> T + Send + Sync + 'static {
+
+        let run: PassFn = Box::new(move |inputs, size| {
+            Box::new(Buffer::from_fn(size, |xy| fragment(xy, inputs)))
+        });
+        self.graph.passes.push(Pass {
+            name: self.name,
+            reads: self.reads,
+            reads_previous: self.reads_previous,
+            output: output.index,
+            run,
+        });
+        self.graph.order = None;
+    }
+}
+
+/// The buffers a pass can read.
+pub struct PassInputs<'a> {
+    pass: &'a Pass,
+    slots: &'a [Slot],
+    frame: u64,
+}
+
+impl PassInputs<'_> {
+    /// A buffer written earlier in this execution.
+    ///
+    /// Panics if the pass didn't declare it with `reads`.
+    pub fn buffer<T: 'static>(&self, id: BufferId<T>) -> &Buffer<T> {
+        assert!(
+            self.pass.reads.contains(&id.index),
+            "pass {:?} reads buffer {:?} without declaring it",
+            self.pass.name, self.slots[id.index].name,
+        );
+        downcast(&self.slots[id.index].current).unwrap()
+    }
+
+    /// A buffer as it was at the end of the previous execution, or `None` during the first
+    /// execution.
+    ///
+    /// Panics if the pass didn't declare it with `reads_previous`.
+    pub fn previous<T: 'static>(&self, id: BufferId<T>) -> Option<&Buffer<T>> {
+        assert!(
+            self.pass.reads_previous.contains(&id.index),
+            "pass {:?} reads the previous buffer {:?} without declaring it",
+            self.pass.name, self.slots[id.index].name,
+        );
+        downcast(&self.slots[id.index].previous)
+    }
+
+    /// Number of times the graph has been executed before this execution.
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+}
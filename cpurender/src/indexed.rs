@@ -0,0 +1,215 @@
+use crate::{
+    canvas::Canvas,
+    IndexPaint,
+};
+
+use std::{
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crossbeam::queue::SegQueue;
+use vek::*;
+
+/// Number of colors in a palette, one for every index.
+pub const PALETTE_LEN: usize = 256;
+
+/// Colors of the 256 indices of an indexed canvas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    colors: Vec<Rgba<u8>>,
+}
+
+impl Palette {
+    /// A palette starting with some colors. Indices past them are transparent black.
+    ///
+    /// Panics if there are more than 256 colors.
+    pub fn new(colors: &[Rgba<u8>]) -> Self {
+        assert!(colors.len() <= PALETTE_LEN, "more than {} colors in palette", PALETTE_LEN);
+        Palette::from_fn(|i| colors.get(i as usize).copied().unwrap_or_else(Rgba::zero))
+    }
+
+    /// Compute the color of every index.
+    pub fn from_fn(f: impl Fn(u8) -> Rgba<u8>) -> Self {
+        Palette {
+            colors: (0..PALETTE_LEN).map(|i| f(i as u8)).collect(),
+        }
+    }
+
+    /// Each index is an opaque gray of that brightness.
+    pub fn grayscale() -> Self {
+        Palette::from_fn(|i| Rgba::new(i, i, i, 0xFF))
+    }
+
+    /// All 256 colors, in index order.
+    pub fn colors(&self) -> &[Rgba<u8>] {
+        &self.colors
+    }
+
+    pub fn get(&self, index: u8) -> Rgba<u8> {
+        self.colors[index as usize]
+    }
+
+    pub fn set(&mut self, index: u8, color: Rgba<u8>) {
+        self.colors[index as usize] = color;
+    }
+
+    /// Rotate the colors of a range of indices by some number of steps, towards higher
+    /// indices and wrapping around within the range, for color cycling animation.
+    pub fn cycle(&mut self, range: RangeInclusive<u8>, steps: i32) {
+        if range.is_empty() {
+            return;
+        }
+        let range = &mut self.colors[*range.start() as usize..=*range.end() as usize];
+        let steps = steps.rem_euclid(range.len() as i32) as usize;
+        range.rotate_right(steps);
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette::grayscale()
+    }
+}
+
+/// The palette of an indexed window, shared with the drawing thread.
+///
+/// The window uploads the palette to the GPU whenever it changes, so changing it recolors
+/// what's displayed without repainting anything.
+#[derive(Debug)]
+pub struct PaletteState {
+    palette: Mutex<Palette>,
+    changed: AtomicBool,
+}
+
+impl PaletteState {
+    pub fn new(palette: Palette) -> Self {
+        PaletteState {
+            palette: Mutex::new(palette),
+            changed: AtomicBool::new(true),
+        }
+    }
+
+    /// A copy of the current palette.
+    pub fn get(&self) -> Palette {
+        self.palette.lock().unwrap().clone()
+    }
+
+    /// Replace the palette.
+    pub fn set(&self, palette: Palette) {
+        self.update(|current| *current = palette);
+    }
+
+    /// Modify the palette in place.
+    pub fn update(&self, f: impl FnOnce(&mut Palette)) {
+        f(&mut self.palette.lock().unwrap());
+        self.changed.store(true, Ordering::Release);
+    }
+
+    /// The palette, if it's changed since this was last called.
+    pub(crate) fn take_changed(&self) -> Option<Palette> {
+        if self.changed.swap(false, Ordering::Acquire) {
+            Some(self.get())
+        } else {
+            None
+        }
+    }
+}
+
+impl Default for PaletteState {
+    fn default() -> Self {
+        PaletteState::new(Palette::default())
+    }
+}
+
+/// A CPU-side buffer of palette indices, which can be painted into an indexed window.
+///
+/// Like a canvas, the origin is the bottom-left corner and the y axis points up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexedCanvas {
+    x_size: usize,
+    y_size: usize,
+    pixels: Vec<u8>,
+}
+
+impl IndexedCanvas {
+    /// Create a canvas filled with index 0.
+    pub fn new(x_size: usize, y_size: usize) -> Self {
+        IndexedCanvas::filled(x_size, y_size, 0)
+    }
+
+    /// Create a canvas filled with a single index.
+    pub fn filled(x_size: usize, y_size: usize, index: u8) -> Self {
+        IndexedCanvas {
+            x_size,
+            y_size,
+            pixels: vec![index; x_size * y_size],
+        }
+    }
+
+    pub fn x_size(&self) -> usize {
+        self.x_size
+    }
+
+    pub fn y_size(&self) -> usize {
+        self.y_size
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        Vec2::new(self.x_size, self.y_size)
+    }
+
+    /// All pixels, in row-major order starting from the bottom row.
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    /// All pixels, in row-major order starting from the bottom row.
+    pub fn pixels_mut(&mut self) -> &mut [u8] {
+        &mut self.pixels
+    }
+
+    /// Panics if out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        assert!(x < self.x_size && y < self.y_size, "pixel out of bounds");
+        self.pixels[y * self.x_size + x]
+    }
+
+    /// Panics if out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, index: u8) {
+        assert!(x < self.x_size && y < self.y_size, "pixel out of bounds");
+        self.pixels[y * self.x_size + x] = index;
+    }
+
+    /// Set every pixel to an index.
+    pub fn fill(&mut self, index: u8) {
+        for p in &mut self.pixels {
+            *p = index;
+        }
+    }
+
+    /// Look up the color of every pixel, as the window would display it.
+    pub fn resolve(&self, palette: &Palette) -> Canvas {
+        let mut canvas = Canvas::new(self.x_size, self.y_size);
+        for (color, &index) in canvas.pixels_mut().iter_mut().zip(&self.pixels) {
+            *color = palette.get(index);
+        }
+        canvas
+    }
+
+    /// Send every pixel to an indexed paint queue.
+    pub fn paint(&self, queue: &SegQueue<IndexPaint>) {
+        for y in 0..self.y_size {
+            for x in 0..self.x_size {
+                queue.push(IndexPaint {
+                    x,
+                    y,
+                    index: self.get(x, y),
+                });
+            }
+        }
+    }
+}
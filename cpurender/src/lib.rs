@@ -1,35 +1,118 @@
 
 #[macro_use]
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate log;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate crossbeam;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate glium;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate image;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate rand;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate rayon;
-#[doc(hide)]
+#[doc(hidden)]
 pub extern crate vek;
 
 /// Concurrent per-fragment painting.
 pub mod frag;
 
+/// SIMD packets of values, for fragment functions which process several pixels at once.
+pub mod packet;
+
+/// Multi-pass rendering with intermediate buffers.
+pub mod graph;
+
+/// Cellular automata, with built-in rules.
+pub mod cellular;
+
+/// Canvases of palette indices, resolved to colors by the window.
+pub mod indexed;
+
+/// Copying images into canvases, with compositing.
+pub mod blit;
+
+/// Anti-aliased vector paths, filled and stroked.
+pub mod vector;
+
+/// Dithering and color quantization.
+pub mod dither;
+
+/// Image-space post-processing effects.
+pub mod post;
+
+/// Fixed-timestep simulation scheduling.
+pub mod timestep;
+
+/// CPU-side pixel buffers.
+pub mod canvas;
+
+/// Bitmap font text rendering.
+pub mod text;
+
+/// Texture loading and sampling.
+pub mod texture;
+
+/// Rays, for ray tracing.
+pub mod ray;
+
+/// Cameras, which generate a ray per pixel.
+pub mod camera;
+
+/// Voxel grids and ray traversal through them.
+pub mod voxel;
+
+/// Loading MagicaVoxel `.vox` files.
+pub mod vox;
+
+/// Sparse voxel octrees, for tracing rays through large voxel scenes.
+pub mod octree;
+
+/// Lighting and shading of voxel scenes.
+pub mod lighting;
+
+/// Ray intersection with analytic shapes, and scenes of them.
+pub mod scene;
+
+/// Signed distance functions, and sphere tracing them.
+pub mod sdf;
+
+/// Golden image regression testing.
+pub mod golden;
+
 /// Displaying pixels in an opengl window.
 mod window;
 
+/// Performance overlay drawn by the window.
+mod overlay;
+
+/// Displaying pixels in the terminal, with ANSI escapes.
+pub mod terminal;
+
 // re-exports
 pub use crossbeam::queue::SegQueue;
 
-#[doc(transparent)]
+#[doc(inline)]
 pub use window::{
     open_window,
+    open_window_with,
+    open_indexed_window,
+    open_indexed_window_with,
+    WindowConfig,
+    FrameStats,
+    Corner,
     Paint,
+    IndexPaint,
+    Input,
+    InputState,
+    VirtualKeyCode,
+    MouseButton,
 };
 
+#[doc(inline)]
+pub use terminal::open_terminal;
+
 /// Re-exports of useful crates.
 pub mod re {
     pub use crossbeam;
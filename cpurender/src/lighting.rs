@@ -0,0 +1,178 @@
+use crate::{
+    ray::Ray,
+    voxel::{Face, VoxelGrid},
+};
+
+use std::f32;
+
+use vek::*;
+
+/// Offset of shadow ray origins from the surface, to keep them out of the hit voxel.
+const SHADOW_BIAS: f32 = 0.001;
+
+/// A light source.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Light {
+    /// Infinitely distant light, such as the sun.
+    Directional {
+        /// Direction in which the light travels.
+        direction: Vec3<f32>,
+        color: Rgb<f32>,
+    },
+    /// Light radiating from a point, falling off with the square of distance.
+    Point {
+        position: Vec3<f32>,
+        /// Color at a distance of 1.
+        color: Rgb<f32>,
+    },
+}
+
+impl Light {
+    /// Unit direction towards the light, its distance, and its color, from a point.
+    fn incident(&self, point: Vec3<f32>) -> (Vec3<f32>, f32, Rgb<f32>) {
+        match *self {
+            Light::Directional { direction, color } => {
+                (-direction.normalized(), f32::INFINITY, color)
+            },
+            Light::Point { position, color } => {
+                let to_light = position - point;
+                let distance = to_light.magnitude();
+                (to_light / distance, distance, color / (distance * distance))
+            },
+        }
+    }
+}
+
+/// Lambert lighting of voxel scenes, with shadows and ambient occlusion.
+///
+/// Colors are linear, and may exceed 1.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelLighting {
+    pub lights: Vec<Light>,
+    /// Light reaching every surface, before ambient occlusion.
+    pub ambient: Rgb<f32>,
+    /// Color of rays which don't hit anything.
+    pub background: Rgb<f32>,
+    /// Whether to cast shadow rays towards each light.
+    pub shadows: bool,
+    /// How much ambient occlusion darkens corners, from 0 (not at all) to 1 (fully).
+    pub ambient_occlusion: f32,
+    /// Maximum distance of primary rays.
+    pub max_distance: f32,
+    /// Maximum number of voxels primary rays step through.
+    pub max_steps: usize,
+}
+
+impl Default for VoxelLighting {
+    fn default() -> Self {
+        VoxelLighting {
+            lights: vec![Light::Directional {
+                direction: Vec3::new(-0.4, -1.0, 0.6),
+                color: Rgb::broadcast(0.8),
+            }],
+            ambient: Rgb::broadcast(0.3),
+            background: Rgb::zero(),
+            shadows: true,
+            ambient_occlusion: 0.7,
+            max_distance: f32::INFINITY,
+            max_steps: usize::MAX,
+        }
+    }
+}
+
+impl VoxelLighting {
+    /// Trace a ray to the first solid voxel, and shade it.
+    ///
+    /// `material` returns the albedo of solid voxels, and `None` for empty ones.
+    pub fn shade<T>(
+        &self,
+        grid: &VoxelGrid<T>,
+        ray: Ray,
+        material: impl Fn(&T) -> Option<Rgb<f32>>,
+    ) -> Rgb<f32> {
+        let hit = grid
+            .trace(ray, self.max_distance)
+            .take(self.max_steps)
+            .find_map(|(hit, voxel)| material(voxel).map(|albedo| (hit, albedo)));
+        let (hit, albedo) = match hit {
+            Some(hit) => hit,
+            None => return self.background,
+        };
+
+        let face = match hit.face {
+            Some(face) => face,
+            // started inside a solid voxel, so there is no surface to light
+            None => return albedo * self.ambient,
+        };
+        let normal = face.normal().map(|c| c as f32);
+        let point = ray.at(hit.distance);
+        let solid = |xyz: Vec3<i32>| grid.get(xyz).and_then(&material).is_some();
+
+        let ao = 1.0 - self.ambient_occlusion
+            * (1.0 - ambient_occlusion(solid, hit.voxel, face, point));
+        let mut light = self.ambient * ao;
+
+        for source in &self.lights {
+            let (to_light, distance, color) = source.incident(point);
+            let lambert = normal.dot(to_light);
+            if lambert <= 0.0 {
+                continue;
+            }
+            if self.shadows {
+                let shadow_ray = Ray::new(point + normal * SHADOW_BIAS, to_light);
+                let occluded = grid
+                    .trace(shadow_ray, distance)
+                    .any(|(_, voxel)| material(voxel).is_some());
+                if occluded {
+                    continue;
+                }
+            }
+            light += color * lambert;
+        }
+
+        albedo * light
+    }
+}
+
+/// Ambient occlusion of a point on a voxel face, from the occupancy of the voxels
+/// surrounding the empty voxel in front of it.
+///
+/// Returns 1 for fully unoccluded, down to 0 for a point in a fully occluded corner.
+/// Each corner of the face is occluded by the two edge-adjacent and one corner-adjacent
+/// neighbours, and the result is interpolated across the face.
+pub fn ambient_occlusion(
+    solid: impl Fn(Vec3<i32>) -> bool,
+    voxel: Vec3<i32>,
+    face: Face,
+    point: Vec3<f32>,
+) -> f32 {
+    let axis = face.axis();
+    let (u_axis, v_axis) = ((axis + 1) % 3, (axis + 2) % 3);
+    let front = voxel + face.normal();
+
+    let mut u_dir: Vec3<i32> = Vec3::zero();
+    u_dir[u_axis] = 1;
+    let mut v_dir: Vec3<i32> = Vec3::zero();
+    v_dir[v_axis] = 1;
+
+    // occlusion of the corner in direction (du, dv) from the face center
+    let corner = |du: i32, dv: i32| -> f32 {
+        let side_u = solid(front + u_dir * du);
+        let side_v = solid(front + v_dir * dv);
+        let diagonal = solid(front + u_dir * du + v_dir * dv);
+        if side_u && side_v {
+            0.0
+        } else {
+            (3 - side_u as i32 - side_v as i32 - diagonal as i32) as f32 / 3.0
+        }
+    };
+
+    // position within the face
+    let local = point - voxel.map(|c| c as f32);
+    let u = local[u_axis].clamp(0.0, 1.0);
+    let v = local[v_axis].clamp(0.0, 1.0);
+
+    let bottom = corner(-1, -1) * (1.0 - u) + corner(1, -1) * u;
+    let top = corner(-1, 1) * (1.0 - u) + corner(1, 1) * u;
+    bottom * (1.0 - v) + top * v
+}
@@ -0,0 +1,273 @@
+use crate::{
+    ray::Ray,
+    vox::VoxModel,
+    voxel::{bounds_intersection, starting_voxel, Face, VoxelGrid, VoxelHit},
+};
+
+use std::f32;
+
+use vek::*;
+
+/// Crossings of different axes' node boundaries which are closer together than this are
+/// merged into a single diagonal step, like in dense traversal.
+const MERGE_EPSILON: f32 = 0.00001;
+
+#[derive(Clone, Debug, PartialEq)]
+enum Node<T> {
+    Empty,
+    Leaf(T),
+    /// Index of the first of 8 contiguous children, ordered by `x + 2y + 4z`.
+    Branch(u32),
+}
+
+/// A sparse voxel octree, which stores only non-empty voxels and lets rays skip over empty
+/// space hierarchically.
+///
+/// It covers the same coordinates as the dense grid it was built from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelOctree<T> {
+    size: Vec3<usize>,
+    /// Power of two side length of the root node.
+    root_size: i32,
+    nodes: Vec<Node<T>>,
+}
+
+impl<T: Clone> VoxelOctree<T> {
+    /// Build an octree from the non-empty voxels of a dense grid.
+    pub fn from_grid(grid: &VoxelGrid<T>, is_empty: impl Fn(&T) -> bool) -> Self {
+        let size = grid.size();
+        let root_size = size.reduce_max().max(1).next_power_of_two() as i32;
+
+        let mut nodes = vec![Node::Empty];
+        let root = build(grid, &is_empty, &mut nodes, Vec3::zero(), root_size);
+        nodes[0] = root;
+
+        VoxelOctree {
+            size,
+            root_size,
+            nodes,
+        }
+    }
+}
+
+impl VoxelOctree<u8> {
+    /// Build an octree of palette indices from a `.vox` model.
+    pub fn from_vox(model: &VoxModel) -> Self {
+        VoxelOctree::from_grid(&model.grid, |&i| i == 0)
+    }
+}
+
+/// Recursively build the node covering a cube, pushing its descendants.
+fn build<T: Clone>(
+    grid: &VoxelGrid<T>,
+    is_empty: &impl Fn(&T) -> bool,
+    nodes: &mut Vec<Node<T>>,
+    min: Vec3<i32>,
+    size: i32,
+) -> Node<T> {
+    if size == 1 {
+        return match grid.get(min) {
+            Some(voxel) if !is_empty(voxel) => Node::Leaf(voxel.clone()),
+            _ => Node::Empty,
+        };
+    }
+
+    // skip cubes entirely outside of the grid
+    if min.map2(grid.size(), |c, n| c as usize >= n).reduce_or() {
+        return Node::Empty;
+    }
+
+    let half = size / 2;
+    let children: Vec<Node<T>> = (0..8)
+        .map(|i| build(grid, is_empty, nodes, min + child_offset(i) * half, half))
+        .collect();
+    if children.iter().all(|child| matches!(child, Node::Empty)) {
+        return Node::Empty;
+    }
+
+    let first = nodes.len() as u32;
+    nodes.extend(children);
+    Node::Branch(first)
+}
+
+/// Position of a child within its parent, in units of the child's size.
+fn child_offset(i: usize) -> Vec3<i32> {
+    Vec3::new(i & 1, (i >> 1) & 1, (i >> 2) & 1).map(|c| c as i32)
+}
+
+impl<T> VoxelOctree<T> {
+    /// Size of the grid this was built from.
+    pub fn size(&self) -> Vec3<usize> {
+        self.size
+    }
+
+    /// Number of nodes, as a measure of memory use.
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Get a voxel, or `None` if empty or out of bounds.
+    pub fn get(&self, xyz: Vec3<i32>) -> Option<&T> {
+        match self.lookup(xyz) {
+            Some((Node::Leaf(voxel), _, _)) => Some(voxel),
+            _ => None,
+        }
+    }
+
+    /// Find the deepest node containing a voxel, and its minimum corner and size.
+    fn lookup(&self, xyz: Vec3<i32>) -> Option<(&Node<T>, Vec3<i32>, i32)> {
+        let in_bounds = xyz
+            .map2(self.size, |c, n| c >= 0 && (c as usize) < n)
+            .reduce_and();
+        if !in_bounds {
+            return None;
+        }
+
+        let mut node = &self.nodes[0];
+        let mut min = Vec3::zero();
+        let mut size = self.root_size;
+        while let Node::Branch(first) = *node {
+            size /= 2;
+            let upper = xyz.map2(min, |c, m| (c >= m + size) as usize);
+            let i = upper.x + upper.y * 2 + upper.z * 4;
+            node = &self.nodes[first as usize + i];
+            min += child_offset(i) * size;
+        }
+        Some((node, min, size))
+    }
+
+    /// Trace a ray through the octree, visiting every non-empty voxel which it passes
+    /// through, in order, until it leaves the grid or exceeds `max_distance`.
+    ///
+    /// This gives the same hits as `VoxelGrid::trace` on the grid it was built from, with
+    /// the empty voxels filtered out.
+    pub fn trace(&self, ray: Ray, max_distance: f32) -> OctreeTrace<'_, T> {
+        let (start, exit, face) = match bounds_intersection(self.size, ray) {
+            Some((enter, exit, _)) if enter <= 0.0 => (0.0, exit, None),
+            Some((enter, exit, face)) => (enter, exit, face),
+            None => (0.0, -1.0, None),
+        };
+
+        let (voxel, _) = starting_voxel(ray.at(start), ray.dir);
+
+        OctreeTrace {
+            octree: self,
+            ray,
+            voxel,
+            distance: start,
+            face,
+            normal: face.map(Face::normal).unwrap_or_default(),
+            max_distance: max_distance.min(exit),
+        }
+    }
+
+    /// The first non-empty voxel hit by a ray.
+    pub fn first_hit(&self, ray: Ray, max_distance: f32) -> Option<(VoxelHit, &T)> {
+        self.trace(ray, max_distance).next()
+    }
+}
+
+/// Iterator over the non-empty voxels a ray passes through in an octree.
+#[derive(Clone, Debug)]
+pub struct OctreeTrace<'a, T> {
+    octree: &'a VoxelOctree<T>,
+    ray: Ray,
+    /// Voxel the ray is currently entering.
+    voxel: Vec3<i32>,
+    distance: f32,
+    face: Option<Face>,
+    normal: Vec3<i32>,
+    max_distance: f32,
+}
+
+impl<'a, T> Iterator for OctreeTrace<'a, T> {
+    type Item = (VoxelHit, &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.distance > self.max_distance {
+                return None;
+            }
+            // outside of the grid is empty, which happens around its boundary due to
+            // rounding error
+            let (node, min, size) = self.octree.lookup(self.voxel)
+                .unwrap_or((&Node::Empty, self.voxel, 1));
+
+            let hit = match node {
+                Node::Leaf(voxel) => Some((
+                    VoxelHit {
+                        voxel: self.voxel,
+                        distance: self.distance,
+                        face: self.face,
+                        normal: self.normal,
+                    },
+                    voxel,
+                )),
+                _ => None,
+            };
+
+            // step out of this node, whether it's an empty region or a single voxel
+            if !self.step_out(min, size) {
+                self.max_distance = f32::NEG_INFINITY;
+            }
+
+            if hit.is_some() {
+                return hit;
+            }
+        }
+    }
+}
+
+impl<'a, T> OctreeTrace<'a, T> {
+    /// Advance to the voxel the ray enters on leaving a cube. Returns false if the ray
+    /// never leaves it.
+    fn step_out(&mut self, min: Vec3<i32>, size: i32) -> bool {
+        let Ray { origin, dir } = self.ray;
+
+        // distance to each axis' exit plane, if it's not parallel
+        let mut exits: [Option<f32>; 3] = [None; 3];
+        for (a, exit) in exits.iter_mut().enumerate() {
+            if dir[a] != 0.0 {
+                let plane = if dir[a] > 0.0 { min[a] + size } else { min[a] };
+                *exit = Some((plane as f32 - origin[a]) / dir[a]);
+            }
+        }
+        let first_exit = exits.iter()
+            .flatten()
+            .fold(f32::INFINITY, |a, &b| a.min(b));
+        if first_exit == f32::INFINITY {
+            return false;
+        }
+
+        // step across every plane at (nearly) the nearest distance
+        let mut step: Vec3<i32> = Vec3::zero();
+        let mut exit_distance = first_exit;
+        let mut face = None;
+        for (a, &exit) in exits.iter().enumerate() {
+            if let Some(exit) = exit {
+                if exit - first_exit < MERGE_EPSILON {
+                    step[a] = if dir[a] > 0.0 { 1 } else { -1 };
+                    exit_distance = exit_distance.max(exit);
+                    if face.is_none() && exit == first_exit {
+                        face = Some(Face::from_axis(a, step[a] < 0));
+                    }
+                }
+            }
+        }
+
+        // stepped axes move exactly across their planes, and the others stay in the cube
+        let position = self.ray.at(exit_distance);
+        for a in 0..3 {
+            self.voxel[a] = match step[a] {
+                1 => min[a] + size,
+                -1 => min[a] - 1,
+                _ => (position[a].floor() as i32).max(min[a]).min(min[a] + size - 1),
+            };
+        }
+
+        self.distance = self.distance.max(exit_distance);
+        self.face = face;
+        self.normal = -step;
+        true
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    canvas::Canvas,
+    text::{draw_text, Font, TextStyle},
+    window::FrameStats,
+};
+
+use std::time::{Duration, Instant};
+
+use vek::*;
+
+/// Width of the overlay, in pixels.
+pub const OVERLAY_X_SIZE: usize = 8 * 22;
+/// Height of the overlay, in pixels.
+pub const OVERLAY_Y_SIZE: usize = 4 * 8 + GRAPH_Y_SIZE + 3 * PADDING;
+
+const PADDING: usize = 4;
+const GRAPH_Y_SIZE: usize = 32;
+
+/// Anything slower than this is drawn in the graph as a bad frame.
+const SLOW_FRAME: Duration = Duration::from_millis(33);
+
+/// Performance statistics drawn over a corner of the window.
+pub struct PerfOverlay {
+    canvas: Canvas,
+    font: Font,
+
+    // counters for the current measurement period
+    period_start: Instant,
+    period_frames: u64,
+    period_pixels: u64,
+
+    // results of the last measurement period
+    fps: f32,
+    pixels_per_sec: f32,
+}
+
+impl PerfOverlay {
+    pub fn new() -> Self {
+        PerfOverlay {
+            canvas: Canvas::new(OVERLAY_X_SIZE, OVERLAY_Y_SIZE),
+            font: Font::embedded(),
+            period_start: Instant::now(),
+            period_frames: 0,
+            period_pixels: 0,
+            fps: 0.0,
+            pixels_per_sec: 0.0,
+        }
+    }
+
+    /// Record that the window displayed a frame, after uploading some pixels.
+    pub fn displayed_frame(&mut self, pixels_uploaded: usize) {
+        self.period_frames += 1;
+        self.period_pixels += pixels_uploaded as u64;
+
+        let elapsed = self.period_start.elapsed();
+        if elapsed >= Duration::from_millis(500) {
+            let secs = elapsed.as_secs_f32();
+            self.fps = self.period_frames as f32 / secs;
+            self.pixels_per_sec = self.period_pixels as f32 / secs;
+            self.period_start = Instant::now();
+            self.period_frames = 0;
+            self.period_pixels = 0;
+        }
+    }
+
+    /// Redraw the overlay.
+    pub fn redraw(&mut self, queue_len: usize, stats: &FrameStats) -> &Canvas {
+        let history = stats.history();
+
+        self.canvas.fill(Rgba::new(0x00, 0x00, 0x00, 0xC0));
+
+        // text
+        let frame_time = match history.last() {
+            Some(t) => format!("{:.1} ms", t.as_secs_f32() * 1000.0),
+            None => "-".to_owned(),
+        };
+        let lines = format!(
+            "fps     {:.1}\nframe   {}\nqueue   {}\nupload  {:.2} Mpx/s",
+            self.fps,
+            frame_time,
+            queue_len,
+            self.pixels_per_sec / 1_000_000.0,
+        );
+        let style = TextStyle::default();
+        let top = (OVERLAY_Y_SIZE - 1 - PADDING) as i32;
+        draw_text(&mut self.canvas, &self.font, Vec2::new(PADDING as i32, top), &lines, &style);
+
+        // frame time graph, newest frame on the right
+        let graph_x_size = OVERLAY_X_SIZE - 2 * PADDING;
+        let max = history.iter()
+            .copied()
+            .max()
+            .unwrap_or_default()
+            .max(SLOW_FRAME)
+            .as_secs_f32();
+        for (i, t) in history.iter().rev().take(graph_x_size).enumerate() {
+            let x = OVERLAY_X_SIZE - 1 - PADDING - i;
+            let height = ((t.as_secs_f32() / max * GRAPH_Y_SIZE as f32).ceil() as usize)
+                .clamp(1, GRAPH_Y_SIZE);
+            let color = if *t >= SLOW_FRAME {
+                Rgba::new(0xFF, 0x40, 0x40, 0xFF)
+            } else {
+                Rgba::new(0x40, 0xFF, 0x40, 0xFF)
+            };
+            for y in PADDING..PADDING + height {
+                self.canvas.set(x, y, color);
+            }
+        }
+
+        &self.canvas
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    iter::Sum,
+    ops::*,
+};
+
+use vek::*;
+
+/// A fixed number of values processed together, one per SIMD lane.
+///
+/// Every operation is a simple loop over the lanes, which LLVM vectorizes on stable Rust.
+/// (vek's own `repr_simd` types need nightly.) For vectors of packets, use vek's vectors
+/// of packets, such as `Vec3<F32s<8>>`, which are laid out as structures of arrays.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Packet<T, const N: usize>(pub [T; N]);
+
+/// A packet width, for naming which widths are supported.
+pub struct Lanes<const N: usize>;
+
+/// Implemented for the packet widths which fragment functions can use: 4, 8 and 16.
+///
+/// This can't be implemented outside of this crate.
+pub trait SupportedLanes: sealed::Sealed {}
+
+impl SupportedLanes for Lanes<4> {}
+impl SupportedLanes for Lanes<8> {}
+impl SupportedLanes for Lanes<16> {}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::Lanes<4> {}
+    impl Sealed for super::Lanes<8> {}
+    impl Sealed for super::Lanes<16> {}
+}
+
+/// Packet of floats.
+pub type F32s<const N: usize> = Packet<f32, N>;
+/// Packet of integers.
+pub type I32s<const N: usize> = Packet<i32, N>;
+/// Packet of booleans, for selecting and disabling lanes.
+pub type Mask<const N: usize> = Packet<bool, N>;
+
+impl<T: Copy, const N: usize> Packet<T, N> {
+    /// Every lane set to the same value.
+    pub fn splat(value: T) -> Self {
+        Packet([value; N])
+    }
+
+    /// Each lane set from its index.
+    #[inline(always)]
+    pub fn from_fn(f: impl FnMut(usize) -> T) -> Self {
+        Packet(std::array::from_fn(f))
+    }
+
+    pub fn lane(self, i: usize) -> T {
+        self.0[i]
+    }
+
+    pub fn set_lane(&mut self, i: usize, value: T) {
+        self.0[i] = value;
+    }
+
+    #[inline(always)]
+    pub fn map<U: Copy>(self, mut f: impl FnMut(T) -> U) -> Packet<U, N> {
+        Packet::from_fn(|i| f(self.0[i]))
+    }
+
+    #[inline(always)]
+    pub fn zip_map<U, V>(self, other: Packet<U, N>, mut f: impl FnMut(T, U) -> V) -> Packet<V, N>
+        where
+            U: Copy,
+            V: Copy {
+        Packet::from_fn(|i| f(self.0[i], other.0[i]))
+    }
+}
+
+impl<T: Copy + PartialOrd, const N: usize> Packet<T, N> {
+    pub fn simd_lt(self, other: Self) -> Mask<N> {
+        self.zip_map(other, |a, b| a < b)
+    }
+
+    pub fn simd_le(self, other: Self) -> Mask<N> {
+        self.zip_map(other, |a, b| a <= b)
+    }
+
+    pub fn simd_gt(self, other: Self) -> Mask<N> {
+        self.zip_map(other, |a, b| a > b)
+    }
+
+    pub fn simd_ge(self, other: Self) -> Mask<N> {
+        self.zip_map(other, |a, b| a >= b)
+    }
+
+    pub fn simd_eq(self, other: Self) -> Mask<N> {
+        self.zip_map(other, |a, b| a == b)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        self.zip_map(other, |a, b| if b < a { b } else { a })
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        self.zip_map(other, |a, b| if b > a { b } else { a })
+    }
+}
+
+impl<const N: usize> F32s<N> {
+    pub fn abs(self) -> Self {
+        self.map(f32::abs)
+    }
+
+    pub fn floor(self) -> Self {
+        self.map(f32::floor)
+    }
+
+    pub fn sqrt(self) -> Self {
+        self.map(f32::sqrt)
+    }
+
+    pub fn recip(self) -> Self {
+        self.map(f32::recip)
+    }
+
+    pub fn signum(self) -> Self {
+        self.map(f32::signum)
+    }
+
+    pub fn clamp(self, min: f32, max: f32) -> Self {
+        self.map(|c| c.clamp(min, max))
+    }
+
+    /// Convert to integers, rounding towards zero.
+    pub fn to_i32(self) -> I32s<N> {
+        self.map(|c| c as i32)
+    }
+}
+
+impl<const N: usize> I32s<N> {
+    pub fn to_f32(self) -> F32s<N> {
+        self.map(|c| c as f32)
+    }
+}
+
+impl<const N: usize> Mask<N> {
+    /// The first `n` lanes enabled, and the rest disabled.
+    pub fn first(n: usize) -> Self {
+        Packet::from_fn(|i| i < n)
+    }
+
+    pub fn any(self) -> bool {
+        self.0.iter().any(|&b| b)
+    }
+
+    pub fn all(self) -> bool {
+        self.0.iter().all(|&b| b)
+    }
+
+    /// Number of enabled lanes.
+    pub fn count(self) -> usize {
+        self.0.iter().filter(|&&b| b).count()
+    }
+
+    /// Lanes of `a` where enabled, and of `b` elsewhere.
+    pub fn select<T: Copy>(self, a: Packet<T, N>, b: Packet<T, N>) -> Packet<T, N> {
+        Packet::from_fn(|i| if self.0[i] { a.0[i] } else { b.0[i] })
+    }
+}
+
+macro_rules! impl_binary_op {
+    ($Op:ident, $op:ident, $OpAssign:ident, $op_assign:ident) => {
+        impl<T: Copy + $Op<Output = T>, const N: usize> $Op for Packet<T, N> {
+            type Output = Self;
+
+            fn $op(self, rhs: Self) -> Self {
+                self.zip_map(rhs, |a, b| a.$op(b))
+            }
+        }
+
+        /// Applies the scalar to every lane.
+        impl<T: Copy + $Op<Output = T>, const N: usize> $Op<T> for Packet<T, N> {
+            type Output = Self;
+
+            fn $op(self, rhs: T) -> Self {
+                self.map(|a| a.$op(rhs))
+            }
+        }
+
+        impl<T: Copy + $Op<Output = T>, const N: usize> $OpAssign for Packet<T, N> {
+            fn $op_assign(&mut self, rhs: Self) {
+                *self = (*self).$op(rhs);
+            }
+        }
+    };
+}
+
+impl_binary_op!(Add, add, AddAssign, add_assign);
+impl_binary_op!(Sub, sub, SubAssign, sub_assign);
+impl_binary_op!(Mul, mul, MulAssign, mul_assign);
+impl_binary_op!(Div, div, DivAssign, div_assign);
+impl_binary_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_binary_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+
+impl<T: Copy + Neg<Output = T>, const N: usize> Neg for Packet<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self.map(|a| -a)
+    }
+}
+
+impl<T: Copy + Not<Output = T>, const N: usize> Not for Packet<T, N> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        self.map(|a| !a)
+    }
+}
+
+impl<T: Copy + Default, const N: usize> Default for Packet<T, N> {
+    fn default() -> Self {
+        Packet::splat(T::default())
+    }
+}
+
+/// Lets vek compute dot products and magnitudes of vectors of packets.
+impl<T: Copy + Default + Add<Output = T>, const N: usize> Sum for Packet<T, N> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Packet::default(), |a, b| a + b)
+    }
+}
+
+/// Every lane set to the same vector.
+pub fn splat3<const N: usize>(v: Vec3<f32>) -> Vec3<F32s<N>> {
+    v.map(Packet::splat)
+}
+
+/// One lane of a vector of packets.
+pub fn lane3<T: Copy, const N: usize>(v: Vec3<Packet<T, N>>, i: usize) -> Vec3<T> {
+    v.map(|c| c.lane(i))
+}
+
+/// Normalize each lane of a vector of packets.
+pub fn normalized3<const N: usize>(v: Vec3<F32s<N>>) -> Vec3<F32s<N>> {
+    let inverse_magnitude = v.magnitude_squared().sqrt().recip();
+    v.map(|c| c * inverse_magnitude)
+}
+
+/// A horizontal run of pixels, given to packet fragment functions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PixelPacket<const N: usize> {
+    pub x: I32s<N>,
+    pub y: I32s<N>,
+    /// Which lanes are real pixels. Packets at the right edge of the canvas may be
+    /// partial, and the colors of their disabled lanes are ignored.
+    pub mask: Mask<N>,
+}
+
+impl<const N: usize> PixelPacket<N> {
+    /// The packet starting at a pixel, in a canvas of some width.
+    pub fn new(x: usize, y: usize, x_size: usize) -> Self {
+        PixelPacket {
+            x: Packet::from_fn(|i| (x + i) as i32),
+            y: Packet::splat(y as i32),
+            mask: Mask::first(x_size.saturating_sub(x)),
+        }
+    }
+
+    /// Coordinates of one lane.
+    pub fn lane(&self, i: usize) -> Vec2<i32> {
+        Vec2::new(self.x.lane(i), self.y.lane(i))
+    }
+
+    /// Coordinates of each lane, as floats.
+    pub fn xy(&self) -> Vec2<F32s<N>> {
+        Vec2::new(self.x.to_f32(), self.y.to_f32())
+    }
+}
+
+/// Convert linear color packets in `[0, 1]` to 8-bit colors, one per lane.
+pub fn to_rgba8<const N: usize>(rgb: Rgb<F32s<N>>) -> [Rgba<u8>; N] {
+    std::array::from_fn(|i| {
+        let c = rgb.map(|c| (c.lane(i).clamp(0.0, 1.0) * 255.0) as u8);
+        Rgba::from_opaque(c)
+    })
+}
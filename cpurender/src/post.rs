@@ -0,0 +1,456 @@
+use crate::{
+    canvas::Canvas,
+    frag,
+};
+
+use std::fmt::Debug;
+
+use rayon::prelude::*;
+use vek::*;
+
+/// An image-space effect, run on a rendered canvas before it's presented.
+///
+/// Effects change the color channels, and keep the alpha of each pixel. They're computed
+/// in parallel with rayon, and can be chained with `then`.
+pub trait Effect: Debug + Send + Sync {
+    fn apply(&self, canvas: &Canvas) -> Canvas;
+
+    /// Chain another effect to run on the result of this one.
+    fn then<E: Effect>(self, next: E) -> Then<Self, E>
+        where
+            Self: Sized {
+        Then {
+            first: self,
+            second: next,
+        }
+    }
+}
+
+impl<E: Effect + ?Sized> Effect for Box<E> {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        (**self).apply(canvas)
+    }
+}
+
+/// Two effects, one after the other. See `Effect::then`.
+#[derive(Clone, Debug)]
+pub struct Then<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Effect, B: Effect> Effect for Then<A, B> {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        self.second.apply(&self.first.apply(canvas))
+    }
+}
+
+/// Separable gaussian blur.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GaussianBlur {
+    /// Standard deviation in pixels.
+    pub sigma: f32,
+}
+
+impl GaussianBlur {
+    pub fn new(sigma: f32) -> Self {
+        GaussianBlur { sigma }
+    }
+}
+
+impl Effect for GaussianBlur {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        blur(&Layer::from_canvas(canvas), self.sigma).to_canvas(canvas)
+    }
+}
+
+/// Blurs the parts of the image brighter than a threshold, and adds them back, so that
+/// bright areas glow.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Bloom {
+    /// Luma above which pixels glow, in `[0, 1]`.
+    pub threshold: f32,
+    /// Standard deviation of the glow in pixels.
+    pub sigma: f32,
+    pub intensity: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 0.7,
+            sigma: 4.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Effect for Bloom {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        let bright = Layer::from_fn(layer.size, |xy| {
+            let color = layer.get(xy);
+            let luma = luma(color);
+            if luma > self.threshold {
+                color * ((luma - self.threshold) / luma)
+            } else {
+                Rgb::zero()
+            }
+        });
+        let glow = blur(&bright, self.sigma);
+        Layer::from_fn(layer.size, |xy| layer.get(xy) + glow.get(xy) * self.intensity)
+            .to_canvas(canvas)
+    }
+}
+
+/// Fast approximate anti-aliasing, which smooths jagged edges by blending along them.
+///
+/// This is the original, single-pass variant of FXAA.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Fxaa {
+    /// Furthest to blend along an edge, in pixels.
+    pub span_max: f32,
+    /// How much the blend direction is damped in bright areas.
+    pub reduce_mul: f32,
+    /// Minimum damping of the blend direction, to avoid blending flat areas.
+    pub reduce_min: f32,
+}
+
+impl Default for Fxaa {
+    fn default() -> Self {
+        Fxaa {
+            span_max: 8.0,
+            reduce_mul: 1.0 / 8.0,
+            reduce_min: 1.0 / 128.0,
+        }
+    }
+}
+
+impl Effect for Fxaa {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        Layer::from_fn(layer.size, |xy| {
+            let center = xy.map(|c| c as f32 + 0.5);
+            let luma_at = |dx, dy| luma(layer.get(xy + Vec2::new(dx, dy)));
+            let (nw, ne, sw, se) = (luma_at(-1, 1), luma_at(1, 1), luma_at(-1, -1), luma_at(1, -1));
+            let m = luma(layer.get(xy));
+            let luma_min = m.min(nw).min(ne).min(sw).min(se);
+            let luma_max = m.max(nw).max(ne).max(sw).max(se);
+
+            // blend perpendicular to the luma gradient, which is along the edge
+            let dir = Vec2::new(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+            let reduce = ((nw + ne + sw + se) * 0.25 * self.reduce_mul).max(self.reduce_min);
+            let scale = 1.0 / (dir.x.abs().min(dir.y.abs()) + reduce);
+            let dir = (dir * scale).map(|c| c.clamp(-self.span_max, self.span_max));
+
+            let inner = (layer.sample(center + dir * (1.0 / 3.0 - 0.5))
+                + layer.sample(center + dir * (2.0 / 3.0 - 0.5))) * 0.5;
+            let outer = inner * 0.5
+                + (layer.sample(center - dir * 0.5) + layer.sample(center + dir * 0.5)) * 0.25;
+
+            // the wider blend may have crossed into another edge
+            let outer_luma = luma(outer);
+            if outer_luma < luma_min || outer_luma > luma_max {
+                inner
+            } else {
+                outer
+            }
+        }).to_canvas(canvas)
+    }
+}
+
+/// Sharpens detail with a 3x3 kernel.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sharpen {
+    /// How much each pixel is pushed away from the average of its neighbors.
+    pub amount: f32,
+}
+
+impl Default for Sharpen {
+    fn default() -> Self {
+        Sharpen { amount: 1.0 }
+    }
+}
+
+impl Effect for Sharpen {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let a = self.amount;
+        Convolution::new(3, 3, vec![
+            0.0, -a, 0.0,
+            -a, 1.0 + 4.0 * a, -a,
+            0.0, -a, 0.0,
+        ]).apply(canvas)
+    }
+}
+
+/// Sobel edge detection on luma, drawing edges in white on black.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct EdgeDetect {
+    /// Multiplier for the gradient magnitude.
+    pub scale: f32,
+}
+
+impl Default for EdgeDetect {
+    fn default() -> Self {
+        EdgeDetect { scale: 1.0 }
+    }
+}
+
+impl Effect for EdgeDetect {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        Layer::from_fn(layer.size, |xy| {
+            let l = |dx, dy| luma(layer.get(xy + Vec2::new(dx, dy)));
+            let gx = (l(1, 1) + 2.0 * l(1, 0) + l(1, -1)) - (l(-1, 1) + 2.0 * l(-1, 0) + l(-1, -1));
+            let gy = (l(-1, 1) + 2.0 * l(0, 1) + l(1, 1)) - (l(-1, -1) + 2.0 * l(0, -1) + l(1, -1));
+            Rgb::broadcast((gx * gx + gy * gy).sqrt() * self.scale)
+        }).to_canvas(canvas)
+    }
+}
+
+/// Darkens the image towards its corners.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vignette {
+    /// How dark the image gets, in `[0, 1]`.
+    pub strength: f32,
+    /// Distance from the center at which the image is fully darkened, where the corners
+    /// are at 1.
+    pub radius: f32,
+    /// Distance over which darkening fades in, before `radius`.
+    pub softness: f32,
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette {
+            strength: 0.6,
+            radius: 1.0,
+            softness: 0.6,
+        }
+    }
+}
+
+impl Effect for Vignette {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        Layer::from_fn(layer.size, |xy| {
+            let distance = layer.offset_from_center(xy.map(|c| c as f32 + 0.5)).magnitude();
+            let t = ((distance - (self.radius - self.softness)) / self.softness).clamp(0.0, 1.0);
+            let smooth = t * t * (3.0 - 2.0 * t);
+            layer.get(xy) * (1.0 - self.strength * smooth)
+        }).to_canvas(canvas)
+    }
+}
+
+/// Shifts the red and blue channels in opposite directions, increasingly towards the
+/// edges, like a cheap lens.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ChromaticAberration {
+    /// Shift at the corners, in pixels.
+    pub offset: f32,
+}
+
+impl Default for ChromaticAberration {
+    fn default() -> Self {
+        ChromaticAberration { offset: 2.0 }
+    }
+}
+
+impl Effect for ChromaticAberration {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        Layer::from_fn(layer.size, |xy| {
+            let center = xy.map(|c| c as f32 + 0.5);
+            let shift = layer.offset_from_center(center) * self.offset;
+            Rgb::new(
+                layer.sample(center + shift).r,
+                layer.get(xy).g,
+                layer.sample(center - shift).b,
+            )
+        }).to_canvas(canvas)
+    }
+}
+
+/// Darkens every few rows, like the scanlines of a CRT.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Scanlines {
+    /// How much darker the scanlines are, in `[0, 1]`.
+    pub intensity: f32,
+    /// Rows per scanline, the last of which, counting from the top, is darkened.
+    pub period: usize,
+}
+
+impl Default for Scanlines {
+    fn default() -> Self {
+        Scanlines {
+            intensity: 0.3,
+            period: 2,
+        }
+    }
+}
+
+impl Effect for Scanlines {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        let y_size = layer.size.y;
+        let period = self.period.max(1);
+        Layer::from_fn(layer.size, |xy| {
+            let row_from_top = y_size - 1 - xy.y as usize;
+            if row_from_top % period == period - 1 {
+                layer.get(xy) * (1.0 - self.intensity)
+            } else {
+                layer.get(xy)
+            }
+        }).to_canvas(canvas)
+    }
+}
+
+/// Convolution with an arbitrary kernel, clamping to the edges of the canvas.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Convolution {
+    size: Vec2<usize>,
+    weights: Vec<f32>,
+}
+
+impl Convolution {
+    /// A kernel with weights in row-major order, with the first row being the top, as
+    /// it'd be written out. The kernel is centered on pixel `(x_size / 2, y_size / 2)`
+    /// of itself.
+    ///
+    /// Panics if the number of weights doesn't match the size.
+    pub fn new(x_size: usize, y_size: usize, weights: Vec<f32>) -> Self {
+        assert_eq!(weights.len(), x_size * y_size, "wrong number of convolution weights");
+        Convolution {
+            size: Vec2::new(x_size, y_size),
+            weights,
+        }
+    }
+
+    /// Scale the weights to sum to 1, so the kernel preserves brightness.
+    pub fn normalized(mut self) -> Self {
+        let sum: f32 = self.weights.iter().sum();
+        if sum != 0.0 {
+            for weight in &mut self.weights {
+                *weight /= sum;
+            }
+        }
+        self
+    }
+
+    pub fn size(&self) -> Vec2<usize> {
+        self.size
+    }
+
+    pub fn weights(&self) -> &[f32] {
+        &self.weights
+    }
+}
+
+impl Effect for Convolution {
+    fn apply(&self, canvas: &Canvas) -> Canvas {
+        let layer = Layer::from_canvas(canvas);
+        let anchor = self.size.map(|c| (c / 2) as i32);
+        Layer::from_fn(layer.size, |xy| {
+            let mut sum = Rgb::zero();
+            for (i, &weight) in self.weights.iter().enumerate() {
+                let k = Vec2::new(i % self.size.x, i / self.size.x).map(|c| c as i32);
+                // kernel rows go down, canvas rows go up
+                let offset = Vec2::new(k.x - anchor.x, anchor.y - k.y);
+                sum += layer.get(xy + offset) * weight;
+            }
+            sum
+        }).to_canvas(canvas)
+    }
+}
+
+/// Rec. 601 luma.
+fn luma(color: Rgb<f32>) -> f32 {
+    color.r * 0.299 + color.g * 0.587 + color.b * 0.114
+}
+
+/// Blur a layer horizontally, then vertically.
+fn blur(layer: &Layer, sigma: f32) -> Layer {
+    if sigma <= 0.0 {
+        return layer.clone();
+    }
+
+    let radius = (sigma * 3.0).ceil() as i32;
+    let mut kernel: Vec<f32> = (-radius..=radius)
+        .map(|i| (-(i * i) as f32 / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let sum: f32 = kernel.iter().sum();
+    for weight in &mut kernel {
+        *weight /= sum;
+    }
+
+    let pass = |layer: &Layer, axis: Vec2<i32>| Layer::from_fn(layer.size, |xy| {
+        let mut sum = Rgb::zero();
+        for (i, &weight) in kernel.iter().enumerate() {
+            sum += layer.get(xy + axis * (i as i32 - radius)) * weight;
+        }
+        sum
+    });
+    pass(&pass(layer, Vec2::unit_x()), Vec2::unit_y())
+}
+
+/// Color channels of a canvas as floats in `[0, 1]`, for intermediate results.
+#[derive(Clone, Debug)]
+struct Layer {
+    size: Vec2<usize>,
+    pixels: Vec<Rgb<f32>>,
+}
+
+impl Layer {
+    fn from_canvas(canvas: &Canvas) -> Self {
+        Layer {
+            size: canvas.size(),
+            pixels: canvas.pixels()
+                .par_iter()
+                .map(|c| c.rgb().map(|c| c as f32 / 255.0))
+                .collect(),
+        }
+    }
+
+    /// Compute every pixel in parallel.
+    fn from_fn(size: Vec2<usize>, f: impl Fn(Vec2<i32>) -> Rgb<f32> + Sync) -> Self {
+        Layer {
+            size,
+            pixels: (0..size.product())
+                .into_par_iter()
+                .map(|i| f(Vec2::new(i % size.x, i / size.x).map(|c| c as i32)))
+                .collect(),
+        }
+    }
+
+    /// Convert back to a canvas, with the alpha of another canvas of the same size.
+    fn to_canvas(&self, alpha: &Canvas) -> Canvas {
+        frag::render(self.size.x, self.size.y, |xy| {
+            let rgb = self.get(xy).map(|c| (c.clamp(0.0, 1.0) * 255.0 + 0.5) as u8);
+            Rgba::from_translucent(rgb, alpha.get(xy.x as usize, xy.y as usize).a)
+        })
+    }
+
+    /// Pixel at some coordinates, clamped to the edges.
+    fn get(&self, xy: Vec2<i32>) -> Rgb<f32> {
+        let max = self.size.map(|c| c as i32 - 1);
+        let xy = xy.map2(max, |c, max| c.clamp(0, max.max(0)) as usize);
+        self.pixels[xy.y * self.size.x + xy.x]
+    }
+
+    /// Bilinear sample, with pixel centers at half-integer coordinates.
+    fn sample(&self, p: Vec2<f32>) -> Rgb<f32> {
+        let p = p - 0.5;
+        let floor = p.map(f32::floor);
+        let t = p - floor;
+        let xy = floor.map(|c| c as i32);
+        let bottom = Lerp::lerp(self.get(xy), self.get(xy + Vec2::unit_x()), t.x);
+        let top = Lerp::lerp(self.get(xy + Vec2::unit_y()), self.get(xy + 1), t.x);
+        Lerp::lerp(bottom, top, t.y)
+    }
+
+    /// Offset of a point from the center, scaled so that the corners are at distance 1.
+    fn offset_from_center(&self, p: Vec2<f32>) -> Vec2<f32> {
+        let half = self.size.map(|c| c as f32 / 2.0);
+        (p - half) / half.magnitude()
+    }
+}
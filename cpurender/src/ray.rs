@@ -0,0 +1,22 @@
+use vek::*;
+
+/// A half-line, starting at an origin and extending in a direction.
+///
+/// Distances along a ray are measured in multiples of `dir`, so they are world-space
+/// distances if `dir` is normalized.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vec3<f32>,
+    pub dir: Vec3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vec3<f32>, dir: Vec3<f32>) -> Self {
+        Ray { origin, dir }
+    }
+
+    /// The point at some distance along the ray.
+    pub fn at(&self, t: f32) -> Vec3<f32> {
+        self.origin + self.dir * t
+    }
+}
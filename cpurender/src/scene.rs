@@ -0,0 +1,429 @@
+pub use crate::ray::Ray;
+
+use std::f32;
+
+use vek::*;
+
+/// Where a ray hits a surface.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Hit {
+    /// Distance along the ray, in multiples of its direction.
+    pub distance: f32,
+    pub point: Vec3<f32>,
+    /// Unit normal, pointing out of the surface's front side, even when hit from behind.
+    pub normal: Vec3<f32>,
+}
+
+impl Hit {
+    fn new(ray: Ray, distance: f32, normal: Vec3<f32>) -> Self {
+        Hit {
+            distance,
+            point: ray.at(distance),
+            normal,
+        }
+    }
+
+    /// The normal, flipped if necessary to face against a ray direction.
+    pub fn facing(&self, dir: Vec3<f32>) -> Vec3<f32> {
+        if self.normal.dot(dir) > 0.0 {
+            -self.normal
+        } else {
+            self.normal
+        }
+    }
+}
+
+/// Something a ray can hit.
+pub trait Intersect {
+    /// The nearest hit with a distance in `[0, max_distance]`.
+    ///
+    /// The ray direction need not be normalized.
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit>;
+
+    /// Whether the ray hits anything in `[0, max_distance]`, such as for shadow rays.
+    fn occludes(&self, ray: Ray, max_distance: f32) -> bool {
+        self.intersect(ray, max_distance).is_some()
+    }
+
+    /// Wrap this in an affine transform from its local space to world space.
+    fn transformed(self, transform: impl Into<Mat4<f32>>) -> Transformed<Self>
+        where Self: Sized {
+        Transformed::new(self, transform)
+    }
+}
+
+impl<T: Intersect + ?Sized> Intersect for Box<T> {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        (**self).intersect(ray, max_distance)
+    }
+}
+
+fn in_range(t: f32, max_distance: f32) -> bool {
+    t >= 0.0 && t <= max_distance
+}
+
+/// Roots of `a t² + 2 half_b t + c`, in ascending order, if real.
+fn solve_quadratic(a: f32, half_b: f32, c: f32) -> Option<(f32, f32)> {
+    let discriminant = half_b * half_b - a * c;
+    if discriminant < 0.0 || a == 0.0 {
+        return None;
+    }
+    let sqrt = discriminant.sqrt();
+    let (t0, t1) = ((-half_b - sqrt) / a, (-half_b + sqrt) / a);
+    Some((t0.min(t1), t0.max(t1)))
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vec3<f32>,
+    pub radius: f32,
+}
+
+impl Intersect for Sphere {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let oc = ray.origin - self.center;
+        let (t0, t1) = solve_quadratic(
+            ray.dir.magnitude_squared(),
+            oc.dot(ray.dir),
+            oc.magnitude_squared() - self.radius * self.radius,
+        )?;
+        let t = if in_range(t0, max_distance) { t0 } else { t1 };
+        if !in_range(t, max_distance) {
+            return None;
+        }
+        let normal = (ray.at(t) - self.center) / self.radius;
+        Some(Hit::new(ray, t, normal))
+    }
+}
+
+/// An infinite plane, whose front side faces along its normal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Plane {
+    pub point: Vec3<f32>,
+    /// Unit normal.
+    pub normal: Vec3<f32>,
+}
+
+impl Plane {
+    /// Distance along the ray to the plane, if not parallel.
+    fn distance(&self, ray: Ray) -> Option<f32> {
+        let denominator = self.normal.dot(ray.dir);
+        if denominator == 0.0 {
+            return None;
+        }
+        Some(self.normal.dot(self.point - ray.origin) / denominator)
+    }
+}
+
+impl Intersect for Plane {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let t = self.distance(ray).filter(|&t| in_range(t, max_distance))?;
+        Some(Hit::new(ray, t, self.normal))
+    }
+}
+
+/// An axis-aligned box.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vec3<f32>,
+    pub max: Vec3<f32>,
+}
+
+impl Intersect for Aabb {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        // intersect the slabs between each axis' pair of planes
+        let mut near = (f32::NEG_INFINITY, 0);
+        let mut far = (f32::INFINITY, 0);
+        for a in 0..3 {
+            if ray.dir[a] == 0.0 {
+                if ray.origin[a] < self.min[a] || ray.origin[a] > self.max[a] {
+                    return None;
+                }
+                continue;
+            }
+            let t0 = (self.min[a] - ray.origin[a]) / ray.dir[a];
+            let t1 = (self.max[a] - ray.origin[a]) / ray.dir[a];
+            let (t0, t1) = (t0.min(t1), t0.max(t1));
+            if t0 > near.0 {
+                near = (t0, a);
+            }
+            if t1 < far.0 {
+                far = (t1, a);
+            }
+        }
+        if near.0 > far.0 {
+            return None;
+        }
+
+        // entering through the near face, or leaving through the far face from inside
+        let (t, axis, sign) = if near.0 >= 0.0 {
+            (near.0, near.1, -ray.dir[near.1].signum())
+        } else {
+            (far.0, far.1, ray.dir[far.1].signum())
+        };
+        if !in_range(t, max_distance) {
+            return None;
+        }
+        let mut normal = Vec3::zero();
+        normal[axis] = sign;
+        Some(Hit::new(ray, t, normal))
+    }
+}
+
+/// A box with arbitrary orientation.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Obb {
+    pub center: Vec3<f32>,
+    /// Half the box's size along each of its local axes.
+    pub half_extents: Vec3<f32>,
+    pub orientation: Quaternion<f32>,
+}
+
+impl Intersect for Obb {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let to_local = self.orientation.conjugate();
+        let local_ray = Ray::new(
+            to_local * (ray.origin - self.center),
+            to_local * ray.dir,
+        );
+        let local_box = Aabb {
+            min: -self.half_extents,
+            max: self.half_extents,
+        };
+        let hit = local_box.intersect(local_ray, max_distance)?;
+        Some(Hit::new(ray, hit.distance, self.orientation * hit.normal))
+    }
+}
+
+/// A triangle, whose front side faces along `(b - a) × (c - a)`.
+///
+/// It can be hit from both sides.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Triangle {
+    pub a: Vec3<f32>,
+    pub b: Vec3<f32>,
+    pub c: Vec3<f32>,
+}
+
+impl Triangle {
+    /// Unit normal of the front side.
+    pub fn normal(&self) -> Vec3<f32> {
+        (self.b - self.a).cross(self.c - self.a).normalized()
+    }
+}
+
+impl Intersect for Triangle {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        // Möller–Trumbore
+        let ab = self.b - self.a;
+        let ac = self.c - self.a;
+        let p = ray.dir.cross(ac);
+        let determinant = ab.dot(p);
+        if determinant == 0.0 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+
+        let ao = ray.origin - self.a;
+        let u = ao.dot(p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = ao.cross(ab);
+        let v = ray.dir.dot(q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+
+        let t = ac.dot(q) * inverse;
+        if !in_range(t, max_distance) {
+            return None;
+        }
+        Some(Hit::new(ray, t, self.normal()))
+    }
+}
+
+/// A flat disc, whose front side faces along its normal.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Disc {
+    pub center: Vec3<f32>,
+    /// Unit normal.
+    pub normal: Vec3<f32>,
+    pub radius: f32,
+}
+
+impl Intersect for Disc {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let plane = Plane {
+            point: self.center,
+            normal: self.normal,
+        };
+        let hit = plane.intersect(ray, max_distance)?;
+        if hit.point.distance_squared(self.center) > self.radius * self.radius {
+            return None;
+        }
+        Some(hit)
+    }
+}
+
+/// A capped cylinder between two points.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Cylinder {
+    pub a: Vec3<f32>,
+    pub b: Vec3<f32>,
+    pub radius: f32,
+}
+
+impl Intersect for Cylinder {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let length = self.a.distance(self.b);
+        let axis = (self.b - self.a) / length;
+        let mut nearest: Option<Hit> = None;
+        let mut consider = |hit: Hit| {
+            if nearest.map(|n| hit.distance < n.distance).unwrap_or(true) {
+                nearest = Some(hit);
+            }
+        };
+
+        // the side, as an infinite cylinder clipped to the segment
+        let ao = ray.origin - self.a;
+        let ao_perp = ao - axis * ao.dot(axis);
+        let dir_perp = ray.dir - axis * ray.dir.dot(axis);
+        let roots = solve_quadratic(
+            dir_perp.magnitude_squared(),
+            ao_perp.dot(dir_perp),
+            ao_perp.magnitude_squared() - self.radius * self.radius,
+        );
+        if let Some((t0, t1)) = roots {
+            for &t in &[t0, t1] {
+                if !in_range(t, max_distance) {
+                    continue;
+                }
+                let along = (ray.at(t) - self.a).dot(axis);
+                if along >= 0.0 && along <= length {
+                    let normal = (ao_perp + dir_perp * t) / self.radius;
+                    consider(Hit::new(ray, t, normal));
+                }
+            }
+        }
+
+        // the caps
+        for &(center, normal) in &[(self.a, -axis), (self.b, axis)] {
+            let cap = Disc {
+                center,
+                normal,
+                radius: self.radius,
+            };
+            if let Some(hit) = cap.intersect(ray, max_distance) {
+                consider(hit);
+            }
+        }
+
+        nearest
+    }
+}
+
+/// Some other shape, moved by an affine transform from its local space to world space.
+///
+/// Distances are preserved, since rays are transformed into local space without
+/// renormalizing their direction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transformed<T> {
+    pub inner: T,
+    to_local: Mat4<f32>,
+    /// Inverse transpose of the transform, for normals.
+    normal_matrix: Mat4<f32>,
+}
+
+impl<T> Transformed<T> {
+    /// Wrap a shape in a transform, such as a `Mat4`, `Quaternion`, or `vek::Transform`.
+    pub fn new(inner: T, transform: impl Into<Mat4<f32>>) -> Self {
+        let to_local = transform.into().inverted();
+        Transformed {
+            inner,
+            to_local,
+            normal_matrix: to_local.transposed(),
+        }
+    }
+}
+
+impl<T: Intersect> Intersect for Transformed<T> {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        let local_ray = Ray::new(
+            self.to_local.mul_point(ray.origin),
+            self.to_local.mul_direction(ray.dir),
+        );
+        let hit = self.inner.intersect(local_ray, max_distance)?;
+        let normal = self.normal_matrix.mul_direction(hit.normal).normalized();
+        Some(Hit::new(ray, hit.distance, normal))
+    }
+}
+
+/// A shape which can be stored in a `Scene`.
+pub type Object = Box<dyn Intersect + Send + Sync>;
+
+/// A collection of shapes, each with a material, which finds the closest hit.
+///
+/// A scene is `Send + Sync` so long as its materials are, so it can be used as the
+/// shared state of `frag::fragment_stateful`.
+pub struct Scene<M> {
+    objects: Vec<(Object, M)>,
+}
+
+impl<M> Default for Scene<M> {
+    fn default() -> Self {
+        Scene {
+            objects: Vec::new(),
+        }
+    }
+}
+
+impl<M> Scene<M> {
+    pub fn new() -> Self {
+        Scene::default()
+    }
+
+    /// Add a shape with a material.
+    pub fn add(&mut self, object: impl Intersect + Send + Sync + 'static, material: M) {
+        self.objects.push((Box::new(object), material));
+    }
+
+    /// Builder-style `add`.
+    pub fn with(mut self, object: impl Intersect + Send + Sync + 'static, material: M) -> Self {
+        self.add(object, material);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// The closest hit in `[0, max_distance]`, and the material of the shape hit.
+    pub fn hit(&self, ray: Ray, max_distance: f32) -> Option<(Hit, &M)> {
+        let mut nearest = None;
+        let mut max_distance = max_distance;
+        for (object, material) in &self.objects {
+            if let Some(hit) = object.intersect(ray, max_distance) {
+                max_distance = hit.distance;
+                nearest = Some((hit, material));
+            }
+        }
+        nearest
+    }
+}
+
+impl<M> Intersect for Scene<M> {
+    fn intersect(&self, ray: Ray, max_distance: f32) -> Option<Hit> {
+        self.hit(ray, max_distance).map(|(hit, _)| hit)
+    }
+
+    fn occludes(&self, ray: Ray, max_distance: f32) -> bool {
+        self.objects
+            .iter()
+            .any(|(object, _)| object.occludes(ray, max_distance))
+    }
+}
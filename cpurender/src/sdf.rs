@@ -0,0 +1,244 @@
+use crate::scene::{Hit, Ray};
+
+use vek::*;
+
+// Signed distance functions take a point, and return its distance to the surface:
+// positive outside, and negative inside. They're written shader-style, as free functions
+// which compose by calling each other, so an SDF scene is just a closure
+// `Fn(Vec3<f32>) -> f32`.
+
+// ==== primitives ====
+
+/// Sphere centered on the origin.
+pub fn sphere(p: Vec3<f32>, radius: f32) -> f32 {
+    p.magnitude() - radius
+}
+
+/// Box centered on the origin, with half its size along each axis.
+pub fn cuboid(p: Vec3<f32>, half_extents: Vec3<f32>) -> f32 {
+    let q = p.map(f32::abs) - half_extents;
+    q.map(|c| c.max(0.0)).magnitude() + q.reduce_partial_max().min(0.0)
+}
+
+/// Box with its edges rounded off by a radius, staying within the same bounds.
+pub fn round_cuboid(p: Vec3<f32>, half_extents: Vec3<f32>, radius: f32) -> f32 {
+    cuboid(p, half_extents - Vec3::broadcast(radius)) - radius
+}
+
+/// Infinite plane through the origin, with a unit normal.
+pub fn plane(p: Vec3<f32>, normal: Vec3<f32>) -> f32 {
+    p.dot(normal)
+}
+
+/// Torus centered on the origin, lying in the xz plane.
+pub fn torus(p: Vec3<f32>, major_radius: f32, minor_radius: f32) -> f32 {
+    let q = Vec2::new(Vec2::new(p.x, p.z).magnitude() - major_radius, p.y);
+    q.magnitude() - minor_radius
+}
+
+/// Line segment between two points, thickened by a radius.
+pub fn capsule(p: Vec3<f32>, a: Vec3<f32>, b: Vec3<f32>, radius: f32) -> f32 {
+    let pa = p - a;
+    let ba = b - a;
+    let h = (pa.dot(ba) / ba.magnitude_squared()).clamp(0.0, 1.0);
+    (pa - ba * h).magnitude() - radius
+}
+
+/// Capped cylinder centered on the origin, along the y axis.
+pub fn cylinder(p: Vec3<f32>, half_height: f32, radius: f32) -> f32 {
+    let d = Vec2::new(Vec2::new(p.x, p.z).magnitude(), p.y.abs())
+        - Vec2::new(radius, half_height);
+    d.x.max(d.y).min(0.0) + d.map(|c| c.max(0.0)).magnitude()
+}
+
+// ==== combining ====
+
+pub fn union(a: f32, b: f32) -> f32 {
+    a.min(b)
+}
+
+pub fn intersection(a: f32, b: f32) -> f32 {
+    a.max(b)
+}
+
+/// `a` with `b` cut out of it.
+pub fn subtraction(a: f32, b: f32) -> f32 {
+    a.max(-b)
+}
+
+/// Union which blends the surfaces together within a distance `k`.
+pub fn smooth_union(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    lerp(b, a, h) - k * h * (1.0 - h)
+}
+
+/// Intersection which blends the surfaces together within a distance `k`.
+pub fn smooth_intersection(a: f32, b: f32, k: f32) -> f32 {
+    let h = (0.5 - 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    lerp(b, a, h) + k * h * (1.0 - h)
+}
+
+/// Subtraction which blends the surfaces together within a distance `k`.
+pub fn smooth_subtraction(a: f32, b: f32, k: f32) -> f32 {
+    smooth_intersection(a, -b, k)
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// ==== domain operations ====
+//
+// These map a point into the local space of a shape, before passing it to the shape's
+// distance function.
+
+/// Move a shape by an offset.
+pub fn translate(p: Vec3<f32>, offset: Vec3<f32>) -> Vec3<f32> {
+    p - offset
+}
+
+/// Rotate a shape about the origin.
+pub fn rotate(p: Vec3<f32>, rotation: Quaternion<f32>) -> Vec3<f32> {
+    rotation.conjugate() * p
+}
+
+/// Uniformly scale a shape about the origin.
+///
+/// Unlike the other domain operations this needs to correct the distance too, so it
+/// takes the shape's distance function.
+pub fn scale(p: Vec3<f32>, factor: f32, sdf: impl Fn(Vec3<f32>) -> f32) -> f32 {
+    sdf(p / factor) * factor
+}
+
+/// Mirror a shape across the planes through the origin, on axes where `mask` is set.
+pub fn mirror(p: Vec3<f32>, mask: Vec3<bool>) -> Vec3<f32> {
+    p.map2(mask, |c, m| if m { c.abs() } else { c })
+}
+
+/// Repeat a shape infinitely, with some period along each axis. Axes with a period of
+/// 0 aren't repeated.
+///
+/// The shape should fit within a cell centered on the origin, or distances may be
+/// overestimated.
+pub fn repeat(p: Vec3<f32>, period: Vec3<f32>) -> Vec3<f32> {
+    p.map2(period, |c, s| if s == 0.0 { c } else { c - s * (c / s).round() })
+}
+
+/// Repeat a shape with some period, out to `limit` copies in each direction from the
+/// origin.
+pub fn repeat_limited(p: Vec3<f32>, period: Vec3<f32>, limit: Vec3<f32>) -> Vec3<f32> {
+    let cell = (p / period).round().map2(limit, |c, l| c.clamp(-l, l));
+    p - period * cell
+}
+
+// ==== rendering ====
+
+/// Configuration for sphere tracing.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SphereTracer {
+    /// Distance from the surface at which a ray counts as hitting it.
+    pub epsilon: f32,
+    /// Maximum number of steps before giving up.
+    pub max_steps: u32,
+    /// Maximum world-space distance to trace.
+    pub max_distance: f32,
+}
+
+impl Default for SphereTracer {
+    fn default() -> Self {
+        SphereTracer {
+            epsilon: 0.001,
+            max_steps: 128,
+            max_distance: 100.0,
+        }
+    }
+}
+
+impl SphereTracer {
+    /// March a ray along the distance field to the surface, estimating the normal there.
+    ///
+    /// Like with `scene::Intersect`, the hit distance is in multiples of the ray
+    /// direction, which need not be normalized.
+    pub fn trace(&self, sdf: impl Fn(Vec3<f32>) -> f32, ray: Ray) -> Option<Hit> {
+        let length = ray.dir.magnitude();
+        let dir = ray.dir / length;
+
+        let mut t = 0.0;
+        for _ in 0..self.max_steps {
+            let point = ray.origin + dir * t;
+            let d = sdf(point);
+            if d.abs() < self.epsilon {
+                return Some(Hit {
+                    distance: t / length,
+                    point,
+                    normal: normal(&sdf, point, self.epsilon),
+                });
+            }
+            t += d.abs();
+            if t > self.max_distance {
+                break;
+            }
+        }
+        None
+    }
+}
+
+/// Estimate the unit surface normal at a point, by central differences.
+pub fn normal(sdf: impl Fn(Vec3<f32>) -> f32, p: Vec3<f32>, epsilon: f32) -> Vec3<f32> {
+    let axis = |a: Vec3<f32>| sdf(p + a * epsilon) - sdf(p - a * epsilon);
+    Vec3::new(
+        axis(Vec3::unit_x()),
+        axis(Vec3::unit_y()),
+        axis(Vec3::unit_z()),
+    ).normalized()
+}
+
+/// Penumbra shadow factor from marching towards a light, from 0 (fully shadowed) to 1
+/// (fully lit).
+///
+/// The ray should start at the surface and point towards the light, and is marched from
+/// `min_distance` to `max_distance`. Larger `hardness` gives sharper shadows.
+pub fn soft_shadow(
+    sdf: impl Fn(Vec3<f32>) -> f32,
+    ray: Ray,
+    min_distance: f32,
+    max_distance: f32,
+    hardness: f32,
+) -> f32 {
+    let dir = ray.dir.normalized();
+    let mut light: f32 = 1.0;
+    let mut t = min_distance;
+    for _ in 0..256 {
+        if t >= max_distance {
+            break;
+        }
+        let d = sdf(ray.origin + dir * t);
+        if d < 0.0001 {
+            return 0.0;
+        }
+        light = light.min(hardness * d / t);
+        t += d;
+    }
+    light.clamp(0.0, 1.0)
+}
+
+/// Ambient occlusion at a surface point, from sampling the distance field at `samples`
+/// points along the normal, `spacing` apart. From 0 (fully occluded) to 1 (unoccluded).
+pub fn ambient_occlusion(
+    sdf: impl Fn(Vec3<f32>) -> f32,
+    point: Vec3<f32>,
+    normal: Vec3<f32>,
+    samples: u32,
+    spacing: f32,
+) -> f32 {
+    // each sample is occluded by how much closer the surface is than it would be if flat,
+    // with nearer samples weighted more
+    let mut occlusion = 0.0;
+    let mut weight = 1.0;
+    for i in 1..=samples {
+        let h = spacing * i as f32;
+        occlusion += (h - sdf(point + normal * h)) * weight;
+        weight *= 0.5;
+    }
+    (1.0 - occlusion / spacing).clamp(0.0, 1.0)
+}
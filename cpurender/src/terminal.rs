@@ -0,0 +1,223 @@
+use crate::{
+    canvas::Canvas,
+    Paint,
+};
+
+use std::{
+    fmt::Write as _,
+    io::{self, Write as _},
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crossbeam::queue::SegQueue;
+use vek::*;
+
+/// Minimum time between redraws of the terminal.
+const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+/// How long to sleep when there's nothing to do.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// Size used if the terminal size can't be determined, such as when piped.
+const FALLBACK_SIZE: Vec2<usize> = Vec2 { x: 80, y: 24 };
+
+/// Present software rendering in the terminal, using 24-bit color ANSI escapes.
+///
+/// Each character cell is an upper half block, colored to show two vertically adjacent
+/// pixels. The canvas is downscaled to fit the terminal if necessary, and only cells which
+/// have changed are redrawn. Terminals can't show transparency, so pixels are composited
+/// over black.
+///
+/// Like `open_window`, this takes over the current thread, and calls the provided closure
+/// in its own thread with a queue that can be sent draw instructions. It returns once the
+/// drawing thread has finished and all of its instructions have been shown.
+pub fn open_terminal(
+    x_size: usize,
+    y_size: usize,
+    draw_thread: impl FnOnce(Arc<SegQueue<Paint>>) + Send + 'static,
+) {
+    let paint_queue_0 = Arc::new(SegQueue::new());
+    let paint_queue_1 = paint_queue_0.clone();
+    let handle = thread::spawn(move || draw_thread(paint_queue_1));
+
+    let mut canvas = Canvas::filled(x_size, y_size, Rgba::black());
+    let mut presenter = Presenter::new();
+    print(presenter.begin());
+
+    let mut dirty = true;
+    let mut last_present = Instant::now() - FRAME_INTERVAL;
+    loop {
+        let finished = handle.is_finished();
+
+        // apply instructions from the paint queue
+        while let Ok(Paint { x, y, r, g, b, a }) = paint_queue_0.pop() {
+            canvas.put(Vec2::new(x as i32, y as i32), Rgba::new(r, g, b, a));
+            dirty = true;
+        }
+
+        if finished {
+            print(&presenter.draw(&canvas, terminal_size()));
+            break;
+        }
+        if dirty && last_present.elapsed() >= FRAME_INTERVAL {
+            print(&presenter.draw(&canvas, terminal_size()));
+            dirty = false;
+            last_present = Instant::now();
+        } else {
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    print(&presenter.end());
+    if handle.join().is_err() {
+        error!("drawing thread panicked");
+    }
+}
+
+/// Size of the terminal in characters.
+fn terminal_size() -> Vec2<usize> {
+    terminal_size::terminal_size()
+        .map(|(w, h)| Vec2::new(w.0 as usize, h.0 as usize))
+        .unwrap_or(FALLBACK_SIZE)
+}
+
+/// Write escapes to stdout immediately.
+fn print(escapes: &str) {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let _ = stdout.write_all(escapes.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Colors of the top and bottom pixel of a character cell.
+type Cell = (Rgb<u8>, Rgb<u8>);
+
+/// Converts canvases into ANSI escapes, remembering what's on screen so that only cells
+/// which have changed are redrawn.
+#[derive(Clone, Debug, Default)]
+pub struct Presenter {
+    /// Terminal size in characters.
+    term_size: Vec2<usize>,
+    /// Cells currently on screen, row-major from the top, or empty if unknown.
+    cells: Vec<Cell>,
+    /// Size in cells of the image currently on screen.
+    image_size: Vec2<usize>,
+}
+
+impl Presenter {
+    pub fn new() -> Self {
+        Presenter::default()
+    }
+
+    /// Escapes to hide the cursor and clear the screen.
+    pub fn begin(&self) -> &'static str {
+        "\x1b[?25l\x1b[2J"
+    }
+
+    /// Escapes to reset colors, show the cursor, and move it below the image.
+    pub fn end(&self) -> String {
+        format!("\x1b[0m\x1b[{};1H\x1b[?25h", self.image_size.y + 1)
+    }
+
+    /// Escapes to draw a canvas in a terminal of some size in characters.
+    ///
+    /// The canvas is downscaled to fit if necessary, leaving the last line for the cursor.
+    /// Only cells which differ from the previous call are drawn, unless the terminal or
+    /// image size changed.
+    pub fn draw(&mut self, canvas: &Canvas, term_size: Vec2<usize>) -> String {
+        let mut out = String::new();
+
+        // fit within the terminal, never upscaling
+        let max_pixels = Vec2::new(term_size.x, term_size.y.saturating_sub(1) * 2);
+        let scale = (max_pixels.x as f32 / canvas.x_size() as f32)
+            .min(max_pixels.y as f32 / canvas.y_size() as f32)
+            .min(1.0);
+        let pixels = if canvas.size().product() == 0 {
+            // nothing to draw, but the screen is still cleared of any previous image
+            Vec2::zero()
+        } else {
+            canvas.size().map(|c| ((c as f32 * scale) as usize).max(1))
+        };
+        let image = downscale(canvas, pixels);
+        let image_size = Vec2::new(pixels.x, pixels.y.div_ceil(2));
+        if term_size != self.term_size || image_size != self.image_size {
+            // resized, so redraw from scratch
+            self.term_size = term_size;
+            self.image_size = image_size;
+            self.cells.clear();
+            out.push_str("\x1b[0m\x1b[2J");
+        }
+        let full_redraw = self.cells.is_empty();
+        self.cells.resize(image_size.product(), (Rgb::black(), Rgb::black()));
+
+        // last colors written, and where the cursor is, to avoid redundant escapes
+        let mut colors: Option<Cell> = None;
+        let mut cursor: Option<Vec2<usize>> = None;
+
+        for row in 0..image_size.y {
+            for col in 0..image_size.x {
+                // canvas y points up, so the top pixel of the top row is the last canvas row
+                let top = image.get(col, pixels.y - 1 - row * 2).rgb();
+                let bottom = match (pixels.y - 1).checked_sub(row * 2 + 1) {
+                    Some(y) => image.get(col, y).rgb(),
+                    None => Rgb::black(),
+                };
+                let cell = (top, bottom);
+
+                let i = row * image_size.x + col;
+                if !full_redraw && self.cells[i] == cell {
+                    continue;
+                }
+                self.cells[i] = cell;
+
+                if cursor != Some(Vec2::new(col, row)) {
+                    let _ = write!(out, "\x1b[{};{}H", row + 1, col + 1);
+                }
+                if colors.map(|c| c.0) != Some(top) {
+                    let _ = write!(out, "\x1b[38;2;{};{};{}m", top.r, top.g, top.b);
+                }
+                if colors.map(|c| c.1) != Some(bottom) {
+                    let _ = write!(out, "\x1b[48;2;{};{};{}m", bottom.r, bottom.g, bottom.b);
+                }
+                out.push('▀');
+                colors = Some(cell);
+                cursor = Some(Vec2::new(col + 1, row));
+            }
+        }
+
+        out
+    }
+}
+
+/// Box-filter a canvas down to a smaller size, compositing it over black.
+///
+/// The result is opaque. Each of its pixels is the average of the canvas pixels it covers,
+/// which are at least one. An empty canvas downscales to black.
+pub fn downscale(canvas: &Canvas, size: Vec2<usize>) -> Canvas {
+    let canvas_size = canvas.size();
+    if canvas_size.product() == 0 {
+        return Canvas::filled(size.x, size.y, Rgba::black());
+    }
+    let mut image = Canvas::new(size.x, size.y);
+    for y in 0..size.y {
+        for x in 0..size.x {
+            let xy = Vec2::new(x, y);
+            let min = (xy * canvas_size) / size;
+            let max = ((xy + 1) * canvas_size / size).map2(min, |c, m| c.max(m + 1));
+
+            let mut sum: Rgb<u32> = Rgb::zero();
+            for y in min.y..max.y {
+                for x in min.x..max.x {
+                    let color = canvas.get(x, y);
+                    sum += color.rgb().map(|c| c as u32 * color.a as u32);
+                }
+            }
+            let count = ((max.x - min.x) * (max.y - min.y)) as u32 * 255;
+            let average = sum.map(|c| ((c + count / 2) / count) as u8);
+            image.set(x, y, Rgba::from_opaque(average));
+        }
+    }
+    image
+}
@@ -0,0 +1,528 @@
+use crate::canvas::Canvas;
+
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::Path,
+};
+
+use vek::*;
+
+/// A single character's bitmap.
+#[derive(Clone, Debug)]
+pub struct Glyph {
+    /// Size of the bitmap, in font pixels.
+    pub size: Vec2<usize>,
+    /// Position of the bitmap's bottom-left corner relative to the pen on the baseline,
+    /// with the y axis pointing up.
+    pub offset: Vec2<i32>,
+    /// How far to move the pen after drawing this glyph.
+    pub advance: i32,
+    /// Bitmap rows, starting from the top.
+    bits: Vec<bool>,
+}
+
+impl Glyph {
+    /// Whether a bitmap pixel is set, with `(0, 0)` being the top-left.
+    pub fn bit(&self, x: usize, y: usize) -> bool {
+        self.bits[y * self.size.x + x]
+    }
+}
+
+/// A bitmap font.
+#[derive(Clone, Debug)]
+pub struct Font {
+    /// Font pixels above the baseline.
+    pub ascent: i32,
+    /// Font pixels below the baseline.
+    pub descent: i32,
+    glyphs: HashMap<char, Glyph>,
+}
+
+/// Error loading a font.
+#[derive(Debug)]
+pub enum FontError {
+    Io(io::Error),
+    /// The data was not a font in a format we understand.
+    Malformed(String),
+}
+
+impl fmt::Display for FontError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FontError::Io(e) => write!(f, "io error: {}", e),
+            FontError::Malformed(msg) => write!(f, "malformed font: {}", msg),
+        }
+    }
+}
+
+impl Error for FontError {}
+
+impl From<io::Error> for FontError {
+    fn from(e: io::Error) -> Self {
+        FontError::Io(e)
+    }
+}
+
+fn malformed<T>(msg: impl Into<String>) -> Result<T, FontError> {
+    Err(FontError::Malformed(msg.into()))
+}
+
+impl Font {
+    /// The built-in 8x8 font, covering printable ASCII.
+    pub fn embedded() -> Self {
+        let mut glyphs = HashMap::new();
+        for (i, rows) in FONT_8X8.iter().enumerate() {
+            let bits = rows.iter()
+                .flat_map(|&row| (0..8).map(move |x| row & (1 << x) != 0))
+                .collect();
+            glyphs.insert((0x20 + i as u8) as char, Glyph {
+                size: Vec2::new(8, 8),
+                offset: Vec2::new(0, -1),
+                advance: 8,
+                bits,
+            });
+        }
+        Font {
+            ascent: 7,
+            descent: 1,
+            glyphs,
+        }
+    }
+
+    /// Load a PSF or BDF font from a file, detecting the format.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, FontError> {
+        let bytes = fs::read(path)?;
+        if bytes.starts_with(&PSF1_MAGIC) || bytes.starts_with(&PSF2_MAGIC) {
+            Font::from_psf(&bytes)
+        } else {
+            match String::from_utf8(bytes) {
+                Ok(text) => Font::from_bdf(&text),
+                Err(_) => malformed("neither a PSF nor a BDF font"),
+            }
+        }
+    }
+
+    /// Parse a PC screen font, version 1 or 2.
+    pub fn from_psf(bytes: &[u8]) -> Result<Self, FontError> {
+        let read_u32 = |i: usize| -> Result<u32, FontError> {
+            match bytes.get(i..i + 4) {
+                Some(b) => Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+                None => malformed("truncated header"),
+            }
+        };
+
+        // parse header
+        let (header_len, count, glyph_len, width, height, has_table, v2) =
+            if bytes.starts_with(&PSF1_MAGIC) {
+                if bytes.len() < 4 {
+                    return malformed("truncated header");
+                }
+                let mode = bytes[2];
+                let height = bytes[3] as usize;
+                let count = if mode & 0x01 != 0 { 512 } else { 256 };
+                (4, count, height, 8, height, mode & 0x06 != 0, false)
+            } else if bytes.starts_with(&PSF2_MAGIC) {
+                let header_len = read_u32(8)? as usize;
+                let flags = read_u32(12)?;
+                let count = read_u32(16)? as usize;
+                let glyph_len = read_u32(20)? as usize;
+                let height = read_u32(24)? as usize;
+                let width = read_u32(28)? as usize;
+                (header_len, count, glyph_len, width, height, flags & 0x01 != 0, true)
+            } else {
+                return malformed("bad PSF magic number");
+            };
+
+        let row_len = width.div_ceil(8);
+        if width == 0 || height == 0 || glyph_len < row_len * height {
+            return malformed("bad glyph dimensions");
+        }
+        let table_start = header_len + count * glyph_len;
+        if bytes.len() < table_start {
+            return malformed("truncated glyph data");
+        }
+
+        // parse bitmaps
+        let bitmaps: Vec<Glyph> = (0..count)
+            .map(|i| {
+                let data = &bytes[header_len + i * glyph_len..];
+                let bits = (0..height)
+                    .flat_map(|y| (0..width)
+                        .map(move |x| data[y * row_len + x / 8] & (0x80 >> (x % 8)) != 0))
+                    .collect();
+                Glyph {
+                    size: Vec2::new(width, height),
+                    offset: Vec2::zero(),
+                    advance: width as i32,
+                    bits,
+                }
+            })
+            .collect();
+
+        // map characters to glyphs
+        let mut glyphs = HashMap::new();
+        if has_table {
+            let mut table = &bytes[table_start..];
+            for glyph in &bitmaps {
+                let (chars, rest) = if v2 {
+                    psf2_table_entry(table)?
+                } else {
+                    psf1_table_entry(table)?
+                };
+                table = rest;
+                for c in chars {
+                    glyphs.entry(c).or_insert_with(|| glyph.clone());
+                }
+            }
+        } else {
+            for (i, glyph) in bitmaps.into_iter().enumerate() {
+                if let Some(c) = std::char::from_u32(i as u32) {
+                    glyphs.insert(c, glyph);
+                }
+            }
+        }
+
+        Ok(Font {
+            ascent: height as i32,
+            descent: 0,
+            glyphs,
+        })
+    }
+
+    /// Parse a Glyph Bitmap Distribution Format font.
+    pub fn from_bdf(text: &str) -> Result<Self, FontError> {
+        fn nums(args: &[&str]) -> Result<Vec<i32>, FontError> {
+            args.iter()
+                .map(|s| s.parse().or_else(|_| malformed(format!("bad number {:?}", s))))
+                .collect()
+        }
+
+        let mut bbox: Option<Vec<i32>> = None;
+        let mut ascent = None;
+        let mut descent = None;
+        let mut glyphs = HashMap::new();
+
+        let mut lines = text.lines();
+        while let Some(line) = lines.next() {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            match words.as_slice() {
+                ["FONTBOUNDINGBOX", args @ ..] => bbox = Some(nums(args)?),
+                ["FONT_ASCENT", a] => ascent = Some(nums(&[a])?[0]),
+                ["FONT_DESCENT", d] => descent = Some(nums(&[d])?[0]),
+                ["STARTCHAR", ..] => {
+                    let mut encoding = None;
+                    let mut advance = None;
+                    let mut char_bbox = bbox.clone();
+                    let mut bits = Vec::new();
+
+                    loop {
+                        let line = match lines.next() {
+                            Some(line) => line,
+                            None => return malformed("unterminated character"),
+                        };
+                        let words: Vec<&str> = line.split_whitespace().collect();
+                        match words.as_slice() {
+                            ["ENCODING", e, ..] => encoding = Some(nums(&[e])?[0]),
+                            ["DWIDTH", dx, ..] => advance = Some(nums(&[dx])?[0]),
+                            ["BBX", args @ ..] => char_bbox = Some(nums(args)?),
+                            ["BITMAP"] => {
+                                let (w, h) = match char_bbox.as_deref() {
+                                    Some(&[w, h, _, _]) => (w.max(0) as usize, h.max(0) as usize),
+                                    _ => return malformed("bitmap without bounding box"),
+                                };
+                                for _ in 0..h {
+                                    let hex = lines.next().unwrap_or("").trim();
+                                    let row = match u128::from_str_radix(hex, 16) {
+                                        Ok(row) if hex.len() <= 32 => row,
+                                        _ => return malformed(format!("bad bitmap row {:?}", hex)),
+                                    };
+                                    let row_bits = hex.len() * 4;
+                                    bits.extend((0..w).map(|x| {
+                                        x < row_bits && row & (1 << (row_bits - 1 - x)) != 0
+                                    }));
+                                }
+                            },
+                            ["ENDCHAR"] => break,
+                            _ => (),
+                        }
+                    }
+
+                    let c = encoding
+                        .filter(|&e| e >= 0)
+                        .and_then(|e| std::char::from_u32(e as u32));
+                    if let (Some(c), Some(&[w, h, x, y])) = (c, char_bbox.as_deref()) {
+                        let size = Vec2::new(w.max(0) as usize, h.max(0) as usize);
+                        if bits.len() == size.x * size.y {
+                            glyphs.insert(c, Glyph {
+                                size,
+                                offset: Vec2::new(x, y),
+                                advance: advance.unwrap_or(w),
+                                bits,
+                            });
+                        }
+                    }
+                },
+                _ => (),
+            }
+        }
+
+        let (ascent, descent) = match (ascent, descent, bbox.as_deref()) {
+            (Some(a), Some(d), _) => (a, d),
+            (_, _, Some(&[_, h, _, y])) => (h + y, -y),
+            _ => return malformed("missing font bounding box"),
+        };
+
+        Ok(Font {
+            ascent,
+            descent,
+            glyphs,
+        })
+    }
+
+    /// Height of one line of text, in font pixels.
+    pub fn line_height(&self) -> i32 {
+        self.ascent + self.descent
+    }
+
+    /// Look up the glyph for a character, falling back to `?`.
+    pub fn glyph(&self, c: char) -> Option<&Glyph> {
+        self.glyphs.get(&c).or_else(|| self.glyphs.get(&'?'))
+    }
+
+    /// Size that a string would take up when drawn, in canvas pixels.
+    pub fn measure(&self, text: &str, scale: usize) -> Vec2<usize> {
+        let mut max_x = 0;
+        let mut lines = 0;
+        for line in text.split('\n') {
+            let x: i32 = line.chars()
+                .filter_map(|c| self.glyph(c))
+                .map(|g| g.advance)
+                .sum();
+            max_x = max_x.max(x);
+            lines += 1;
+        }
+        Vec2::new(max_x as usize, (lines * self.line_height()) as usize) * scale
+    }
+}
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xB5, 0x4A, 0x86];
+
+/// Parse one glyph's entry of a PSF1 unicode table.
+fn psf1_table_entry(table: &[u8]) -> Result<(Vec<char>, &[u8]), FontError> {
+    let mut chars = Vec::new();
+    let mut in_seq = false;
+    let mut i = 0;
+    loop {
+        let unit = match table.get(i..i + 2) {
+            Some(b) => u16::from_le_bytes([b[0], b[1]]),
+            None => return malformed("truncated unicode table"),
+        };
+        i += 2;
+        match unit {
+            0xFFFF => return Ok((chars, &table[i..])),
+            0xFFFE => in_seq = true,
+            _ if !in_seq => chars.extend(std::char::from_u32(unit as u32)),
+            _ => (),
+        }
+    }
+}
+
+/// Parse one glyph's entry of a PSF2 unicode table.
+fn psf2_table_entry(table: &[u8]) -> Result<(Vec<char>, &[u8]), FontError> {
+    let end = match table.iter().position(|&b| b == 0xFF) {
+        Some(end) => end,
+        None => return malformed("truncated unicode table"),
+    };
+    let singles = table[..end].split(|&b| b == 0xFE).next().unwrap();
+    let chars = String::from_utf8_lossy(singles)
+        .chars()
+        .filter(|&c| c != std::char::REPLACEMENT_CHARACTER)
+        .collect();
+    Ok((chars, &table[end + 1..]))
+}
+
+/// How to draw text.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TextStyle {
+    pub fg: Rgba<u8>,
+    /// Fills each character's cell behind the glyph, if present.
+    pub bg: Option<Rgba<u8>>,
+    /// Size of each font pixel, in canvas pixels.
+    pub scale: usize,
+}
+
+impl Default for TextStyle {
+    fn default() -> Self {
+        TextStyle {
+            fg: Rgba::white(),
+            bg: None,
+            scale: 1,
+        }
+    }
+}
+
+/// Draw a string into a canvas, alpha-blending it over the existing pixels.
+///
+/// `pos` is the top-left corner of the text. Because the canvas y axis points up,
+/// successive lines are drawn at decreasing y coordinates.
+pub fn draw_text(
+    canvas: &mut Canvas,
+    font: &Font,
+    pos: Vec2<i32>,
+    text: &str,
+    style: &TextStyle,
+) {
+    let scale = style.scale as i32;
+    let line_height = font.line_height() * scale;
+
+    // top edge of the current line, exclusive
+    let mut top = pos.y + 1;
+    for line in text.split('\n') {
+        let baseline = top - font.ascent * scale;
+        let mut pen = pos.x;
+
+        for c in line.chars() {
+            let glyph = match font.glyph(c) {
+                Some(glyph) => glyph,
+                None => continue,
+            };
+
+            // background cell
+            if let Some(bg) = style.bg {
+                for y in top - line_height..top {
+                    for x in pen..pen + glyph.advance * scale {
+                        canvas.blend(Vec2::new(x, y), bg);
+                    }
+                }
+            }
+
+            // foreground bitmap
+            for gy in 0..glyph.size.y {
+                for gx in 0..glyph.size.x {
+                    if !glyph.bit(gx, gy) {
+                        continue;
+                    }
+                    let corner = Vec2::new(
+                        pen + (glyph.offset.x + gx as i32) * scale,
+                        baseline + (glyph.offset.y + (glyph.size.y - 1 - gy) as i32) * scale,
+                    );
+                    for sy in 0..scale {
+                        for sx in 0..scale {
+                            canvas.blend(corner + Vec2::new(sx, sy), style.fg);
+                        }
+                    }
+                }
+            }
+
+            pen += glyph.advance * scale;
+        }
+
+        top -= line_height;
+    }
+}
+
+/// Public-domain 8x8 bitmaps for `U+0020` through `U+007E`, from Daniel Hepper's font8x8.
+///
+/// Each byte is a row, starting from the top, with the least significant bit leftmost.
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // "'"
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
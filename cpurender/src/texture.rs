@@ -0,0 +1,233 @@
+use std::path::Path;
+
+use image::{ImageResult, RgbaImage};
+use vek::*;
+
+/// How a texture is filtered when sampled with normalized coordinates.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Filter {
+    /// Take the closest texel of the base level.
+    Nearest,
+    /// Blend the four closest texels of the base level.
+    Bilinear,
+    /// Blend bilinear samples from the two closest mipmap levels.
+    Trilinear,
+}
+
+/// How texel coordinates outside of the texture are mapped back into it.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Wrap {
+    /// Tile the texture.
+    Repeat,
+    /// Extend the edge texels.
+    Clamp,
+    /// Tile the texture, flipping every other tile.
+    Mirror,
+}
+
+impl Wrap {
+    /// Map a texel coordinate into `[0, len)`.
+    pub fn apply(self, i: i32, len: usize) -> usize {
+        let len = len as i32;
+        let i = match self {
+            Wrap::Repeat => i.rem_euclid(len),
+            Wrap::Clamp => i.max(0).min(len - 1),
+            Wrap::Mirror => {
+                let m = i.rem_euclid(len * 2);
+                if m >= len { len * 2 - 1 - m } else { m }
+            },
+        };
+        i as usize
+    }
+}
+
+/// One level of a mipmap chain.
+#[derive(Clone, Debug)]
+struct Level {
+    size: Vec2<usize>,
+    texels: Vec<Rgba<u8>>,
+}
+
+impl Level {
+    fn get(&self, xy: Vec2<usize>) -> Rgba<u8> {
+        self.texels[xy.y * self.size.x + xy.x]
+    }
+
+    /// Box-filter this level down to half its size.
+    fn downsample(&self) -> Level {
+        let size = self.size.map(|n| (n / 2).max(1));
+        let mut texels = Vec::with_capacity(size.x * size.y);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                let mut sum = Rgba::<u32>::zero();
+                for &(dx, dy) in &[(0, 0), (1, 0), (0, 1), (1, 1)] {
+                    let src = Vec2::new(
+                        (x * 2 + dx).min(self.size.x - 1),
+                        (y * 2 + dy).min(self.size.y - 1),
+                    );
+                    sum += self.get(src).map(|c| c as u32);
+                }
+                texels.push(sum.map(|c| ((c + 2) / 4) as u8));
+            }
+        }
+        Level { size, texels }
+    }
+}
+
+/// An image which fragment functions can sample from.
+///
+/// Samples are returned as floating point colors in the range `[0, 1]`.
+#[derive(Clone, Debug)]
+pub struct Texture {
+    levels: Vec<Level>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+impl Texture {
+    /// Load a texture from a file, in any format the `image` crate supports.
+    pub fn open(path: impl AsRef<Path>) -> ImageResult<Self> {
+        Ok(Texture::from_image(image::open(path)?.to_rgba()))
+    }
+
+    /// Load a texture from an encoded image in memory.
+    pub fn from_memory(bytes: &[u8]) -> ImageResult<Self> {
+        Ok(Texture::from_image(image::load_from_memory(bytes)?.to_rgba()))
+    }
+
+    /// Create a texture from decoded pixels, generating its mipmaps.
+    ///
+    /// Defaults to bilinear filtering and repeat wrapping.
+    pub fn from_image(image: RgbaImage) -> Self {
+        let size = Vec2::new(image.width() as usize, image.height() as usize);
+        assert!(size.x > 0 && size.y > 0, "texture must not be empty");
+
+        let texels = image.pixels()
+            .map(|p| Rgba::new(p[0], p[1], p[2], p[3]))
+            .collect();
+        let mut levels = vec![Level { size, texels }];
+        while levels.last().unwrap().size != Vec2::one() {
+            let next = levels.last().unwrap().downsample();
+            levels.push(next);
+        }
+
+        Texture {
+            levels,
+            filter: Filter::Bilinear,
+            wrap: Wrap::Repeat,
+        }
+    }
+
+    /// Builder-style setter for the filter.
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Builder-style setter for the wrap mode.
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    /// Size of the base level, in texels.
+    pub fn size(&self) -> Vec2<usize> {
+        self.levels[0].size
+    }
+
+    /// Number of mipmap levels, including the base level.
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    /// Fetch a texel of the base level by integer coordinates, applying the wrap mode.
+    pub fn texel(&self, xy: Vec2<i32>) -> Rgba<u8> {
+        self.texel_level(xy, 0)
+    }
+
+    /// Fetch a texel of some mipmap level by integer coordinates, applying the wrap mode.
+    ///
+    /// Panics if the level does not exist.
+    pub fn texel_level(&self, xy: Vec2<i32>, level: usize) -> Rgba<u8> {
+        let level = &self.levels[level];
+        level.get(Vec2::new(
+            self.wrap.apply(xy.x, level.size.x),
+            self.wrap.apply(xy.y, level.size.y),
+        ))
+    }
+
+    /// Sample the texture at normalized coordinates, where `[0, 1]` covers the texture once.
+    ///
+    /// Trilinear filtering samples the base level, use `sample_lod` or `sample_grad` to
+    /// select a mipmap level.
+    pub fn sample(&self, uv: Vec2<f32>) -> Rgba<f32> {
+        self.sample_lod(uv, 0.0)
+    }
+
+    /// Sample the texture at normalized coordinates and an explicit level of detail.
+    ///
+    /// The level of detail only has an effect with trilinear filtering.
+    pub fn sample_lod(&self, uv: Vec2<f32>, lod: f32) -> Rgba<f32> {
+        match self.filter {
+            Filter::Nearest => self.nearest(uv, 0),
+            Filter::Bilinear => self.bilinear(uv, 0),
+            Filter::Trilinear => {
+                let lod = lod.max(0.0).min((self.levels.len() - 1) as f32);
+                let lo = lod.floor() as usize;
+                let hi = (lo + 1).min(self.levels.len() - 1);
+                Rgba::lerp(
+                    self.bilinear(uv, lo),
+                    self.bilinear(uv, hi),
+                    lod - lo as f32,
+                )
+            },
+        }
+    }
+
+    /// Sample the texture at normalized coordinates, deriving the level of detail from the
+    /// rate of change of the coordinates per pixel in each screen axis.
+    pub fn sample_grad(
+        &self,
+        uv: Vec2<f32>,
+        duv_dx: Vec2<f32>,
+        duv_dy: Vec2<f32>,
+    ) -> Rgba<f32> {
+        let size = self.size().map(|n| n as f32);
+        let rho = (duv_dx * size).magnitude().max((duv_dy * size).magnitude());
+        let lod = if rho > 0.0 { rho.log2() } else { 0.0 };
+        self.sample_lod(uv, lod)
+    }
+
+    fn fetch(&self, xy: Vec2<i32>, level: usize) -> Rgba<f32> {
+        self.texel_level(xy, level).map(|c| c as f32 / 255.0)
+    }
+
+    fn nearest(&self, uv: Vec2<f32>, level: usize) -> Rgba<f32> {
+        let size = self.levels[level].size.map(|n| n as f32);
+        let xy = (uv * size).map(|c| c.floor() as i32);
+        self.fetch(xy, level)
+    }
+
+    fn bilinear(&self, uv: Vec2<f32>, level: usize) -> Rgba<f32> {
+        let size = self.levels[level].size.map(|n| n as f32);
+
+        // texel centers lie on half-integer coordinates
+        let p = uv * size - Vec2::broadcast(0.5);
+        let base = p.map(|c| c.floor());
+        let t = p - base;
+        // casting saturates, so keep room for the neighbors past the far edge
+        let base = base.map(|c| (c as i32).min(i32::MAX - 1));
+
+        let top = Rgba::lerp(
+            self.fetch(base, level),
+            self.fetch(base + Vec2::new(1, 0), level),
+            t.x,
+        );
+        let bottom = Rgba::lerp(
+            self.fetch(base + Vec2::new(0, 1), level),
+            self.fetch(base + Vec2::new(1, 1), level),
+            t.x,
+        );
+        Rgba::lerp(top, bottom, t.y)
+    }
+}
@@ -0,0 +1,108 @@
+use std::time::Duration;
+
+/// Configuration for running a simulation at a fixed rate, independent of frame rate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FixedTimestep {
+    /// Simulated time per tick.
+    pub tick: Duration,
+    /// Most ticks run before a single frame. If rendering falls further behind than this,
+    /// the excess time is dropped rather than simulated, so the simulation slows down
+    /// instead of spiraling.
+    pub max_ticks_per_frame: u32,
+}
+
+impl FixedTimestep {
+    /// Some number of ticks per second, catching up by at most 8 ticks per frame.
+    ///
+    /// Panics if `ticks` is 0.
+    pub fn per_second(ticks: u32) -> Self {
+        assert!(ticks > 0, "timestep must have a nonzero number of ticks per second");
+        FixedTimestep {
+            tick: Duration::from_secs(1) / ticks,
+            ..FixedTimestep::default()
+        }
+    }
+
+    /// Builder-style setter for the most ticks run before a single frame.
+    pub fn with_max_ticks_per_frame(mut self, max_ticks_per_frame: u32) -> Self {
+        self.max_ticks_per_frame = max_ticks_per_frame;
+        self
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep {
+            tick: Duration::from_secs(1) / 60,
+            max_ticks_per_frame: 8,
+        }
+    }
+}
+
+/// Accumulates real time, and decides how many fixed ticks are due.
+///
+/// Given the same sequence of elapsed times, this always produces the same sequence of
+/// ticks, so simulations only depend on real time through how many ticks run per frame.
+#[derive(Clone, Debug)]
+pub struct Accumulator {
+    timestep: FixedTimestep,
+    /// Real time not yet simulated, less than one tick after `advance`.
+    accumulated: Duration,
+    ticks: u64,
+    dropped: Duration,
+}
+
+impl Accumulator {
+    /// Panics if the timestep's tick is zero, because every frame would be infinitely many
+    /// ticks.
+    pub fn new(timestep: FixedTimestep) -> Self {
+        assert!(timestep.tick > Duration::ZERO, "timestep tick must be nonzero");
+        Accumulator {
+            timestep,
+            accumulated: Duration::ZERO,
+            ticks: 0,
+            dropped: Duration::ZERO,
+        }
+    }
+
+    pub fn timestep(&self) -> FixedTimestep {
+        self.timestep
+    }
+
+    /// Add elapsed real time, and return how many ticks should run before the next frame.
+    pub fn advance(&mut self, elapsed: Duration) -> u32 {
+        self.accumulated += elapsed;
+
+        let tick = self.timestep.tick;
+        let mut due = 0;
+        while self.accumulated >= tick && due < self.timestep.max_ticks_per_frame {
+            self.accumulated -= tick;
+            due += 1;
+        }
+
+        // too far behind to catch up, so drop whole ticks, keeping the fraction
+        let fraction = self.accumulated.as_nanos() % tick.as_nanos();
+        let fraction = Duration::from_nanos(fraction as u64);
+        self.dropped += self.accumulated - fraction;
+        self.accumulated = fraction;
+
+        self.ticks += due as u64;
+        due
+    }
+
+    /// How far between the last tick and the next one the current frame is, in `[0, 1)`,
+    /// for interpolating between simulation states.
+    pub fn alpha(&self) -> f32 {
+        (self.accumulated.as_secs_f64() / self.timestep.tick.as_secs_f64()) as f32
+    }
+
+    /// Total ticks due so far.
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Total real time which was dropped rather than simulated, because of falling behind.
+    pub fn dropped(&self) -> Duration {
+        self.dropped
+    }
+}
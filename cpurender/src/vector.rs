@@ -0,0 +1,729 @@
+use crate::canvas::Canvas;
+
+use std::{
+    f32::consts::{FRAC_PI_2, PI},
+    mem,
+};
+
+use vek::*;
+
+/// Furthest that flattened curves stray from the true curves, in pixels.
+const TOLERANCE: f32 = 0.02;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Segment {
+    MoveTo(Vec2<f32>),
+    LineTo(Vec2<f32>),
+    QuadTo(Vec2<f32>, Vec2<f32>),
+    CubicTo(Vec2<f32>, Vec2<f32>, Vec2<f32>),
+    Close,
+}
+
+/// A vector shape, made of subpaths of lines and curves, like an SVG path.
+///
+/// Coordinates are in canvas pixels, with the y axis pointing up. Drawing commands before
+/// the first `move_to` start at the origin.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Path {
+    segments: Vec<Segment>,
+    /// Start of the current subpath.
+    start: Vec2<f32>,
+    current: Vec2<f32>,
+}
+
+impl Path {
+    pub fn new() -> Self {
+        Path::default()
+    }
+
+    /// An axis-aligned rectangle.
+    pub fn rect(min: Vec2<f32>, size: Vec2<f32>) -> Self {
+        Path::new()
+            .move_to(min)
+            .line_to(min + Vec2::new(size.x, 0.0))
+            .line_to(min + size)
+            .line_to(min + Vec2::new(0.0, size.y))
+            .close()
+    }
+
+    pub fn circle(center: Vec2<f32>, radius: f32) -> Self {
+        let radii = Vec2::broadcast(radius);
+        Path::new()
+            .move_to(center + Vec2::new(radius, 0.0))
+            .arc_to(radii, 0.0, false, true, center - Vec2::new(radius, 0.0))
+            .arc_to(radii, 0.0, false, true, center + Vec2::new(radius, 0.0))
+            .close()
+    }
+
+    /// Start a new subpath.
+    pub fn move_to(mut self, to: Vec2<f32>) -> Self {
+        self.start = to;
+        self.current = to;
+        self.push(Segment::MoveTo(to))
+    }
+
+    pub fn line_to(mut self, to: Vec2<f32>) -> Self {
+        self.current = to;
+        self.push(Segment::LineTo(to))
+    }
+
+    /// Quadratic bézier curve.
+    pub fn quad_to(mut self, control: Vec2<f32>, to: Vec2<f32>) -> Self {
+        self.current = to;
+        self.push(Segment::QuadTo(control, to))
+    }
+
+    /// Cubic bézier curve.
+    pub fn cubic_to(mut self, c1: Vec2<f32>, c2: Vec2<f32>, to: Vec2<f32>) -> Self {
+        self.current = to;
+        self.push(Segment::CubicTo(c1, c2, to))
+    }
+
+    /// Elliptical arc, with the same parameters as an SVG arc: the ellipse's radii, and its
+    /// rotation in radians, and which of the four possible arcs to take.
+    ///
+    /// `sweep` means going in the direction of increasing angle, which is counterclockwise
+    /// since the y axis points up. Radii too small to reach are scaled up, and the arc is
+    /// approximated with cubic curves.
+    pub fn arc_to(
+        self,
+        radii: Vec2<f32>,
+        rotation: f32,
+        large_arc: bool,
+        sweep: bool,
+        to: Vec2<f32>,
+    ) -> Self {
+        let from = self.current;
+        let mut radii = radii.map(f32::abs);
+        if from == to {
+            return self;
+        }
+        if radii.x == 0.0 || radii.y == 0.0 {
+            return self.line_to(to);
+        }
+
+        // find the center, following the SVG spec's implementation notes
+        let (sin, cos) = rotation.sin_cos();
+        let rotate = |v: Vec2<f32>| Vec2::new(cos * v.x - sin * v.y, sin * v.x + cos * v.y);
+        let half = (from - to) / 2.0;
+        let p = Vec2::new(cos * half.x + sin * half.y, cos * half.y - sin * half.x);
+        let lambda = (p / radii).magnitude_squared();
+        if lambda > 1.0 {
+            radii *= lambda.sqrt();
+        }
+        let (rx2, ry2) = (radii.x * radii.x, radii.y * radii.y);
+        let numerator = rx2 * ry2 - rx2 * p.y * p.y - ry2 * p.x * p.x;
+        let denominator = rx2 * p.y * p.y + ry2 * p.x * p.x;
+        let mut scale = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            scale = -scale;
+        }
+        let center_p = Vec2::new(p.y * radii.x / radii.y, -p.x * radii.y / radii.x) * scale;
+        let center = rotate(center_p) + (from + to) / 2.0;
+
+        let angle = |v: Vec2<f32>| v.y.atan2(v.x);
+        let start_angle = angle((p - center_p) / radii);
+        let mut sweep_angle = angle((-p - center_p) / radii) - start_angle;
+        if sweep && sweep_angle < 0.0 {
+            sweep_angle += 2.0 * PI;
+        } else if !sweep && sweep_angle > 0.0 {
+            sweep_angle -= 2.0 * PI;
+        }
+
+        // a cubic per quarter turn or less
+        let n = (sweep_angle.abs() / FRAC_PI_2).ceil().max(1.0) as usize;
+        let step = sweep_angle / n as f32;
+        let k = 4.0 / 3.0 * (step / 4.0).tan();
+        let on_ellipse = |v: Vec2<f32>| center + rotate(v * radii);
+        let mut path = self;
+        for i in 0..n {
+            let a0 = start_angle + step * i as f32;
+            let a1 = a0 + step;
+            let (p0, p1) = (Vec2::new(a0.cos(), a0.sin()), Vec2::new(a1.cos(), a1.sin()));
+            let (d0, d1) = (Vec2::new(-p0.y, p0.x), Vec2::new(-p1.y, p1.x));
+            let end = if i == n - 1 { to } else { on_ellipse(p1) };
+            path = path.cubic_to(on_ellipse(p0 + d0 * k), on_ellipse(p1 - d1 * k), end);
+        }
+        path
+    }
+
+    /// Close the current subpath with a line back to its start.
+    pub fn close(mut self) -> Self {
+        self.current = self.start;
+        self.push(Segment::Close)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The outline of this path stroked with some stroke, as a path to fill with the
+    /// non-zero rule.
+    pub fn stroked(&self, stroke: &Stroke) -> Path {
+        let mut outline = Path::new();
+        for polyline in self.flatten() {
+            for polygon in stroke_polyline(&polyline, stroke) {
+                outline = outline.polygon(&polygon);
+            }
+        }
+        outline
+    }
+
+    fn push(mut self, segment: Segment) -> Self {
+        self.segments.push(segment);
+        self
+    }
+
+    /// Add a closed subpath through some points.
+    fn polygon(mut self, points: &[Vec2<f32>]) -> Self {
+        if let Some((&first, rest)) = points.split_first() {
+            self = self.move_to(first);
+            for &point in rest {
+                self = self.line_to(point);
+            }
+            self = self.close();
+        }
+        self
+    }
+
+    /// Approximate the subpaths with lines.
+    fn flatten(&self) -> Vec<Polyline> {
+        let mut polylines = Vec::new();
+        let mut polyline = Polyline::starting_at(Vec2::zero());
+        // whether anything's been drawn in the current subpath
+        let mut drew = false;
+        for &segment in &self.segments {
+            let from = *polyline.points.last().unwrap();
+            match segment {
+                Segment::MoveTo(to) => {
+                    let previous = mem::replace(&mut polyline, Polyline::starting_at(to));
+                    if drew {
+                        polylines.push(previous);
+                    }
+                    drew = false;
+                    continue;
+                },
+                Segment::LineTo(to) => polyline.points.push(to),
+                Segment::QuadTo(control, to) => {
+                    let n = segment_count((from - control * 2.0 + to).magnitude() / 4.0);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let (a, b) = (Vec2::lerp(from, control, t), Vec2::lerp(control, to, t));
+                        polyline.points.push(Vec2::lerp(a, b, t));
+                    }
+                },
+                Segment::CubicTo(c1, c2, to) => {
+                    let dd = (from - c1 * 2.0 + c2).magnitude()
+                        .max((c1 - c2 * 2.0 + to).magnitude());
+                    let n = segment_count(dd * 0.75);
+                    for i in 1..=n {
+                        let t = i as f32 / n as f32;
+                        let s = 1.0 - t;
+                        let p = from * (s * s * s)
+                            + c1 * (3.0 * s * s * t)
+                            + c2 * (3.0 * s * t * t)
+                            + to * (t * t * t);
+                        polyline.points.push(p);
+                    }
+                },
+                Segment::Close => {
+                    let start = polyline.points[0];
+                    polyline.closed = true;
+                    polylines.push(mem::replace(&mut polyline, Polyline::starting_at(start)));
+                    drew = false;
+                    continue;
+                },
+            }
+            drew = true;
+        }
+        if drew {
+            polylines.push(polyline);
+        }
+        polylines
+    }
+}
+
+/// Number of lines to flatten a curve into, given the largest magnitude of its second
+/// derivative divided by 8, so that it strays at most the tolerance.
+fn segment_count(max_error: f32) -> usize {
+    (max_error / TOLERANCE).sqrt().ceil().max(1.0) as usize
+}
+
+/// A subpath approximated with lines.
+#[derive(Clone, Debug)]
+struct Polyline {
+    points: Vec<Vec2<f32>>,
+    closed: bool,
+}
+
+impl Polyline {
+    fn starting_at(point: Vec2<f32>) -> Self {
+        Polyline {
+            points: vec![point],
+            closed: false,
+        }
+    }
+}
+
+/// How to decide which parts of a path are inside it.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum FillRule {
+    /// Inside is where the path winds around a non-zero number of times.
+    #[default]
+    NonZero,
+    /// Inside is where the path winds around an odd number of times, so overlapping
+    /// subpaths cut holes in each other.
+    EvenOdd,
+}
+
+/// The shape of the corners where a stroke's segments meet.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Join {
+    /// A sharp corner, unless it's longer than the miter limit.
+    #[default]
+    Miter,
+    Round,
+    /// A corner cut off flat.
+    Bevel,
+}
+
+/// The shape of the ends of a stroke's open subpaths.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Cap {
+    /// Ending flat at the end point.
+    #[default]
+    Butt,
+    /// A half circle around the end point.
+    Round,
+    /// Ending flat, half the width past the end point.
+    Square,
+}
+
+/// How to draw the outline of a path.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Stroke {
+    pub width: f32,
+    pub join: Join,
+    pub cap: Cap,
+    /// The longest a miter join can be, as a multiple of the width, before it's beveled
+    /// instead.
+    pub miter_limit: f32,
+}
+
+impl Stroke {
+    pub fn new(width: f32) -> Self {
+        Stroke {
+            width,
+            ..Stroke::default()
+        }
+    }
+
+    /// Builder-style setter for the join.
+    pub fn with_join(mut self, join: Join) -> Self {
+        self.join = join;
+        self
+    }
+
+    /// Builder-style setter for the cap.
+    pub fn with_cap(mut self, cap: Cap) -> Self {
+        self.cap = cap;
+        self
+    }
+
+    /// Builder-style setter for the miter limit.
+    pub fn with_miter_limit(mut self, miter_limit: f32) -> Self {
+        self.miter_limit = miter_limit;
+        self
+    }
+}
+
+impl Default for Stroke {
+    fn default() -> Self {
+        Stroke {
+            width: 1.0,
+            join: Join::Miter,
+            cap: Cap::Butt,
+            miter_limit: 4.0,
+        }
+    }
+}
+
+/// The outline of a stroked polyline, as a set of overlapping counterclockwise polygons:
+/// a quad for each segment, and the joins and caps.
+fn stroke_polyline(polyline: &Polyline, stroke: &Stroke) -> Vec<Vec<Vec2<f32>>> {
+    let half_width = stroke.width / 2.0;
+    let mut points = polyline.points.clone();
+    points.dedup();
+    if polyline.closed && points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    let mut polygons = Vec::new();
+    if half_width <= 0.0 {
+        return polygons;
+    }
+
+    // a subpath without length is drawn as a dot, if it has caps
+    if points.len() == 1 {
+        let p = points[0];
+        match stroke.cap {
+            Cap::Butt => (),
+            Cap::Round => polygons.push(arc_points(p, half_width, 0.0, 2.0 * PI)),
+            Cap::Square => {
+                let h = half_width;
+                polygons.push(vec![
+                    p + Vec2::new(-h, -h),
+                    p + Vec2::new(h, -h),
+                    p + Vec2::new(h, h),
+                    p + Vec2::new(-h, h),
+                ]);
+            },
+        }
+        return polygons;
+    }
+
+    let closed = polyline.closed && points.len() > 2;
+    let segment_count = if closed { points.len() } else { points.len() - 1 };
+    let segment = |i: usize| (points[i % points.len()], points[(i + 1) % points.len()]);
+    let left_normal = |(a, b): (Vec2<f32>, Vec2<f32>)| {
+        let d = (b - a).normalized();
+        Vec2::new(-d.y, d.x) * half_width
+    };
+
+    for i in 0..segment_count {
+        let (a, b) = segment(i);
+        let n = left_normal((a, b));
+        polygons.push(vec![a - n, b - n, b + n, a + n]);
+    }
+
+    // joins, between each segment and the next
+    let joins = if closed { 0..segment_count } else { 0..segment_count - 1 };
+    for i in joins {
+        let (a, v) = segment(i);
+        let (_, b) = segment(i + 1);
+        let (n0, n1) = (left_normal((a, v)), left_normal((v, b)));
+        let turn = (v - a).x * (b - v).y - (v - a).y * (b - v).x;
+
+        // the outside of the corner is on the right when turning left
+        let side = if turn > 0.0 { -1.0 } else { 1.0 };
+        let (o0, o1) = (v + n0 * side, v + n1 * side);
+        if o0.distance_squared(o1) < 1e-12 {
+            continue;
+        }
+        match stroke.join {
+            Join::Bevel => polygons.push(vec![v, o0, o1]),
+            Join::Round => {
+                let start = (o0 - v).y.atan2((o0 - v).x);
+                let end = (o1 - v).y.atan2((o1 - v).x);
+                let mut sweep = end - start;
+                if sweep > PI {
+                    sweep -= 2.0 * PI;
+                } else if sweep < -PI {
+                    sweep += 2.0 * PI;
+                }
+                let mut polygon = vec![v];
+                polygon.extend(arc_points(v, half_width, start, sweep));
+                polygons.push(polygon);
+            },
+            Join::Miter => {
+                // the miter's length is the width divided by the cosine of half the angle
+                // between the normals
+                let middle = (n0 + n1) / 2.0;
+                let ratio = half_width / middle.magnitude();
+                if middle.magnitude_squared() > 1e-12 && ratio <= stroke.miter_limit {
+                    let tip = v + middle * (ratio * ratio) * side;
+                    polygons.push(vec![v, o0, tip, o1]);
+                } else {
+                    polygons.push(vec![v, o0, o1]);
+                }
+            },
+        }
+    }
+
+    // caps, at each end of open subpaths
+    if !closed {
+        let last = points.len() - 1;
+        let ends = [
+            (points[0], (points[0] - points[1]).normalized()),
+            (points[last], (points[last] - points[last - 1]).normalized()),
+        ];
+        for &(end, direction) in &ends {
+            let n = Vec2::new(-direction.y, direction.x) * half_width;
+            match stroke.cap {
+                Cap::Butt => (),
+                Cap::Round => {
+                    let start = n.y.atan2(n.x);
+                    polygons.push(arc_points(end, half_width, start, -PI));
+                },
+                Cap::Square => {
+                    let out = direction * half_width;
+                    polygons.push(vec![end + n, end - n, end - n + out, end + n + out]);
+                },
+            }
+        }
+    }
+
+    for polygon in &mut polygons {
+        if signed_area(polygon) < 0.0 {
+            polygon.reverse();
+        }
+    }
+    polygons
+}
+
+/// Points along an arc of a circle, from a start angle through a sweep angle, in radians.
+fn arc_points(center: Vec2<f32>, radius: f32, start: f32, sweep: f32) -> Vec<Vec2<f32>> {
+    let max_step = if radius > TOLERANCE {
+        2.0 * (1.0 - TOLERANCE / radius).acos()
+    } else {
+        FRAC_PI_2
+    };
+    let n = (sweep.abs() / max_step).ceil().max(1.0) as usize;
+    (0..=n)
+        .map(|i| {
+            let angle = start + sweep * i as f32 / n as f32;
+            center + Vec2::new(angle.cos(), angle.sin()) * radius
+        })
+        .collect()
+}
+
+/// Twice the area of a polygon, positive if it's counterclockwise.
+fn signed_area(points: &[Vec2<f32>]) -> f32 {
+    let mut area = 0.0;
+    for (i, &a) in points.iter().enumerate() {
+        let b = points[(i + 1) % points.len()];
+        area += a.x * b.y - b.x * a.y;
+    }
+    area
+}
+
+/// Colors at positions from 0 to 1, interpolated between.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Gradient {
+    stops: Vec<(f32, Rgba<u8>)>,
+}
+
+impl Gradient {
+    /// A gradient through colors at positions from 0 to 1, which needn't be in order.
+    pub fn new(stops: &[(f32, Rgba<u8>)]) -> Self {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        Gradient { stops }
+    }
+
+    /// The color at some position, which is the nearest end color past the ends.
+    ///
+    /// Fully transparent if there are no stops.
+    pub fn sample(&self, t: f32) -> Rgba<u8> {
+        let after = self.stops.iter().position(|&(position, _)| position > t);
+        let (start, end) = match after {
+            None => return self.stops.last().map_or(Rgba::zero(), |&(_, color)| color),
+            Some(0) => return self.stops[0].1,
+            Some(i) => (self.stops[i - 1], self.stops[i]),
+        };
+        let t = (t - start.0) / (end.0 - start.0);
+        Rgba::lerp(start.1.map(|c| c as f32), end.1.map(|c| c as f32), t)
+            .map(|c| c.round() as u8)
+    }
+}
+
+/// What a path is filled or stroked with.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Brush {
+    Solid(Rgba<u8>),
+    /// A gradient along the line from `start` to `end`, which is constant perpendicular to
+    /// it.
+    Linear {
+        start: Vec2<f32>,
+        end: Vec2<f32>,
+        gradient: Gradient,
+    },
+    /// A gradient by distance from a center, from 0 at the center to 1 at the radius.
+    Radial {
+        center: Vec2<f32>,
+        radius: f32,
+        gradient: Gradient,
+    },
+}
+
+impl Brush {
+    /// The color at a point in canvas pixels.
+    pub fn color_at(&self, xy: Vec2<f32>) -> Rgba<u8> {
+        match self {
+            &Brush::Solid(color) => color,
+            Brush::Linear { start, end, gradient } => {
+                let along = *end - *start;
+                gradient.sample((xy - *start).dot(along) / along.magnitude_squared())
+            },
+            Brush::Radial { center, radius, gradient } => {
+                gradient.sample(xy.distance(*center) / radius)
+            },
+        }
+    }
+}
+
+/// Fill a path, blending it over a canvas with anti-aliasing.
+pub fn fill(canvas: &mut Canvas, path: &Path, rule: FillRule, brush: &Brush) {
+    let polygons: Vec<Vec<Vec2<f32>>> = path.flatten()
+        .into_iter()
+        .map(|polyline| polyline.points)
+        .collect();
+    let rasterizer = match Rasterizer::new(canvas.size(), &polygons) {
+        Some(rasterizer) => rasterizer,
+        None => return,
+    };
+    rasterizer.for_each_covered(rule, |xy, coverage| {
+        let mut color = brush.color_at(xy.map(|c| c as f32 + 0.5));
+        color.a = (color.a as f32 * coverage).round() as u8;
+        canvas.blend(xy, color);
+    });
+}
+
+/// Stroke a path, blending it over a canvas with anti-aliasing.
+pub fn stroke(canvas: &mut Canvas, path: &Path, stroke: &Stroke, brush: &Brush) {
+    fill(canvas, &path.stroked(stroke), FillRule::NonZero, brush)
+}
+
+/// Accumulates the signed area of edges in each pixel, so that summing each row from the
+/// left gives the winding number weighted by coverage.
+///
+/// Only covers the part of the canvas the polygons' bounds overlap.
+struct Rasterizer {
+    min: Vec2<i32>,
+    size: Vec2<usize>,
+    /// Row-major, with two extra cells at the end of each row, since edges at the right
+    /// edge write past it.
+    cells: Vec<f32>,
+}
+
+impl Rasterizer {
+    /// Rasterize implicitly closed polygons, or `None` if they don't overlap the canvas.
+    fn new(canvas_size: Vec2<usize>, polygons: &[Vec<Vec2<f32>>]) -> Option<Self> {
+        let points = polygons.iter().flatten();
+        let lo = points.clone().fold(Vec2::broadcast(f32::INFINITY), |lo, &p| {
+            Vec2::partial_min(lo, p)
+        });
+        let hi = points.fold(Vec2::broadcast(f32::NEG_INFINITY), |hi, &p| {
+            Vec2::partial_max(hi, p)
+        });
+        let min = lo.map(|c| (c.floor() as i32).max(0));
+        let max = hi.map2(canvas_size, |c, size| (c.ceil() as i32).min(size as i32));
+        if !(min.x < max.x && min.y < max.y) {
+            return None;
+        }
+
+        let size = (max - min).map(|c| c as usize);
+        let mut rasterizer = Rasterizer {
+            min,
+            size,
+            cells: vec![0.0; (size.x + 2) * size.y],
+        };
+        let offset = min.map(|c| c as f32);
+        for polygon in polygons {
+            for (i, &a) in polygon.iter().enumerate() {
+                let b = polygon[(i + 1) % polygon.len()];
+                rasterizer.add_line(a - offset, b - offset);
+            }
+        }
+        Some(rasterizer)
+    }
+
+    /// Add an edge, clipping it to the left and right sides. The parts beyond them are
+    /// moved onto them, which keeps what's to their right covered.
+    fn add_line(&mut self, a: Vec2<f32>, b: Vec2<f32>) {
+        let right = self.size.x as f32;
+        let mut splits = [0.0, 1.0, 1.0, 1.0];
+        if a.x != b.x {
+            for (i, &x) in [0.0, right].iter().enumerate() {
+                let t = (x - a.x) / (b.x - a.x);
+                if t > 0.0 && t < 1.0 {
+                    splits[i + 1] = t;
+                }
+            }
+        }
+        splits.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        for pair in splits.windows(2) {
+            if pair[0] < pair[1] {
+                let clamp = |p: Vec2<f32>| Vec2::new(p.x.clamp(0.0, right), p.y);
+                let p0 = clamp(Vec2::lerp(a, b, pair[0]));
+                let p1 = clamp(Vec2::lerp(a, b, pair[1]));
+                self.draw_line(p0, p1);
+            }
+        }
+    }
+
+    /// Accumulate an edge's area, as in font-rs.
+    fn draw_line(&mut self, p0: Vec2<f32>, p1: Vec2<f32>) {
+        if p0.y == p1.y {
+            return;
+        }
+        let (direction, p0, p1) = if p0.y < p1.y { (1.0, p0, p1) } else { (-1.0, p1, p0) };
+        let stride = self.size.x + 2;
+        let right = self.size.x as f32;
+        let dxdy = (p1.x - p0.x) / (p1.y - p0.y);
+        let mut x = p0.x;
+        if p0.y < 0.0 {
+            x -= p0.y * dxdy;
+        }
+
+        let y_start = p0.y.max(0.0) as usize;
+        let y_end = self.size.y.min(p1.y.ceil().max(0.0) as usize);
+        for y in y_start..y_end {
+            let row = &mut self.cells[y * stride..(y + 1) * stride];
+            let dy = ((y + 1) as f32).min(p1.y) - (y as f32).max(p0.y);
+            let x_next = (x + dxdy * dy).clamp(0.0, right);
+            let d = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+            if x1i <= x0i + 1 {
+                // within one pixel
+                let x_mid = 0.5 * (x + x_next) - x0_floor;
+                row[x0i] += d - d * x_mid;
+                row[x0i + 1] += d * x_mid;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0_fract = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0_fract) * (1.0 - x0_fract);
+                let x1_fract = x1 - x1_ceil + 1.0;
+                let a_end = 0.5 * s * x1_fract * x1_fract;
+                row[x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    row[x0i + 1] += d * (1.0 - a0 - a_end);
+                } else {
+                    let a1 = s * (1.5 - x0_fract);
+                    row[x0i + 1] += d * (a1 - a0);
+                    for cell in &mut row[x0i + 2..x1i - 1] {
+                        *cell += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    row[x1i - 1] += d * (1.0 - a2 - a_end);
+                }
+                row[x1i] += d * a_end;
+            }
+            x = x_next;
+        }
+    }
+
+    /// Call a function with the coverage of every pixel which is at least partly covered.
+    fn for_each_covered(&self, rule: FillRule, mut f: impl FnMut(Vec2<i32>, f32)) {
+        let stride = self.size.x + 2;
+        for (y, row) in self.cells.chunks(stride).enumerate() {
+            let mut winding = 0.0;
+            for (x, &cell) in row[..self.size.x].iter().enumerate() {
+                winding += cell;
+                let coverage = match rule {
+                    FillRule::NonZero => f32::abs(winding).min(1.0),
+                    FillRule::EvenOdd => {
+                        let folded = f32::abs(winding) % 2.0;
+                        if folded > 1.0 { 2.0 - folded } else { folded }
+                    },
+                };
+                if coverage > 0.5 / 255.0 {
+                    f(self.min + Vec2::new(x as i32, y as i32), coverage);
+                }
+            }
+        }
+    }
+}
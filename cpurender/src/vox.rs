@@ -0,0 +1,226 @@
+use crate::voxel::VoxelGrid;
+
+use std::{
+    error::Error,
+    fmt,
+    fs,
+    io,
+    path::Path,
+};
+
+use vek::*;
+
+/// A scene loaded from a MagicaVoxel `.vox` file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxScene {
+    pub models: Vec<VoxModel>,
+    /// Colors of each palette index. Index 0 means empty, and is fully transparent.
+    pub palette: [Rgba<u8>; 256],
+}
+
+/// A single model from a `.vox` file.
+///
+/// MagicaVoxel is z-up, so models are converted to our y-up coordinates by swapping the
+/// y and z axes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxModel {
+    /// Palette index of each voxel, with 0 meaning empty.
+    pub grid: VoxelGrid<u8>,
+}
+
+impl VoxModel {
+    /// Resolve every voxel's color, with empty voxels becoming `None`.
+    pub fn colors(&self, palette: &[Rgba<u8>; 256]) -> VoxelGrid<Option<Rgba<u8>>> {
+        self.grid.map(|&i| match i {
+            0 => None,
+            i => Some(palette[i as usize]),
+        })
+    }
+}
+
+/// Error loading a `.vox` file.
+#[derive(Debug)]
+pub enum VoxError {
+    Io(io::Error),
+    /// The data was not a `.vox` file we understand.
+    Malformed(String),
+}
+
+impl fmt::Display for VoxError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VoxError::Io(e) => write!(f, "io error: {}", e),
+            VoxError::Malformed(msg) => write!(f, "malformed vox file: {}", msg),
+        }
+    }
+}
+
+impl Error for VoxError {}
+
+impl From<io::Error> for VoxError {
+    fn from(e: io::Error) -> Self {
+        VoxError::Io(e)
+    }
+}
+
+/// MagicaVoxel's limit on the size of a model, in each dimension.
+const MAX_MODEL_SIZE: usize = 256;
+
+fn malformed<T>(msg: impl Into<String>) -> Result<T, VoxError> {
+    Err(VoxError::Malformed(msg.into()))
+}
+
+/// A chunk of a `.vox` file.
+struct Chunk<'a> {
+    id: &'a [u8],
+    content: &'a [u8],
+    children: &'a [u8],
+}
+
+/// Little-endian reader over a byte slice.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], VoxError> {
+        if self.0.len() < n {
+            return malformed("unexpected end of data");
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn u32(&mut self) -> Result<u32, VoxError> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn chunk(&mut self) -> Result<Chunk<'a>, VoxError> {
+        let id = self.bytes(4)?;
+        let content_len = self.u32()? as usize;
+        let children_len = self.u32()? as usize;
+        Ok(Chunk {
+            id,
+            content: self.bytes(content_len)?,
+            children: self.bytes(children_len)?,
+        })
+    }
+}
+
+impl VoxScene {
+    /// Load a `.vox` file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, VoxError> {
+        VoxScene::from_bytes(&fs::read(path)?)
+    }
+
+    /// Parse the contents of a `.vox` file.
+    ///
+    /// Reads the SIZE, XYZI and RGBA chunks, ignoring everything else (such as the scene
+    /// graph and materials). Files without an RGBA chunk get MagicaVoxel's default palette.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, VoxError> {
+        let mut reader = Reader(bytes);
+        if reader.bytes(4)? != b"VOX " {
+            return malformed("bad magic number");
+        }
+        let _version = reader.u32()?;
+
+        let main = reader.chunk()?;
+        if main.id != b"MAIN" {
+            return malformed("missing MAIN chunk");
+        }
+
+        let mut models = Vec::new();
+        let mut size: Option<Vec3<usize>> = None;
+        let mut palette_chunk: Option<[u32; 256]> = None;
+
+        let mut children = Reader(main.children);
+        while !children.0.is_empty() {
+            let chunk = children.chunk()?;
+            let mut content = Reader(chunk.content);
+            match chunk.id {
+                b"SIZE" => {
+                    // swap z-up to y-up
+                    let x = content.u32()? as usize;
+                    let z = content.u32()? as usize;
+                    let y = content.u32()? as usize;
+                    let xyz = Vec3::new(x, y, z);
+                    if xyz.reduce_partial_max() > MAX_MODEL_SIZE {
+                        return malformed(format!("model size {} is too large", xyz));
+                    }
+                    size = Some(xyz);
+                },
+                b"XYZI" => {
+                    let size = match size.take() {
+                        Some(size) => size,
+                        None => return malformed("XYZI chunk without preceding SIZE chunk"),
+                    };
+                    let mut grid = VoxelGrid::new(size, 0);
+                    let count = content.u32()?;
+                    for _ in 0..count {
+                        let v = content.bytes(4)?;
+                        let xyz = Vec3::new(v[0], v[2], v[1]).map(|c| c as i32);
+                        match grid.get_mut(xyz) {
+                            Some(voxel) => *voxel = v[3],
+                            None => return malformed("voxel outside of model"),
+                        }
+                    }
+                    models.push(VoxModel { grid });
+                },
+                b"RGBA" => {
+                    let mut entries = [0; 256];
+                    for entry in entries.iter_mut() {
+                        *entry = content.u32()?;
+                    }
+                    palette_chunk = Some(entries);
+                },
+                _ => (),
+            }
+        }
+
+        // palette chunk entry i is the color of index i + 1
+        let entries = palette_chunk.unwrap_or(DEFAULT_PALETTE);
+        let mut palette = [Rgba::zero(); 256];
+        for (i, &entry) in entries.iter().take(255).enumerate() {
+            let [r, g, b, a] = entry.to_le_bytes();
+            palette[i + 1] = Rgba::new(r, g, b, a);
+        }
+
+        Ok(VoxScene { models, palette })
+    }
+}
+
+/// MagicaVoxel's default palette, laid out like the contents of an RGBA chunk.
+const DEFAULT_PALETTE: [u32; 256] = [
+    0xFFFFFFFF, 0xFFCCFFFF, 0xFF99FFFF, 0xFF66FFFF, 0xFF33FFFF, 0xFF00FFFF, 0xFFFFCCFF, 0xFFCCCCFF,
+    0xFF99CCFF, 0xFF66CCFF, 0xFF33CCFF, 0xFF00CCFF, 0xFFFF99FF, 0xFFCC99FF, 0xFF9999FF, 0xFF6699FF,
+    0xFF3399FF, 0xFF0099FF, 0xFFFF66FF, 0xFFCC66FF, 0xFF9966FF, 0xFF6666FF, 0xFF3366FF, 0xFF0066FF,
+    0xFFFF33FF, 0xFFCC33FF, 0xFF9933FF, 0xFF6633FF, 0xFF3333FF, 0xFF0033FF, 0xFFFF00FF, 0xFFCC00FF,
+    0xFF9900FF, 0xFF6600FF, 0xFF3300FF, 0xFF0000FF, 0xFFFFFFCC, 0xFFCCFFCC, 0xFF99FFCC, 0xFF66FFCC,
+    0xFF33FFCC, 0xFF00FFCC, 0xFFFFCCCC, 0xFFCCCCCC, 0xFF99CCCC, 0xFF66CCCC, 0xFF33CCCC, 0xFF00CCCC,
+    0xFFFF99CC, 0xFFCC99CC, 0xFF9999CC, 0xFF6699CC, 0xFF3399CC, 0xFF0099CC, 0xFFFF66CC, 0xFFCC66CC,
+    0xFF9966CC, 0xFF6666CC, 0xFF3366CC, 0xFF0066CC, 0xFFFF33CC, 0xFFCC33CC, 0xFF9933CC, 0xFF6633CC,
+    0xFF3333CC, 0xFF0033CC, 0xFFFF00CC, 0xFFCC00CC, 0xFF9900CC, 0xFF6600CC, 0xFF3300CC, 0xFF0000CC,
+    0xFFFFFF99, 0xFFCCFF99, 0xFF99FF99, 0xFF66FF99, 0xFF33FF99, 0xFF00FF99, 0xFFFFCC99, 0xFFCCCC99,
+    0xFF99CC99, 0xFF66CC99, 0xFF33CC99, 0xFF00CC99, 0xFFFF9999, 0xFFCC9999, 0xFF999999, 0xFF669999,
+    0xFF339999, 0xFF009999, 0xFFFF6699, 0xFFCC6699, 0xFF996699, 0xFF666699, 0xFF336699, 0xFF006699,
+    0xFFFF3399, 0xFFCC3399, 0xFF993399, 0xFF663399, 0xFF333399, 0xFF003399, 0xFFFF0099, 0xFFCC0099,
+    0xFF990099, 0xFF660099, 0xFF330099, 0xFF000099, 0xFFFFFF66, 0xFFCCFF66, 0xFF99FF66, 0xFF66FF66,
+    0xFF33FF66, 0xFF00FF66, 0xFFFFCC66, 0xFFCCCC66, 0xFF99CC66, 0xFF66CC66, 0xFF33CC66, 0xFF00CC66,
+    0xFFFF9966, 0xFFCC9966, 0xFF999966, 0xFF669966, 0xFF339966, 0xFF009966, 0xFFFF6666, 0xFFCC6666,
+    0xFF996666, 0xFF666666, 0xFF336666, 0xFF006666, 0xFFFF3366, 0xFFCC3366, 0xFF993366, 0xFF663366,
+    0xFF333366, 0xFF003366, 0xFFFF0066, 0xFFCC0066, 0xFF990066, 0xFF660066, 0xFF330066, 0xFF000066,
+    0xFFFFFF33, 0xFFCCFF33, 0xFF99FF33, 0xFF66FF33, 0xFF33FF33, 0xFF00FF33, 0xFFFFCC33, 0xFFCCCC33,
+    0xFF99CC33, 0xFF66CC33, 0xFF33CC33, 0xFF00CC33, 0xFFFF9933, 0xFFCC9933, 0xFF999933, 0xFF669933,
+    0xFF339933, 0xFF009933, 0xFFFF6633, 0xFFCC6633, 0xFF996633, 0xFF666633, 0xFF336633, 0xFF006633,
+    0xFFFF3333, 0xFFCC3333, 0xFF993333, 0xFF663333, 0xFF333333, 0xFF003333, 0xFFFF0033, 0xFFCC0033,
+    0xFF990033, 0xFF660033, 0xFF330033, 0xFF000033, 0xFFFFFF00, 0xFFCCFF00, 0xFF99FF00, 0xFF66FF00,
+    0xFF33FF00, 0xFF00FF00, 0xFFFFCC00, 0xFFCCCC00, 0xFF99CC00, 0xFF66CC00, 0xFF33CC00, 0xFF00CC00,
+    0xFFFF9900, 0xFFCC9900, 0xFF999900, 0xFF669900, 0xFF339900, 0xFF009900, 0xFFFF6600, 0xFFCC6600,
+    0xFF996600, 0xFF666600, 0xFF336600, 0xFF006600, 0xFFFF3300, 0xFFCC3300, 0xFF993300, 0xFF663300,
+    0xFF333300, 0xFF003300, 0xFFFF0000, 0xFFCC0000, 0xFF990000, 0xFF660000, 0xFF330000, 0xFF0000EE,
+    0xFF0000DD, 0xFF0000BB, 0xFF0000AA, 0xFF000088, 0xFF000077, 0xFF000055, 0xFF000044, 0xFF000022,
+    0xFF000011, 0xFF00EE00, 0xFF00DD00, 0xFF00BB00, 0xFF00AA00, 0xFF008800, 0xFF007700, 0xFF005500,
+    0xFF004400, 0xFF002200, 0xFF001100, 0xFFEE0000, 0xFFDD0000, 0xFFBB0000, 0xFFAA0000, 0xFF880000,
+    0xFF770000, 0xFF550000, 0xFF440000, 0xFF220000, 0xFF110000, 0xFFEEEEEE, 0xFFDDDDDD, 0xFFBBBBBB,
+    0xFFAAAAAA, 0xFF888888, 0xFF777777, 0xFF555555, 0xFF444444, 0xFF222222, 0xFF111111, 0x00000000,
+];
@@ -0,0 +1,380 @@
+use crate::ray::Ray;
+
+use std::f32;
+
+use vek::*;
+
+/// Crossings of different axes' voxel boundaries which are closer together than this are
+/// merged into a single diagonal step.
+const MERGE_EPSILON: f32 = 0.00001;
+
+/// A dense, finite 3D grid of voxels, with the voxel at `(0, 0, 0)` occupying the unit cube
+/// from the origin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelGrid<T> {
+    size: Vec3<usize>,
+    voxels: Vec<T>,
+}
+
+/// Number of voxels in a grid of some size.
+///
+/// Panics if it overflows.
+fn voxel_count(size: Vec3<usize>) -> usize {
+    size.x.checked_mul(size.y)
+        .and_then(|n| n.checked_mul(size.z))
+        .expect("voxel grid size overflows")
+}
+
+impl<T> VoxelGrid<T> {
+    /// Create a grid with every voxel set to the same value.
+    pub fn new(size: Vec3<usize>, fill: T) -> Self
+        where
+            T: Clone {
+        VoxelGrid {
+            size,
+            voxels: vec![fill; voxel_count(size)],
+        }
+    }
+
+    /// Create a grid by computing every voxel from its coordinates.
+    pub fn from_fn(size: Vec3<usize>, mut f: impl FnMut(Vec3<i32>) -> T) -> Self {
+        let mut voxels = Vec::with_capacity(voxel_count(size));
+        for z in 0..size.z {
+            for y in 0..size.y {
+                for x in 0..size.x {
+                    voxels.push(f(Vec3::new(x, y, z).map(|c| c as i32)));
+                }
+            }
+        }
+        VoxelGrid { size, voxels }
+    }
+
+    pub fn size(&self) -> Vec3<usize> {
+        self.size
+    }
+
+    /// Whether the coordinates are within the grid.
+    pub fn contains(&self, xyz: Vec3<i32>) -> bool {
+        self.index(xyz).is_some()
+    }
+
+    /// Get a voxel, or `None` if out of bounds.
+    pub fn get(&self, xyz: Vec3<i32>) -> Option<&T> {
+        self.index(xyz).map(|i| &self.voxels[i])
+    }
+
+    /// Get a voxel, or `None` if out of bounds.
+    pub fn get_mut(&mut self, xyz: Vec3<i32>) -> Option<&mut T> {
+        match self.index(xyz) {
+            Some(i) => Some(&mut self.voxels[i]),
+            None => None,
+        }
+    }
+
+    /// Panics if out of bounds.
+    pub fn set(&mut self, xyz: Vec3<i32>, voxel: T) {
+        *self.get_mut(xyz).expect("voxel out of bounds") = voxel;
+    }
+
+    /// Iterate over every voxel and its coordinates.
+    pub fn iter(&self) -> impl Iterator<Item=(Vec3<i32>, &T)> + '_ {
+        let size = self.size;
+        self.voxels.iter()
+            .enumerate()
+            .map(move |(i, voxel)| {
+                let xyz = Vec3::new(
+                    i % size.x,
+                    i / size.x % size.y,
+                    i / (size.x * size.y),
+                );
+                (xyz.map(|c| c as i32), voxel)
+            })
+    }
+
+    /// Create a new grid of the same size by transforming every voxel.
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> VoxelGrid<U> {
+        VoxelGrid {
+            size: self.size,
+            voxels: self.voxels.iter().map(f).collect(),
+        }
+    }
+
+    /// Trace a ray through the grid, visiting every voxel within the grid which it passes
+    /// through, in order, until it leaves the grid or exceeds `max_distance`.
+    ///
+    /// Rays starting outside the grid skip ahead to where they enter it.
+    pub fn trace(
+        &self,
+        ray: Ray,
+        max_distance: f32,
+    ) -> impl Iterator<Item=(VoxelHit, &T)> + '_ {
+        let traversal = match bounds_intersection(self.size, ray) {
+            Some((enter, exit, _)) if enter <= 0.0 => {
+                Traversal::starting_at(ray, 0.0, None, max_distance.min(exit))
+            },
+            Some((enter, exit, face)) => {
+                Traversal::starting_at(ray, enter, face, max_distance.min(exit))
+            },
+            None => Traversal::empty(),
+        };
+        traversal.filter_map(move |hit| self.get(hit.voxel).map(|voxel| (hit, voxel)))
+    }
+
+    fn index(&self, xyz: Vec3<i32>) -> Option<usize> {
+        let in_bounds = xyz
+            .map2(self.size, |c, n| c >= 0 && (c as usize) < n)
+            .reduce_and();
+        if in_bounds {
+            let xyz = xyz.map(|c| c as usize);
+            Some(xyz.x + self.size.x * (xyz.y + self.size.y * xyz.z))
+        } else {
+            None
+        }
+    }
+}
+
+/// Slab test of a ray against the bounding box of a grid, returning the entry distance,
+/// exit distance, and face of entry.
+pub(crate) fn bounds_intersection(
+    size: Vec3<usize>,
+    ray: Ray,
+) -> Option<(f32, f32, Option<Face>)> {
+    let mut enter = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut face = None;
+
+    for a in 0..3 {
+        let (o, d, max) = (ray.origin[a], ray.dir[a], size[a] as f32);
+        if d == 0.0 {
+            if o < 0.0 || o > max {
+                return None;
+            }
+            continue;
+        }
+
+        let (near, far) = if d > 0.0 {
+            (-o / d, (max - o) / d)
+        } else {
+            ((max - o) / d, -o / d)
+        };
+        if near > enter {
+            enter = near;
+            face = Some(Face::from_axis(a, d < 0.0));
+        }
+        exit = exit.min(far);
+    }
+
+    if enter <= exit && exit >= 0.0 {
+        Some((enter, exit, face))
+    } else {
+        None
+    }
+}
+
+/// A face of a voxel.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Face {
+    NegX,
+    PosX,
+    NegY,
+    PosY,
+    NegZ,
+    PosZ,
+}
+
+impl Face {
+    /// The face perpendicular to an axis (0, 1 or 2), on its positive or negative side.
+    pub fn from_axis(axis: usize, positive: bool) -> Self {
+        match (axis, positive) {
+            (0, false) => Face::NegX,
+            (0, true) => Face::PosX,
+            (1, false) => Face::NegY,
+            (1, true) => Face::PosY,
+            (2, false) => Face::NegZ,
+            (2, true) => Face::PosZ,
+            _ => panic!("invalid axis {}", axis),
+        }
+    }
+
+    /// The axis this face is perpendicular to.
+    pub fn axis(self) -> usize {
+        match self {
+            Face::NegX | Face::PosX => 0,
+            Face::NegY | Face::PosY => 1,
+            Face::NegZ | Face::PosZ => 2,
+        }
+    }
+
+    /// Outward-facing unit normal.
+    pub fn normal(self) -> Vec3<i32> {
+        let mut normal = Vec3::zero();
+        normal[self.axis()] = match self {
+            Face::PosX | Face::PosY | Face::PosZ => 1,
+            _ => -1,
+        };
+        normal
+    }
+}
+
+/// A voxel visited by a ray.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VoxelHit {
+    pub voxel: Vec3<i32>,
+    /// Distance along the ray at which it entered this voxel.
+    pub distance: f32,
+    /// Face through which the ray entered this voxel, or `None` if it started in it.
+    ///
+    /// If the ray passed exactly through an edge or corner, this is the face of whichever
+    /// axis it crossed first, preferring x, then y, then z.
+    pub face: Option<Face>,
+    /// Normal of the surface through which the ray entered this voxel, or zero if it
+    /// started in it.
+    ///
+    /// If the ray passed exactly through an edge or corner, this has a component for each
+    /// axis it crossed, so the previous voxel is always `voxel + normal`.
+    pub normal: Vec3<i32>,
+}
+
+/// Iterator over every voxel which a ray passes through, in an unbounded grid, using a
+/// digital differential analyzer.
+///
+/// The first voxel yielded is the one the ray starts in. A ray starting exactly on a voxel
+/// boundary is considered to start in the voxel it is moving into.
+#[derive(Clone, Debug)]
+pub struct Traversal {
+    /// Current voxel.
+    voxel: Vec3<i32>,
+    /// Position within the current voxel, with each component in `[0, 1]`.
+    ingress: Vec3<f32>,
+    dir: Vec3<f32>,
+    distance: f32,
+    max_distance: f32,
+    first: Option<VoxelHit>,
+    done: bool,
+}
+
+/// The voxel containing a ray's origin, and the origin's position within it.
+pub(crate) fn starting_voxel(origin: Vec3<f32>, dir: Vec3<f32>) -> (Vec3<i32>, Vec3<f32>) {
+    let floor = origin.map(|c| c.floor());
+
+    // consider the following on all axis:
+    //
+    // if direction is negative, then the collision plane will be at 0. if the
+    // position is a multiple of 1, then `pos - floor(pos)` will also equal zero. that
+    // would cause the ingress point to lie in the plane it is casting to intersect
+    // with, which would ruin the math. so, in that situation, we start in the voxel
+    // below, with an ingress of 1.
+    let on_boundary = origin.map2(dir, |p, d| p % 1.0 == 0.0 && d < 0.0);
+    let voxel = floor.map2(on_boundary, |f, b| f as i32 - b as i32);
+    let ingress = (origin - floor).map2(on_boundary, |i, b| if b { 1.0 } else { i });
+    (voxel, ingress)
+}
+
+/// Traverse the voxels which a ray passes through, until it exceeds `max_distance`.
+pub fn traverse(ray: Ray, max_distance: f32) -> Traversal {
+    Traversal::starting_at(ray, 0.0, None, max_distance)
+}
+
+impl Traversal {
+    fn starting_at(ray: Ray, start: f32, face: Option<Face>, max_distance: f32) -> Self {
+        let (voxel, ingress) = starting_voxel(ray.at(start), ray.dir);
+
+        let first = if start <= max_distance {
+            Some(VoxelHit {
+                voxel,
+                distance: start,
+                face,
+                normal: face.map(Face::normal).unwrap_or_default(),
+            })
+        } else {
+            None
+        };
+
+        Traversal {
+            voxel,
+            ingress,
+            dir: ray.dir,
+            distance: start,
+            max_distance,
+            done: first.is_none(),
+            first,
+        }
+    }
+
+    fn empty() -> Self {
+        Traversal {
+            voxel: Vec3::zero(),
+            ingress: Vec3::zero(),
+            dir: Vec3::zero(),
+            distance: 0.0,
+            max_distance: 0.0,
+            first: None,
+            done: true,
+        }
+    }
+}
+
+impl Iterator for Traversal {
+    type Item = VoxelHit;
+
+    fn next(&mut self) -> Option<VoxelHit> {
+        if let Some(first) = self.first.take() {
+            return Some(first);
+        }
+        if self.done {
+            return None;
+        }
+
+        // distance to the next voxel boundary on each axis, if it's not parallel
+        let mut distances: [Option<f32>; 3] = [None; 3];
+        for (a, distance) in distances.iter_mut().enumerate() {
+            let d = self.dir[a];
+            if d != 0.0 {
+                let plane = if d > 0.0 { 1.0 } else { 0.0 };
+                *distance = Some(((plane - self.ingress[a]) / d).max(0.0));
+            }
+        }
+
+        let min = distances.iter()
+            .flatten()
+            .fold(f32::INFINITY, |a, &b| a.min(b));
+        if min == f32::INFINITY {
+            // zero direction
+            self.done = true;
+            return None;
+        }
+
+        // step across every boundary at (nearly) the nearest distance, with equal-distance
+        // merging, so that passing through an edge or corner is a single diagonal step
+        let mut step: Vec3<i32> = Vec3::zero();
+        let mut step_distance = min;
+        let mut face = None;
+        for (a, &distance) in distances.iter().enumerate() {
+            if let Some(distance) = distance {
+                if distance - min < MERGE_EPSILON {
+                    step[a] = if self.dir[a] > 0.0 { 1 } else { -1 };
+                    step_distance = step_distance.max(distance);
+                    if face.is_none() && distance == min {
+                        face = Some(Face::from_axis(a, step[a] < 0));
+                    }
+                }
+            }
+        }
+
+        self.distance += step_distance;
+        if self.distance > self.max_distance {
+            self.done = true;
+            return None;
+        }
+
+        self.ingress = self.ingress + self.dir * step_distance - step.map(|c| c as f32);
+        self.voxel += step;
+
+        Some(VoxelHit {
+            voxel: self.voxel,
+            distance: self.distance,
+            face,
+            normal: -step,
+        })
+    }
+}
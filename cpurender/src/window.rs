@@ -1,15 +1,27 @@
 
+use crate::{
+    indexed::{Palette, PaletteState, PALETTE_LEN},
+    overlay::{PerfOverlay, OVERLAY_X_SIZE, OVERLAY_Y_SIZE},
+};
+
 use std::thread;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
 
 use crossbeam::queue::SegQueue;
+use vek::Vec2;
 
 #[allow(unused_imports)]
 use glium::{
     glutin,
     glutin::dpi,
-    glutin::{Event, WindowEvent, DeviceEvent, KeyboardInput, VirtualKeyCode, ModifiersState},
-    texture::{UnsignedTexture2d, buffer_texture::{BufferTexture, BufferTextureType}},
+    glutin::{Event, WindowEvent, DeviceEvent, KeyboardInput, ModifiersState, ElementState},
+    texture::{
+        UnsignedTexture2d,
+        buffer_texture::{BufferTexture, BufferTextureType, TextureBufferContent},
+    },
     draw_parameters::DrawParameters,
     Surface,
     Display,
@@ -19,6 +31,8 @@ use glium::{
     backend::Facade,
 };
 
+pub use glium::glutin::{VirtualKeyCode, MouseButton};
+
 /// OS-specific (conditional compilation) window configuration.
 trait WindowBuilderOsSpecific: Sized {
     fn os_specific_window_configure(self) -> Self;
@@ -66,6 +80,240 @@ pub struct Paint {
     pub a: u8,
 }
 
+/// Instruction to paint a single pixel of an indexed window with a palette index.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct IndexPaint {
+    pub x: usize,
+    pub y: usize,
+    pub index: u8,
+}
+
+/// A kind of paint instruction which a window can apply.
+trait WindowPixel: Send + 'static {
+    /// How the pixel is stored in the canvas buffer.
+    type Texel: TextureBufferContent + Copy + Default + 'static;
+
+    /// Whether the texel is an index into the palette, rather than a color.
+    const INDEXED: bool;
+
+    fn xy(&self) -> (usize, usize);
+
+    fn texel(&self) -> Self::Texel;
+}
+
+impl WindowPixel for Paint {
+    type Texel = [u8; 4];
+
+    const INDEXED: bool = false;
+
+    fn xy(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn texel(&self) -> [u8; 4] {
+        [self.r, self.g, self.b, self.a]
+    }
+}
+
+impl WindowPixel for IndexPaint {
+    type Texel = u8;
+
+    const INDEXED: bool = true;
+
+    fn xy(&self) -> (usize, usize) {
+        (self.x, self.y)
+    }
+
+    fn texel(&self) -> u8 {
+        self.index
+    }
+}
+
+/// Number of frame times kept by `FrameStats`.
+const FRAME_HISTORY_LEN: usize = 256;
+
+/// Frame times reported by a drawing thread, shared with the window for display.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    history: Mutex<VecDeque<Duration>>,
+}
+
+impl FrameStats {
+    pub fn new() -> Self {
+        FrameStats::default()
+    }
+
+    /// Record how long the drawing thread took to draw a frame.
+    pub fn record_frame(&self, time: Duration) {
+        let mut history = self.history.lock().unwrap();
+        if history.len() == FRAME_HISTORY_LEN {
+            history.pop_front();
+        }
+        history.push_back(time);
+    }
+
+    /// Recent frame times, oldest first.
+    pub fn history(&self) -> Vec<Duration> {
+        self.history.lock().unwrap().iter().copied().collect()
+    }
+}
+
+/// Keyboard and mouse input to the window at some point in time.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Input {
+    /// Keys currently held down.
+    pub keys_down: HashSet<VirtualKeyCode>,
+    /// Keys pressed since the previous snapshot.
+    pub keys_pressed: HashSet<VirtualKeyCode>,
+    /// Mouse buttons currently held down.
+    pub buttons_down: HashSet<MouseButton>,
+    /// Cursor position in canvas pixels, or `None` if it's outside the window.
+    pub cursor: Option<Vec2<f32>>,
+    /// Raw mouse movement since the previous snapshot, with y pointing down.
+    pub mouse_delta: Vec2<f32>,
+}
+
+impl Input {
+    pub fn is_down(&self, key: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&key)
+    }
+
+    pub fn was_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    pub fn is_button_down(&self, button: MouseButton) -> bool {
+        self.buttons_down.contains(&button)
+    }
+
+    /// Forget presses and mouse movement, keeping what's held down and the cursor.
+    pub fn clear_accumulated(&mut self) {
+        self.keys_pressed.clear();
+        self.mouse_delta = Vec2::zero();
+    }
+}
+
+/// Input received by the window, shared with the drawing thread.
+#[derive(Debug, Default)]
+pub struct InputState {
+    input: Mutex<Input>,
+    closed: AtomicBool,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        InputState::default()
+    }
+
+    /// The current input, resetting what accumulates between snapshots.
+    pub fn snapshot(&self) -> Input {
+        let mut input = self.input.lock().unwrap();
+        let snapshot = input.clone();
+        input.clear_accumulated();
+        snapshot
+    }
+
+    /// Record a key being pressed, ignoring key repeat.
+    ///
+    /// The window calls this and the following methods as it receives events, but they can
+    /// also drive input without a window.
+    pub fn press_key(&self, key: VirtualKeyCode) {
+        self.update(|input| {
+            if input.keys_down.insert(key) {
+                input.keys_pressed.insert(key);
+            }
+        })
+    }
+
+    pub fn release_key(&self, key: VirtualKeyCode) {
+        self.update(|input| {
+            input.keys_down.remove(&key);
+        })
+    }
+
+    pub fn press_button(&self, button: MouseButton) {
+        self.update(|input| {
+            input.buttons_down.insert(button);
+        })
+    }
+
+    pub fn release_button(&self, button: MouseButton) {
+        self.update(|input| {
+            input.buttons_down.remove(&button);
+        })
+    }
+
+    /// Release every key and button, such as when the window loses focus and won't hear
+    /// about releases.
+    pub fn release_all(&self) {
+        self.update(|input| {
+            input.keys_down.clear();
+            input.buttons_down.clear();
+        })
+    }
+
+    /// Record the cursor position in canvas pixels, or `None` if it left the window.
+    pub fn move_cursor(&self, cursor: Option<Vec2<f32>>) {
+        self.update(|input| input.cursor = cursor)
+    }
+
+    /// Record raw mouse movement, with y pointing down.
+    pub fn move_mouse(&self, delta: Vec2<f32>) {
+        self.update(|input| input.mouse_delta += delta)
+    }
+
+    /// Whether the window has closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Input)) {
+        f(&mut self.input.lock().unwrap());
+    }
+}
+
+/// A corner of the window.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+/// Optional window behavior.
+#[derive(Clone, Debug)]
+pub struct WindowConfig {
+    /// Whether the performance overlay starts visible. It can be toggled with F3.
+    pub perf_overlay: bool,
+    /// Where the performance overlay is drawn.
+    pub perf_overlay_corner: Corner,
+    /// Frame times to display in the performance overlay.
+    ///
+    /// The drawing thread should hold a clone of this, and record its frames into it.
+    pub frame_stats: Arc<FrameStats>,
+    /// Input received by the window.
+    ///
+    /// The drawing thread should hold a clone of this, and read its input from it.
+    pub input: Arc<InputState>,
+    /// Palette which an indexed window resolves indices with.
+    ///
+    /// The drawing thread can hold a clone of this, and change it to recolor the window.
+    pub palette: Arc<PaletteState>,
+}
+
+impl Default for WindowConfig {
+    fn default() -> Self {
+        WindowConfig {
+            perf_overlay: false,
+            perf_overlay_corner: Corner::TopLeft,
+            frame_stats: Arc::new(FrameStats::new()),
+            input: Arc::new(InputState::new()),
+            palette: Arc::new(PaletteState::default()),
+        }
+    }
+}
+
 /// Open a software rendering window.
 ///
 /// This will take over the current thread (which should be the main thread) until the window
@@ -76,6 +324,61 @@ pub fn open_window(
     x_size: usize,
     y_size: usize,
     draw_thread: impl FnOnce(Arc<SegQueue<Paint>>) + Send + 'static,
+) {
+    open_window_with(x_size, y_size, WindowConfig::default(), draw_thread)
+}
+
+/// Open a software rendering window, with non-default configuration.
+///
+/// See `open_window`.
+pub fn open_window_with(
+    x_size: usize,
+    y_size: usize,
+    config: WindowConfig,
+    draw_thread: impl FnOnce(Arc<SegQueue<Paint>>) + Send + 'static,
+) {
+    run_window(x_size, y_size, config, draw_thread)
+}
+
+/// Open a software rendering window, which displays palette indices.
+///
+/// The drawing thread sends indices rather than colors, and the window looks up their
+/// colors in the palette as it displays them. To change the palette while the window is
+/// open, use `open_indexed_window_with`, and change the config's palette.
+///
+/// See `open_window`.
+pub fn open_indexed_window(
+    x_size: usize,
+    y_size: usize,
+    palette: Palette,
+    draw_thread: impl FnOnce(Arc<SegQueue<IndexPaint>>) + Send + 'static,
+) {
+    let config = WindowConfig {
+        palette: Arc::new(PaletteState::new(palette)),
+        ..WindowConfig::default()
+    };
+    open_indexed_window_with(x_size, y_size, config, draw_thread)
+}
+
+/// Open a software rendering window, which displays palette indices, with non-default
+/// configuration.
+///
+/// See `open_indexed_window`.
+pub fn open_indexed_window_with(
+    x_size: usize,
+    y_size: usize,
+    config: WindowConfig,
+    draw_thread: impl FnOnce(Arc<SegQueue<IndexPaint>>) + Send + 'static,
+) {
+    run_window(x_size, y_size, config, draw_thread)
+}
+
+/// Open a window which applies some kind of paint instruction.
+fn run_window<P: WindowPixel>(
+    x_size: usize,
+    y_size: usize,
+    config: WindowConfig,
+    draw_thread: impl FnOnce(Arc<SegQueue<P>>) + Send + 'static,
 ) {
     // reference-counted queue for painting
     let paint_queue_0 = Arc::new(SegQueue::new());
@@ -147,6 +450,14 @@ uniform int x_size;
 uniform int y_size;
 uniform usamplerBuffer canvas_buf;
 
+uniform bool indexed;
+uniform usamplerBuffer palette_buf;
+
+uniform bool overlay_enabled;
+uniform ivec2 overlay_min;
+uniform ivec2 overlay_size;
+uniform usamplerBuffer overlay_buf;
+
 in vec2 v_pos;
 in vec2 v_tex;
 
@@ -162,10 +473,25 @@ void main() {
 
     // retrieve the painted pixel
     uvec4 painted_256 = texelFetch(canvas_buf, index);
+
+    // look up its color, if it's a palette index
+    if (indexed) {
+        painted_256 = texelFetch(palette_buf, int(painted_256.r));
+    }
     vec4 painted = vec4(painted_256) / 256.0;
 
     // mix it in, by its alpha
     f_col = mix(f_col, painted, painted.a);
+
+    // mix in the overlay, if we're inside it
+    ivec2 overlay_xy = ivec2(tex_xy) - overlay_min;
+    if (overlay_enabled
+        && all(greaterThanEqual(overlay_xy, ivec2(0)))
+        && all(lessThan(overlay_xy, overlay_size))) {
+        int overlay_index = overlay_xy.y * overlay_size.x + overlay_xy.x;
+        vec4 overlay = vec4(texelFetch(overlay_buf, overlay_index)) / 256.0;
+        f_col = mix(f_col, overlay, overlay.a);
+    }
 }
 
         "###,
@@ -174,12 +500,8 @@ void main() {
 
     // buffer to store the pixels
     // memory-mapped between CPU and GPU
-    let mut canvas_buf_tex: BufferTexture<[u8; 4]> = {
-        let num_zeroes: usize = x_size * y_size;
-        let mut zeroes: Vec<[u8; 4]> = Vec::with_capacity(num_zeroes);
-        for _ in 0..num_zeroes {
-            zeroes.push([0x00, 0x00, 0x00, 0x00]);
-        }
+    let mut canvas_buf_tex: BufferTexture<P::Texel> = {
+        let zeroes: Vec<P::Texel> = vec![P::Texel::default(); x_size * y_size];
 
         BufferTexture::dynamic(
             &display,
@@ -188,15 +510,68 @@ void main() {
         ).expect("error creating buffer texture")
     };
 
+    // buffer to store the palette, for indexed windows
+    let mut palette_buf_tex: BufferTexture<[u8; 4]> = BufferTexture::empty_dynamic(
+        &display,
+        PALETTE_LEN,
+        BufferTextureType::Unsigned,
+    ).expect("error creating buffer texture");
+
+    // buffer to store the overlay pixels
+    let mut overlay_buf_tex: BufferTexture<[u8; 4]> = BufferTexture::empty_dynamic(
+        &display,
+        OVERLAY_X_SIZE * OVERLAY_Y_SIZE,
+        BufferTextureType::Unsigned,
+    ).expect("error creating buffer texture");
+
+    let overlay_min: [i32; 2] = {
+        let right = x_size as i32 - OVERLAY_X_SIZE as i32;
+        let top = y_size as i32 - OVERLAY_Y_SIZE as i32;
+        match config.perf_overlay_corner {
+            Corner::TopLeft => [0, top],
+            Corner::TopRight => [right, top],
+            Corner::BottomLeft => [0, 0],
+            Corner::BottomRight => [right, 0],
+        }
+    };
+
+    let mut perf_overlay = PerfOverlay::new();
+    let mut overlay_enabled = config.perf_overlay;
+    let mut pixels_uploaded = 0;
+
     // window loop
     let mut open = true;
     while open {
+        // update the overlay
+        perf_overlay.displayed_frame(pixels_uploaded);
+        if overlay_enabled {
+            let overlay = perf_overlay.redraw(paint_queue_0.len(), &config.frame_stats);
+            let mut overlay_mmap = overlay_buf_tex.map_write();
+            for (i, c) in overlay.pixels().iter().enumerate() {
+                overlay_mmap.set(i, [c.r, c.g, c.b, c.a]);
+            }
+        }
+
+        // upload the palette, if it's changed
+        if let Some(palette) = config.palette.take_changed() {
+            let mut palette_mmap = palette_buf_tex.map_write();
+            for (i, c) in palette.colors().iter().enumerate() {
+                palette_mmap.set(i, [c.r, c.g, c.b, c.a]);
+            }
+        }
+
         // render
         {
             let uniforms = glium::uniform! {
                 x_size: x_size as i32,
                 y_size: y_size as i32,
-                canvas_buf: &canvas_buf_tex
+                canvas_buf: &canvas_buf_tex,
+                indexed: P::INDEXED,
+                palette_buf: &palette_buf_tex,
+                overlay_enabled: overlay_enabled,
+                overlay_min: overlay_min,
+                overlay_size: [OVERLAY_X_SIZE as i32, OVERLAY_Y_SIZE as i32],
+                overlay_buf: &overlay_buf_tex
             };
 
             let draw_params = DrawParameters::default();
@@ -218,22 +593,17 @@ void main() {
         }
 
         // apply instructions from the paint queue
+        pixels_uploaded = 0;
         if !paint_queue_0.is_empty() {
             let mut canvas_mmap = canvas_buf_tex.map_write();
 
-            while let Ok(Paint {
-                             x,
-                             y,
-                             r,
-                             g,
-                             b,
-                             a,
-                         }) = paint_queue_0.pop() {
+            while let Ok(pixel) = paint_queue_0.pop() {
 
-                let rgba = [r, g, b, a];
+                let (x, y) = pixel.xy();
                 let i: usize = y * x_size + x;
 
-                canvas_mmap.set(i, rgba);
+                canvas_mmap.set(i, pixel.texel());
+                pixels_uploaded += 1;
 
             }
         }
@@ -293,11 +663,67 @@ void main() {
                     open = false;
                 }
 
+                Event::WindowEvent { event: WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(VirtualKeyCode::F3),
+                        state: ElementState::Pressed,
+                        ..
+                    },
+                    ..
+                }, .. } => {
+                    // toggle performance overlay
+                    overlay_enabled = !overlay_enabled;
+                }
+
                 _ => ()
 
             }
+
+            // record input for the drawing thread
+            match event {
+                Event::WindowEvent { event: WindowEvent::KeyboardInput {
+                    input: KeyboardInput {
+                        virtual_keycode: Some(key),
+                        state,
+                        ..
+                    },
+                    ..
+                }, .. } => match state {
+                    ElementState::Pressed => config.input.press_key(key),
+                    ElementState::Released => config.input.release_key(key),
+                },
+
+                Event::WindowEvent { event: WindowEvent::MouseInput { state, button, .. }, .. } => {
+                    match state {
+                        ElementState::Pressed => config.input.press_button(button),
+                        ElementState::Released => config.input.release_button(button),
+                    }
+                },
+
+                Event::WindowEvent { event: WindowEvent::CursorMoved { position, .. }, .. } => {
+                    // window coordinates have y pointing down
+                    let cursor = Vec2::new(position.x as f32, y_size as f32 - position.y as f32);
+                    config.input.move_cursor(Some(cursor))
+                },
+
+                Event::WindowEvent { event: WindowEvent::CursorLeft { .. }, .. } => {
+                    config.input.move_cursor(None)
+                },
+
+                Event::WindowEvent { event: WindowEvent::Focused(false), .. } => {
+                    config.input.release_all()
+                },
+
+                Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                    let delta = Vec2::new(delta.0 as f32, delta.1 as f32);
+                    config.input.move_mouse(delta)
+                },
+
+                _ => ()
+            }
         });
     }
 
+    config.input.closed.store(true, Ordering::Relaxed);
     trace!("closing window");
 }
\ No newline at end of file
@@ -0,0 +1,172 @@
+use cpurender::{
+    blit::*,
+    canvas::Canvas,
+    re::{
+        image::{self, RgbaImage},
+        vek::{Rect, Rgba, Vec2},
+    },
+};
+
+/// An image whose pixels record their own image coordinates.
+fn coords(width: u32, height: u32) -> RgbaImage {
+    RgbaImage::from_fn(width, height, |x, y| image::Rgba([x as u8, y as u8, 0, 255]))
+}
+
+/// The image coordinates recorded in a canvas pixel.
+fn coord_at(canvas: &Canvas, x: usize, y: usize) -> Option<(u8, u8)> {
+    let color = canvas.get(x, y);
+    Some((color.r, color.g)).filter(|_| color.a == 255)
+}
+
+#[test]
+fn placement_and_clipping() {
+    let image = coords(2, 3);
+    let mut canvas = Canvas::new(5, 5);
+    blit(&mut canvas, &image, Vec2::new(1, 1), &BlitOptions::default());
+
+    // upright, so the top-left of the image is at the top-left of where it lands
+    assert_eq!(coord_at(&canvas, 1, 3), Some((0, 0)));
+    assert_eq!(coord_at(&canvas, 2, 3), Some((1, 0)));
+    assert_eq!(coord_at(&canvas, 1, 1), Some((0, 2)));
+    assert_eq!(coord_at(&canvas, 0, 1), None);
+    assert_eq!(coord_at(&canvas, 1, 4), None);
+    assert_eq!(canvas.pixels().iter().filter(|c| c.a == 255).count(), 6);
+
+    // partly off the canvas
+    let mut canvas = Canvas::new(5, 5);
+    blit(&mut canvas, &image, Vec2::new(-1, -1), &BlitOptions::default());
+    assert_eq!(coord_at(&canvas, 0, 0), Some((1, 1)));
+    assert_eq!(coord_at(&canvas, 0, 1), Some((1, 0)));
+    assert_eq!(canvas.pixels().iter().filter(|c| c.a == 255).count(), 2);
+
+    blit(&mut canvas, &image, Vec2::new(4, 4), &BlitOptions::default());
+    assert_eq!(coord_at(&canvas, 4, 4), Some((0, 2)));
+
+    // or entirely off it
+    let mut canvas = Canvas::new(5, 5);
+    blit(&mut canvas, &image, Vec2::new(-2, 0), &BlitOptions::default());
+    blit(&mut canvas, &image, Vec2::new(0, 5), &BlitOptions::default());
+    assert!(canvas.pixels().iter().all(|&c| c == Rgba::zero()));
+}
+
+#[test]
+fn sprite_sheets() {
+    let sheet = coords(8, 8);
+    let source = Some(Rect::new(4, 2, 3, 2));
+    let blitted = |options: BlitOptions| {
+        let mut canvas = Canvas::new(8, 8);
+        blit(&mut canvas, &sheet, Vec2::zero(), &BlitOptions { source, ..options });
+        canvas
+    };
+
+    let canvas = blitted(BlitOptions::default());
+    assert_eq!(coord_at(&canvas, 0, 1), Some((4, 2)));
+    assert_eq!(coord_at(&canvas, 2, 0), Some((6, 3)));
+    assert_eq!(coord_at(&canvas, 3, 0), None);
+    assert_eq!(coord_at(&canvas, 0, 2), None);
+
+    let canvas = blitted(BlitOptions { flip_x: true, flip_y: true, ..Default::default() });
+    assert_eq!(coord_at(&canvas, 0, 1), Some((6, 3)));
+    assert_eq!(coord_at(&canvas, 2, 0), Some((4, 2)));
+
+    // each image pixel becomes a block
+    let canvas = blitted(BlitOptions { scale: 2, flip_x: true, ..Default::default() });
+    assert_eq!(coord_at(&canvas, 0, 3), Some((6, 2)));
+    assert_eq!(coord_at(&canvas, 1, 2), Some((6, 2)));
+    assert_eq!(coord_at(&canvas, 5, 0), Some((4, 3)));
+    assert_eq!(coord_at(&canvas, 6, 0), None);
+    assert_eq!(coord_at(&canvas, 0, 4), None);
+
+    // sources past the edge of the sheet are cut short
+    let mut canvas = Canvas::new(8, 8);
+    let options = BlitOptions { source: Some(Rect::new(6, 7, 10, 10)), ..Default::default() };
+    blit(&mut canvas, &sheet, Vec2::zero(), &options);
+    assert_eq!(coord_at(&canvas, 1, 0), Some((7, 7)));
+    assert_eq!(canvas.pixels().iter().filter(|c| c.a == 255).count(), 2);
+}
+
+#[test]
+fn composite_modes() {
+    let red = Rgba::new(255, 0, 0, 255);
+    let blue = Rgba::new(0, 0, 255, 255);
+    let half_red = Rgba::new(255, 0, 0, 128);
+    let none = Rgba::zero();
+
+    assert_eq!(Composite::Clear.apply(red, blue), none);
+    assert_eq!(Composite::Src.apply(half_red, blue), half_red);
+    assert_eq!(Composite::Dst.apply(half_red, blue), blue);
+    assert_eq!(Composite::SrcOver.apply(half_red, blue), Rgba::new(128, 0, 127, 255));
+    assert_eq!(Composite::DstOver.apply(half_red, blue), blue);
+    assert_eq!(Composite::DstOver.apply(red, none), red);
+
+    // in and out, by the other's coverage
+    assert_eq!(Composite::SrcIn.apply(red, blue), red);
+    assert_eq!(Composite::SrcIn.apply(red, none), none);
+    assert_eq!(Composite::SrcOut.apply(red, blue), none);
+    assert_eq!(Composite::SrcOut.apply(red, none), red);
+    assert_eq!(Composite::DstIn.apply(half_red, blue), Rgba::new(0, 0, 255, 128));
+    assert_eq!(Composite::DstOut.apply(half_red, blue), Rgba::new(0, 0, 255, 127));
+
+    // atop keeps the coverage of what's underneath
+    assert_eq!(Composite::SrcAtop.apply(half_red, blue), Rgba::new(128, 0, 127, 255));
+    assert_eq!(Composite::SrcAtop.apply(red, none), none);
+    assert_eq!(Composite::DstAtop.apply(half_red, blue), Rgba::new(0, 0, 255, 128));
+
+    assert_eq!(Composite::Xor.apply(red, blue), none);
+    assert_eq!(Composite::Xor.apply(red, none), red);
+    assert_eq!(Composite::Plus.apply(red, blue), Rgba::new(255, 0, 255, 255));
+    assert_eq!(Composite::Plus.apply(half_red, half_red), Rgba::new(255, 0, 0, 255));
+
+    // blitting uses them, even for transparent pixels
+    let image = RgbaImage::from_fn(2, 1, |x, _| image::Rgba([0, 255, 0, x as u8 * 255]));
+    let mut canvas = Canvas::filled(2, 1, blue);
+    let options = BlitOptions { composite: Composite::Src, ..Default::default() };
+    blit(&mut canvas, &image, Vec2::zero(), &options);
+    assert_eq!(canvas.get(0, 0), Rgba::new(0, 255, 0, 0));
+    assert_eq!(canvas.get(1, 0), Rgba::new(0, 255, 0, 255));
+
+    // but only where the image lands, even for operators which clear the rest
+    let mut canvas = Canvas::filled(3, 1, blue);
+    let options = BlitOptions { composite: Composite::Clear, ..Default::default() };
+    blit(&mut canvas, &image, Vec2::zero(), &options);
+    assert_eq!(canvas.get(1, 0), none);
+    assert_eq!(canvas.get(2, 0), blue);
+}
+
+#[test]
+fn tinting() {
+    let image = RgbaImage::from_pixel(1, 1, image::Rgba([255, 200, 100, 255]));
+    let tinted = |tint, composite| {
+        let mut canvas = Canvas::filled(1, 1, Rgba::new(0, 0, 0, 255));
+        let options = BlitOptions { tint, composite, ..Default::default() };
+        blit(&mut canvas, &image, Vec2::zero(), &options);
+        canvas.get(0, 0)
+    };
+
+    assert_eq!(tinted(Rgba::white(), Composite::Src), Rgba::new(255, 200, 100, 255));
+    assert_eq!(tinted(Rgba::new(255, 0, 128, 255), Composite::Src), Rgba::new(255, 0, 50, 255));
+
+    // the tint's alpha fades the image
+    let faded = tinted(Rgba::new(255, 255, 255, 128), Composite::Src);
+    assert_eq!(faded, Rgba::new(255, 200, 100, 128));
+    let faded = tinted(Rgba::new(255, 255, 255, 128), Composite::SrcOver);
+    assert_eq!(faded, Rgba::new(128, 100, 50, 255));
+}
+
+#[test]
+fn huge_placements() {
+    let image = coords(2, 2);
+    let mut canvas = Canvas::new(4, 4);
+
+    // scales too large to fit in the canvas's coordinates are clipped, not overflowed
+    let options = BlitOptions { scale: usize::MAX / 2, ..Default::default() };
+    blit(&mut canvas, &image, Vec2::new(-1, -1), &options);
+    assert!(canvas.pixels().iter().all(|&c| c == Rgba::new(0, 1, 0, 255)));
+
+    let options = BlitOptions { scale: 1 << 31, ..Default::default() };
+    blit(&mut canvas, &image, Vec2::new(i32::MIN, i32::MIN), &options);
+    assert!(canvas.pixels().iter().all(|&c| c == Rgba::new(1, 0, 0, 255)));
+    blit(&mut canvas, &image, Vec2::new(i32::MAX, 0), &options);
+    blit(&mut canvas, &image, Vec2::new(i32::MIN, i32::MIN), &BlitOptions::default());
+    assert!(canvas.pixels().iter().all(|&c| c == Rgba::new(1, 0, 0, 255)));
+}
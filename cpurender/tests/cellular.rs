@@ -0,0 +1,122 @@
+use cpurender::{
+    cellular::*,
+    re::{
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        vek::{Rgba, Vec2},
+    },
+};
+
+fn live_cells<R: Rule<Cell = bool>>(automaton: &Automaton<R>) -> Vec<Vec2<i32>> {
+    let x_size = automaton.size().x;
+    automaton.cells().iter()
+        .enumerate()
+        .filter(|&(_, &alive)| alive)
+        .map(|(i, _)| Vec2::new(i % x_size, i / x_size).map(|c| c as i32))
+        .collect()
+}
+
+#[test]
+fn glider_wraps_around() {
+    let mut life = Automaton::new(LifeLike::conway(), 6, 6);
+    let glider = [(1, 2), (2, 1), (0, 0), (1, 0), (2, 0)];
+    for &(x, y) in &glider {
+        life.set(Vec2::new(x, y), true);
+    }
+    let start = live_cells(&life);
+
+    // a glider moves one cell diagonally every 4 generations, so 24 brings it back
+    for _ in 0..24 {
+        life.step();
+    }
+    assert_eq!(life.generation(), 24);
+    assert_eq!(live_cells(&life), start);
+
+    // with bounded edges, it crashes into the corner and becomes a block
+    let mut life = life.with_edges(Edges::Bounded);
+    for _ in 0..40 {
+        life.step();
+    }
+    assert_eq!(live_cells(&life).len(), 4);
+}
+
+#[test]
+fn life_like_notation() {
+    let highlife = LifeLike::parse("B36/S23").unwrap();
+    assert!(highlife.birth[3] && highlife.birth[6] && !highlife.birth[2]);
+    assert!(highlife.survival[2] && highlife.survival[3] && !highlife.survival[4]);
+    assert_eq!(LifeLike::parse("b3/s23"), Some(LifeLike::conway()));
+    assert_eq!(LifeLike::parse("B3S23"), None);
+    assert_eq!(LifeLike::parse("B39/S23"), None);
+}
+
+#[test]
+fn brains_and_wires() {
+    // two firing cells make the cells beside both of them fire, then rest
+    let mut brain = Automaton::new(BriansBrain, 5, 5);
+    brain.set(Vec2::new(2, 2), BrainCell::Firing);
+    brain.set(Vec2::new(2, 3), BrainCell::Firing);
+    brain.step();
+    assert_eq!(brain.get(Vec2::new(2, 2)), Some(BrainCell::Refractory));
+    assert_eq!(brain.get(Vec2::new(1, 2)), Some(BrainCell::Firing));
+    assert_eq!(brain.get(Vec2::new(3, 3)), Some(BrainCell::Firing));
+    brain.step();
+    assert_eq!(brain.get(Vec2::new(2, 2)), Some(BrainCell::Off));
+
+    // an electron travels along a wire, one cell per generation
+    let mut wire = Automaton::new(Wireworld, 8, 1).with_edges(Edges::Bounded);
+    wire.fill_with(|_| WireCell::Conductor);
+    wire.set(Vec2::new(1, 0), WireCell::Head);
+    wire.set(Vec2::new(0, 0), WireCell::Tail);
+    for _ in 0..5 {
+        wire.step();
+    }
+    assert_eq!(wire.get(Vec2::new(6, 0)), Some(WireCell::Head));
+    assert_eq!(wire.get(Vec2::new(5, 0)), Some(WireCell::Tail));
+    assert_eq!(wire.cells().iter().filter(|&&c| c == WireCell::Head).count(), 1);
+}
+
+#[test]
+fn sand_settles_into_pile() {
+    let mut sand = Automaton::new(FallingSand, 21, 16);
+    let mut rng = StdRng::seed_from_u64(7);
+    sand.fill_with(|xy| match xy.y {
+        0 => Particle::Wall,
+        y if y > 8 && rng.gen_range(0, 3) == 0 => Particle::Sand,
+        _ => Particle::Empty,
+    });
+    let count = |sand: &Automaton<FallingSand>| {
+        sand.cells().iter().filter(|&&c| c == Particle::Sand).count()
+    };
+    let grains = count(&sand);
+
+    for _ in 0..100 {
+        sand.step();
+        assert_eq!(count(&sand), grains);
+    }
+
+    // no grain is left floating
+    for y in 2..16 {
+        for x in 0..21 {
+            if sand.get(Vec2::new(x, y)) == Some(Particle::Sand) {
+                assert_eq!(sand.get(Vec2::new(x, y - 1)), Some(Particle::Sand));
+            }
+        }
+    }
+}
+
+#[test]
+fn render_with_palette() {
+    let mut life = Automaton::new(LifeLike::conway(), 3, 2);
+    life.set(Vec2::new(2, 1), true);
+    let canvas = life.render(&LifeLike::palette(), 4);
+    assert_eq!(canvas.size(), Vec2::new(12, 8));
+    assert_eq!(canvas.get(8, 4), Rgba::white());
+    assert_eq!(canvas.get(11, 7), Rgba::white());
+    assert_eq!(canvas.get(7, 4), Rgba::black());
+
+    let palette = Palette::new(Rgba::zero())
+        .with(Particle::Sand, Rgba::white())
+        .with(Particle::Sand, Rgba::black());
+    assert_eq!(palette.color(Particle::Sand), Rgba::black());
+    assert_eq!(palette.color(Particle::Wall), Rgba::zero());
+}
@@ -0,0 +1,159 @@
+use cpurender::{
+    canvas::Canvas,
+    dither::*,
+    frag::render,
+    golden::Golden,
+    post::Effect,
+    re::{
+        image::{self, RgbaImage},
+        vek::{Rgba, Vec2},
+    },
+};
+
+/// Fraction of a channel's levels which are fully on, for 1 bit per channel.
+fn fraction_on(colors: &[Rgba<u8>]) -> f32 {
+    assert!(colors.iter().all(|c| c.r == 0 || c.r == 255));
+    colors.iter().filter(|c| c.r == 255).count() as f32 / colors.len() as f32
+}
+
+#[test]
+fn bayer_matrices() {
+    let ranks = |bayer: Bayer, y: i32| -> Vec<u32> {
+        let n = bayer.size() as i32;
+        (0..n).map(|x| (bayer.threshold(Vec2::new(x, y)) * (n * n) as f32) as u32).collect()
+    };
+    assert_eq!(ranks(Bayer::X2, 0), vec![0, 2]);
+    assert_eq!(ranks(Bayer::X2, 1), vec![3, 1]);
+    assert_eq!(ranks(Bayer::X4, 0), vec![0, 8, 2, 10]);
+    assert_eq!(ranks(Bayer::X4, 1), vec![12, 4, 14, 6]);
+
+    // every rank appears once, and the matrix tiles
+    let mut all: Vec<u32> = (0..8).flat_map(|y| ranks(Bayer::X8, y)).collect();
+    all.sort();
+    assert_eq!(all, (0..64).collect::<Vec<u32>>());
+    assert_eq!(Bayer::X8.threshold(Vec2::new(-3, 13)), Bayer::X8.threshold(Vec2::new(5, 5)));
+}
+
+#[test]
+fn blue_noise_is_evenly_spread() {
+    let n = BLUE_NOISE_SIZE as i32;
+    let mut all: Vec<f32> = (0..n * n).map(|i| blue_noise(Vec2::new(i % n, i / n))).collect();
+    assert_eq!(blue_noise(Vec2::new(-1, 2 * n)), blue_noise(Vec2::new(n - 1, 0)));
+
+    // no clumps or gaps, so every small block averages out
+    for block_y in 0..n / 8 {
+        for block_x in 0..n / 8 {
+            let block = Vec2::new(block_x, block_y) * 8;
+            let sum: f32 = (0..64).map(|i| blue_noise(block + Vec2::new(i % 8, i / 8))).sum();
+            assert!((sum / 64.0 - 0.5).abs() < 0.1, "block {} averages {}", block, sum / 64.0);
+        }
+    }
+
+    all.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    all.dedup();
+    assert_eq!(all.len(), (n * n) as usize);
+}
+
+#[test]
+fn dithering_keeps_average() {
+    let quarter = Canvas::filled(32, 32, Rgba::new(64, 64, 64, 200));
+    let dithers = [
+        Dither::Bayer(Bayer::X4),
+        Dither::BlueNoise,
+        Dither::FloydSteinberg,
+        Dither::Atkinson,
+    ];
+    for &dither in &dithers {
+        let dithered = Quantize::new(Quantizer::Levels(2), dither).apply(&quarter);
+        let on = fraction_on(dithered.pixels());
+        if dither == Dither::Atkinson {
+            // which loses a quarter of the error, so is darker
+            assert!(on > 0.1 && on < 0.25, "{:?} has {} on", dither, on);
+        } else {
+            assert!((on - 0.25).abs() < 0.02, "{:?} has {} on", dither, on);
+        }
+        assert!(dithered.pixels().iter().all(|c| c.a == 200));
+
+        // images work the same, in their own coordinates
+        let mut image = RgbaImage::from_pixel(32, 32, image::Rgba([64, 64, 64, 200]));
+        dither.apply(&mut image, &Quantizer::Levels(2));
+        assert_eq!(Pixels::colors(&image), Pixels::colors(&dithered));
+    }
+
+    // without dithering, it all rounds down
+    let mut nearest = quarter.clone();
+    Dither::Nearest.apply(&mut nearest, &Quantizer::Levels(2));
+    assert_eq!(fraction_on(nearest.pixels()), 0.0);
+}
+
+/// Clusters of colors around some centers, of some sizes, and transparent pixels to ignore.
+fn clusters(centers: &[Rgba<u8>], sizes: &[usize]) -> Vec<Rgba<u8>> {
+    let mut colors = vec![Rgba::new(255, 255, 255, 0); 100];
+    for (&center, &size) in centers.iter().zip(sizes) {
+        for j in 0..size {
+            let jitter = (j % 7) as i32 - 3;
+            colors.push(center.map(|c| (c as i32 + jitter).clamp(0, 255) as u8));
+        }
+    }
+    colors
+}
+
+/// Whether every center has a palette color close to it.
+fn has_centers(palette: &[Rgba<u8>], centers: &[Rgba<u8>]) -> bool {
+    centers.iter().all(|&center| {
+        palette.iter().any(|c| {
+            c.map2(center, |a, b| (a as i32 - b as i32).abs() <= 3).reduce_and()
+        })
+    })
+}
+
+#[test]
+fn palette_extraction() {
+    let centers = [
+        Rgba::new(200, 30, 30, 255),
+        Rgba::new(20, 180, 60, 255),
+        Rgba::new(40, 40, 220, 255),
+        Rgba::new(230, 230, 40, 255),
+    ];
+
+    // median cut splits clusters of equal size apart
+    let even = clusters(&centers, &[60; 4]);
+    let palette = median_cut(&even, 4);
+    assert!(has_centers(&palette, &centers), "{:?}", palette);
+
+    // but splits by population, so k-means is needed to find uneven ones
+    let uneven = clusters(&centers[..3], &[50, 100, 150]);
+    let palette = k_means(&uneven, 3, 10);
+    assert_eq!(palette.len(), 3);
+    assert!(has_centers(&palette, &centers[..3]), "{:?}", palette);
+
+    assert_eq!(median_cut(&uneven, 8).len(), 8);
+    assert_eq!(median_cut(&[Rgba::new(1, 2, 3, 255); 5], 4), vec![Rgba::new(1, 2, 3, 255)]);
+    assert_eq!(median_cut(&uneven, 0), vec![]);
+    assert_eq!(k_means(&uneven, 0, 10), vec![]);
+
+    // quantizing to the palette picks the closest
+    let quantizer = Quantizer::Palette(centers.to_vec());
+    assert_eq!(quantizer.quantize(Rgba::new(250, 0, 0, 9)), Rgba::new(200, 30, 30, 9));
+}
+
+#[test]
+fn dither_golden() {
+    // a band of each dither over a colorful gradient, quantized to 1 bit per channel
+    let dithers = [
+        Dither::Bayer(Bayer::X8),
+        Dither::BlueNoise,
+        Dither::FloydSteinberg,
+        Dither::Atkinson,
+    ];
+    let mut bands = Canvas::new(64, 64);
+    for (i, &dither) in dithers.iter().enumerate() {
+        let mut band = render(64, 16, |xy| {
+            let p = xy.map(|c| c as f32 / 63.0);
+            Rgba::new(p.x, 1.0 - p.x, p.x * 0.5 + p.y * 2.0, 1.0).map(|c| (c * 255.0) as u8)
+        });
+        dither.apply(&mut band, &Quantizer::Levels(2));
+        bands.draw_over(&band, Vec2::new(0, i as i32 * 16));
+    }
+    Golden::new("dither_bands", 64, 64).check(&bands);
+}
@@ -0,0 +1,102 @@
+use cpurender::{
+    canvas::Canvas,
+    frag::{render, render_with, Execution, Feedback, FrameClock},
+    re::{
+        rayon::ThreadPoolBuilder,
+        vek::{Rgba, Vec2},
+    },
+    Input,
+};
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+fn pattern(xy: Vec2<i32>) -> Rgba<u8> {
+    Rgba::new((xy.x * 7) as u8, (xy.y * 13) as u8, (xy.x ^ xy.y) as u8, 255)
+}
+
+#[test]
+fn executions_agree() {
+    let pool = Arc::new(ThreadPoolBuilder::new().num_threads(3).build().unwrap());
+    let executions = [
+        Execution::Global,
+        Execution::Pool(pool),
+        Execution::Threads(2),
+        Execution::Sequential,
+    ];
+
+    let expected = render_with(37, 23, &Execution::Sequential, pattern);
+    assert_eq!(expected.get(5, 3), pattern(Vec2::new(5, 3)));
+    for execution in &executions {
+        assert_eq!(render_with(37, 23, execution, pattern), expected, "{:?}", execution);
+    }
+}
+
+#[test]
+fn sequential_order() {
+    let order = Mutex::new(Vec::new());
+    render_with(4, 3, &Execution::Sequential, |xy| {
+        order.lock().unwrap().push(xy);
+        Rgba::zero()
+    });
+
+    let expected: Vec<Vec2<i32>> = (0..3)
+        .flat_map(|y| (0..4).map(move |x| Vec2::new(x, y)))
+        .collect();
+    assert_eq!(order.into_inner().unwrap(), expected);
+}
+
+#[test]
+fn feedback_scrolls_with_wrapping() {
+    let initial = render(7, 5, pattern);
+    let mut feedback = Feedback::new(initial.clone());
+
+    // scroll right by one pixel per frame, wrapping around
+    for _ in 0..7 {
+        feedback.step_with(&Execution::Sequential, |xy, previous| {
+            previous.wrapped(xy - Vec2::unit_x())
+        });
+    }
+    assert_eq!(feedback.frame(), 7);
+    assert_eq!(feedback.current(), &initial);
+}
+
+#[test]
+fn feedback_clamps_and_counts_frames() {
+    let mut feedback = Feedback::new(Canvas::filled(4, 3, Rgba::zero()));
+    feedback.step(|xy, _| Rgba::new(xy.x as u8 * 10, 0, 0, 255));
+
+    // copy the pixel to the left, clamped at the edge, and record the frame number
+    feedback.step(|xy, previous| {
+        let mut color = previous.clamped(xy - Vec2::unit_x());
+        color.g = previous.frame() as u8;
+        color
+    });
+    let row: Vec<u8> = (0..4).map(|x| feedback.current().get(x, 1).r).collect();
+    assert_eq!(row, vec![0, 0, 10, 20]);
+    assert_eq!(feedback.current().get(2, 2).g, 1);
+}
+
+#[test]
+fn frame_clock_times_from_the_first_frame() {
+    let mut clock = FrameClock::new();
+    let size = Vec2::new(4, 3);
+    let start = Instant::now() + Duration::from_secs(1);
+
+    let first = clock.next_frame(start, size, Input::default());
+    assert_eq!(first.frame, 0);
+    assert_eq!((first.time, first.delta), (Duration::ZERO, Duration::ZERO));
+    assert_eq!(first.size, size);
+
+    let second = clock.next_frame(start + Duration::from_millis(16), size, Input::default());
+    assert_eq!(second.frame, 1);
+    assert_eq!(second.time, Duration::from_millis(16));
+    assert_eq!(second.delta, Duration::from_millis(16));
+
+    let third = clock.next_frame(start + Duration::from_millis(50), size, Input::default());
+    assert_eq!(third.frame, 2);
+    assert_eq!(third.time, Duration::from_millis(50));
+    assert_eq!(third.delta, Duration::from_millis(34));
+}
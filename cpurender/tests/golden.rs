@@ -0,0 +1,157 @@
+use cpurender::{
+    camera::Camera,
+    canvas::Canvas,
+    golden::{compare, Golden},
+    lighting::{Light, VoxelLighting},
+    scene::{self, Intersect},
+    sdf,
+    text::{draw_text, Font, TextStyle},
+    voxel::VoxelGrid,
+    re::{
+        rand::Rng,
+        vek::{Rgb, Rgba, Vec2, Vec3},
+    },
+};
+
+const SIZE: usize = 64;
+
+fn to_rgba(rgb: Rgb<f32>) -> Rgba<u8> {
+    Rgba::from_opaque(rgb.map(|c| (c.clamp(0.0, 1.0) * 255.0) as u8))
+}
+
+/// The checkerboard lattice from `foo`.
+#[test]
+fn voxel_lattice() {
+    let grid = VoxelGrid::from_fn(Vec3::new(5, 5, 5), |xyz| {
+        match xyz.sum() % 2 {
+            0 => Some(xyz.map(|c| c as f32 / 4.0)),
+            _ => None,
+        }
+    });
+    let camera = Camera::perspective(SIZE, SIZE, 60f32.to_radians())
+        .at(Vec3::new(-4.0, 8.0, -5.0))
+        .looking_at(Vec3::broadcast(2.5), Vec3::unit_y());
+    let lighting = VoxelLighting {
+        lights: vec![
+            Light::Directional {
+                direction: Vec3::new(-0.4, -1.0, 0.6),
+                color: Rgb::broadcast(0.7),
+            },
+            Light::Point {
+                position: Vec3::new(2.5, 7.5, -2.5),
+                color: Rgb::new(6.0, 5.0, 4.0),
+            },
+        ],
+        ..VoxelLighting::default()
+    };
+
+    Golden::new("voxel_lattice", SIZE, SIZE)
+        .with_max_differing(4)
+        .check_fragment(|xy, _| {
+            let ray = camera.ray_for_pixel(xy);
+            to_rgba(lighting.shade(&grid, ray, |voxel| voxel.map(Rgb::from)))
+        });
+}
+
+/// Analytic shapes, antialiased with random jitter.
+#[test]
+fn analytic_scene() {
+    let scene = scene::Scene::new()
+        .with(scene::Plane { point: Vec3::zero(), normal: Vec3::unit_y() }, Rgb::new(0.6, 0.6, 0.6))
+        .with(scene::Sphere { center: Vec3::new(-1.2, 1.0, 0.0), radius: 1.0 }, Rgb::new(0.9, 0.2, 0.2))
+        .with(scene::Cylinder { a: Vec3::new(1.2, 0.0, 0.0), b: Vec3::new(1.2, 1.5, 0.0), radius: 0.6 }, Rgb::new(0.2, 0.4, 0.9));
+    let camera = Camera::perspective(SIZE, SIZE, 60f32.to_radians())
+        .at(Vec3::new(0.0, 2.5, -5.0))
+        .looking_at(Vec3::new(0.0, 0.7, 0.0), Vec3::unit_y());
+    let to_light = Vec3::new(-0.5, 1.0, -0.3).normalized();
+
+    Golden::new("analytic_scene", SIZE, SIZE)
+        .with_seed(7)
+        .with_max_differing(4)
+        .check_fragment(|xy, rng| {
+            let mut color = Rgb::zero();
+            for _ in 0..4 {
+                let jitter = Vec2::new(rng.gen_range(-0.5, 0.5), rng.gen_range(-0.5, 0.5));
+                let ndc = (xy.map(|c| c as f32) + Vec2::broadcast(0.5) + jitter)
+                    / SIZE as f32 * 2.0 - Vec2::one();
+                let ray = camera.ray_for_ndc(ndc);
+                color += match scene.hit(ray, f32::INFINITY) {
+                    Some((hit, &albedo)) => {
+                        let shadow_ray = scene::Ray::new(hit.point + hit.normal * 0.001, to_light);
+                        let lit = !scene.occludes(shadow_ray, f32::INFINITY);
+                        let lambert = hit.normal.dot(to_light).max(0.0) * lit as u8 as f32;
+                        albedo * (0.2 + 0.8 * lambert)
+                    },
+                    None => Rgb::new(0.5, 0.7, 1.0),
+                };
+            }
+            to_rgba(color / 4.0)
+        });
+}
+
+fn sdf_scene(p: Vec3<f32>) -> f32 {
+    let ground = sdf::plane(p, Vec3::unit_y());
+    let blob = sdf::smooth_union(
+        sdf::sphere(sdf::translate(p, Vec3::new(0.0, 1.0, 0.0)), 0.8),
+        sdf::torus(sdf::translate(p, Vec3::new(0.0, 0.3, 0.0)), 1.2, 0.25),
+        0.3,
+    );
+    let pillars = sdf::cylinder(
+        sdf::translate(sdf::repeat_limited(p, Vec3::new(2.0, 0.0, 2.0), Vec3::new(1.0, 0.0, 1.0)), Vec3::new(0.0, 0.5, 3.0)),
+        0.5,
+        0.15,
+    );
+    sdf::union(ground, sdf::union(blob, pillars))
+}
+
+/// A sphere traced distance field, with soft shadows and ambient occlusion.
+#[test]
+fn sdf_scene_shading() {
+    let camera = Camera::perspective(SIZE, SIZE, 60f32.to_radians())
+        .at(Vec3::new(0.0, 3.0, -5.0))
+        .looking_at(Vec3::new(0.0, 0.5, 0.0), Vec3::unit_y());
+    let tracer = sdf::SphereTracer::default();
+    let to_light = Vec3::new(0.6, 1.0, -0.4).normalized();
+
+    Golden::new("sdf_scene", SIZE, SIZE)
+        .with_max_differing(4)
+        .check_fragment(|xy, _| {
+            let ray = camera.ray_for_pixel(xy);
+            let color = match tracer.trace(sdf_scene, ray) {
+                Some(hit) => {
+                    let shadow_ray = scene::Ray::new(hit.point, to_light);
+                    let shadow = sdf::soft_shadow(sdf_scene, shadow_ray, 0.02, 20.0, 8.0);
+                    let ao = sdf::ambient_occlusion(sdf_scene, hit.point, hit.normal, 5, 0.1);
+                    let lambert = hit.normal.dot(to_light).max(0.0);
+                    Rgb::new(0.9, 0.8, 0.7) * (0.25 * ao + 0.75 * lambert * shadow)
+                },
+                None => Rgb::new(0.3, 0.4, 0.6),
+            };
+            to_rgba(color)
+        });
+}
+
+#[test]
+fn text() {
+    let mut canvas = Canvas::filled(SIZE, 24, Rgba::new(0, 0, 0, 255));
+    let font = Font::embedded();
+    draw_text(&mut canvas, &font, Vec2::new(2, 21), "Hello,\nworld!", &TextStyle::default());
+
+    Golden::new("text", SIZE, 24)
+        .with_tolerance(0)
+        .check(&canvas);
+}
+
+#[test]
+fn comparison() {
+    let a = Canvas::filled(4, 4, Rgba::new(100, 100, 100, 255));
+    let mut b = a.clone();
+    b.set(0, 0, Rgba::new(103, 100, 100, 255));
+    b.set(1, 0, Rgba::new(101, 100, 100, 255));
+
+    let comparison = compare(&a, &b, 2);
+    assert_eq!(comparison.differing, 1);
+    assert_eq!(comparison.max_difference, 3);
+    assert_eq!(comparison.diff.get(0, 0), Rgba::new(255, 0, 0, 255));
+    assert_ne!(comparison.diff.get(1, 0), Rgba::new(255, 0, 0, 255));
+}
@@ -0,0 +1,142 @@
+use cpurender::{
+    frag::fragment_graph,
+    graph::{GraphError, RenderGraph},
+    re::vek::{Rgba, Vec2},
+};
+
+/// Per-pixel geometry, as a G-buffer would store it.
+#[derive(Copy, Clone, Debug)]
+struct Surface {
+    depth: f32,
+    albedo: u8,
+}
+
+#[test]
+fn deferred_passes_in_dependency_order() {
+    let mut graph = RenderGraph::new(8, 4);
+    let surfaces = graph.buffer::<Surface>("surfaces");
+    let light = graph.buffer::<f32>("light");
+    let output = graph.buffer::<Rgba<u8>>("output");
+
+    // declared out of order, so the graph has to sort them
+    graph.pass("shade")
+        .reads(surfaces)
+        .reads(light)
+        .writes(output, move |xy, inputs| {
+            let surface = inputs.buffer(surfaces).get(xy);
+            let light = *inputs.buffer(light).get(xy);
+            let value = (surface.albedo as f32 * light) as u8;
+            Rgba::new(value, (surface.depth * 10.0) as u8, 0, 255)
+        });
+    graph.pass("light")
+        .reads(surfaces)
+        .writes(light, move |xy, inputs| 1.0 / inputs.buffer(surfaces).get(xy).depth);
+    graph.pass("geometry").writes(surfaces, |xy, _| Surface {
+        depth: 1.0 + xy.x as f32,
+        albedo: 200,
+    });
+
+    assert_eq!(graph.pass_order().unwrap(), vec!["geometry", "light", "shade"]);
+    let canvas = graph.render(output).unwrap();
+    assert_eq!(canvas.size(), Vec2::new(8, 4));
+    assert_eq!(canvas.get(0, 2), Rgba::new(200, 10, 0, 255));
+    assert_eq!(canvas.get(3, 1), Rgba::new(50, 40, 0, 255));
+}
+
+#[test]
+fn previous_frame_feedback() {
+    let mut graph = RenderGraph::new(3, 2);
+    let counter = graph.buffer::<u32>("counter");
+    let half = graph.buffer_sized::<u32>("half", 2, 1);
+
+    // each frame adds to what the buffer held last frame
+    graph.pass("accumulate")
+        .reads_previous(counter)
+        .writes(counter, move |xy, inputs| {
+            let previous = inputs.previous(counter).map_or(0, |b| *b.get(xy));
+            previous + xy.x as u32 + 1
+        });
+    graph.pass("downsample")
+        .reads(counter)
+        .writes(half, move |xy, inputs| {
+            *inputs.buffer(counter).get(xy * 2) + inputs.frame() as u32
+        });
+
+    for _ in 0..3 {
+        graph.execute().unwrap();
+    }
+    assert_eq!(graph.get(counter).unwrap().pixels(), &[3, 6, 9, 3, 6, 9]);
+    assert_eq!(graph.get(half).unwrap().pixels(), &[5, 11]);
+}
+
+#[test]
+fn invalid_graphs() {
+    let mut graph = RenderGraph::new(1, 1);
+    let a = graph.buffer::<u8>("a");
+    let b = graph.buffer::<u8>("b");
+    graph.pass("first").reads(b).writes(a, |_, _| 0);
+    graph.pass("second").reads(a).writes(b, |_, _| 0);
+    assert_eq!(graph.execute(), Err(GraphError::Cycle {
+        passes: vec!["first".to_owned(), "second".to_owned()],
+    }));
+
+    let mut graph = RenderGraph::new(1, 1);
+    let a = graph.buffer::<u8>("a");
+    let b = graph.buffer::<u8>("b");
+    graph.pass("first").writes(a, |_, _| 0);
+    graph.pass("second").reads(b).writes(a, |_, _| 0);
+    assert_eq!(graph.compile(), Err(GraphError::MultipleWriters { buffer: "a".to_owned() }));
+
+    let mut graph = RenderGraph::new(1, 1);
+    let a = graph.buffer::<u8>("a");
+    let b = graph.buffer::<u8>("b");
+    graph.pass("first").reads_previous(b).writes(a, |_, _| 0);
+    assert_eq!(graph.compile(), Err(GraphError::NeverWritten {
+        buffer: "b".to_owned(),
+        pass: Some("first".to_owned()),
+    }));
+}
+
+#[test]
+fn unwritten_output() {
+    let mut graph = RenderGraph::new(2, 2);
+    let color = graph.buffer::<Rgba<u8>>("color");
+    let output = graph.buffer::<Rgba<u8>>("output");
+    graph.pass("shade").writes(color, |_, _| Rgba::white());
+    let error = GraphError::NeverWritten { buffer: "output".to_owned(), pass: None };
+    assert_eq!(graph.render(output), Err(error.clone()));
+    assert_eq!(graph.compile(), Ok(()));
+
+    // which is caught before opening a window
+    assert_eq!(fragment_graph(graph, output), Err(error));
+}
+
+#[test]
+fn output_sized_differently() {
+    // outputs keep their own size, which the window is opened at
+    let mut graph = RenderGraph::new(4, 4);
+    let small = graph.buffer_sized::<Rgba<u8>>("small", 2, 3);
+    let large = graph.buffer_sized::<Rgba<u8>>("large", 8, 5);
+    graph.pass("small").writes(small, |xy, _| Rgba::new(xy.x as u8, xy.y as u8, 0, 255));
+    graph.pass("large").reads(small).writes(large, move |xy, inputs| *inputs.buffer(small).get(xy));
+
+    assert_eq!(graph.buffer_size(small), Vec2::new(2, 3));
+    assert_eq!(graph.buffer_size(large), Vec2::new(8, 5));
+    let canvas = graph.render(small).unwrap();
+    assert_eq!(canvas.size(), Vec2::new(2, 3));
+    assert_eq!(canvas.get(1, 2), Rgba::new(1, 2, 0, 255));
+    let canvas = graph.render(large).unwrap();
+    assert_eq!(canvas.size(), Vec2::new(8, 5));
+    assert_eq!(canvas.get(7, 4), Rgba::new(1, 2, 0, 255));
+}
+
+#[test]
+#[should_panic(expected = "without declaring it")]
+fn undeclared_read() {
+    let mut graph = RenderGraph::new(2, 2);
+    let a = graph.buffer::<u8>("a");
+    let b = graph.buffer::<u8>("b");
+    graph.pass("first").writes(a, |_, _| 1);
+    graph.pass("second").writes(b, move |xy, inputs| *inputs.buffer(a).get(xy));
+    graph.execute().unwrap();
+}
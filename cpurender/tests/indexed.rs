@@ -0,0 +1,63 @@
+use cpurender::{
+    frag::{render_indexed, render_indexed_with, Execution},
+    indexed::*,
+    re::vek::{Rgba, Vec2},
+    IndexPaint,
+    SegQueue,
+};
+
+fn red(r: u8) -> Rgba<u8> {
+    Rgba::new(r, 0, 0, 0xFF)
+}
+
+#[test]
+fn palette_cycles_within_range() {
+    let mut palette = Palette::new(&[red(0), red(1), red(2), red(3), red(4)]);
+    assert_eq!(palette.colors().len(), PALETTE_LEN);
+    assert_eq!(palette.get(5), Rgba::zero());
+
+    palette.cycle(1..=3, 1);
+    let first = palette.colors()[..5].to_vec();
+    assert_eq!(first, vec![red(0), red(3), red(1), red(2), red(4)]);
+
+    // cycling backwards, or by a whole lap, undoes it
+    palette.cycle(1..=3, -1);
+    palette.cycle(1..=3, 3);
+    assert_eq!(palette, Palette::new(&[red(0), red(1), red(2), red(3), red(4)]));
+
+    // the full range works, and empty ranges do nothing
+    let mut gray = Palette::grayscale();
+    gray.cycle(0..=255, 1);
+    assert_eq!(gray.get(0), Rgba::new(255, 255, 255, 255));
+    let (start, end) = (3, 2);
+    gray.cycle(start..=end, 1);
+    assert_eq!(gray.get(3), Rgba::new(2, 2, 2, 255));
+}
+
+#[test]
+fn palette_state_updates() {
+    let state = PaletteState::new(Palette::grayscale());
+    state.update(|palette| palette.set(7, red(7)));
+    assert_eq!(state.get().get(7), red(7));
+    state.set(Palette::new(&[red(1)]));
+    assert_eq!(state.get(), Palette::new(&[red(1)]));
+}
+
+#[test]
+fn render_and_resolve() {
+    let fragment = |xy: Vec2<i32>| (xy.x + xy.y * 4) as u8;
+    let canvas = render_indexed(4, 3, fragment);
+    assert_eq!(canvas, render_indexed_with(4, 3, &Execution::Sequential, fragment));
+    assert_eq!(canvas.get(1, 2), 9);
+
+    // recoloring only needs a different palette
+    let colors = canvas.resolve(&Palette::from_fn(red));
+    assert_eq!(colors.get(1, 2), red(9));
+    let colors = canvas.resolve(&Palette::grayscale());
+    assert_eq!(colors.get(3, 0), Rgba::new(3, 3, 3, 255));
+
+    let queue = SegQueue::new();
+    canvas.paint(&queue);
+    assert_eq!(queue.len(), 12);
+    assert_eq!(queue.pop().ok(), Some(IndexPaint { x: 0, y: 0, index: 0 }));
+}
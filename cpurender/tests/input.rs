@@ -0,0 +1,74 @@
+use cpurender::{
+    re::vek::Vec2,
+    InputState,
+    MouseButton,
+    VirtualKeyCode as Key,
+};
+
+#[test]
+fn snapshots_drain_presses_and_movement() {
+    let state = InputState::new();
+    state.press_key(Key::W);
+    state.press_key(Key::W);
+    state.press_button(MouseButton::Left);
+    state.move_mouse(Vec2::new(3.0, -1.0));
+    state.move_mouse(Vec2::new(2.0, 4.0));
+    state.move_cursor(Some(Vec2::new(10.0, 20.0)));
+
+    let first = state.snapshot();
+    assert!(first.is_down(Key::W) && first.was_pressed(Key::W));
+    assert!(first.is_button_down(MouseButton::Left));
+    assert_eq!(first.mouse_delta, Vec2::new(5.0, 3.0));
+    assert_eq!(first.cursor, Some(Vec2::new(10.0, 20.0)));
+
+    // held keys and buttons persist, but presses and movement were taken by the first
+    let second = state.snapshot();
+    assert!(second.is_down(Key::W) && !second.was_pressed(Key::W));
+    assert!(second.is_button_down(MouseButton::Left));
+    assert_eq!(second.mouse_delta, Vec2::zero());
+    assert_eq!(second.cursor, Some(Vec2::new(10.0, 20.0)));
+
+    // a key pressed and released between snapshots is still seen as pressed
+    state.press_key(Key::Space);
+    state.release_key(Key::Space);
+    state.release_key(Key::W);
+    state.release_button(MouseButton::Left);
+    state.move_cursor(None);
+    let third = state.snapshot();
+    assert!(third.was_pressed(Key::Space) && !third.is_down(Key::Space));
+    assert!(!third.is_down(Key::W) && !third.is_button_down(MouseButton::Left));
+    assert_eq!(third.cursor, None);
+}
+
+#[test]
+fn held_keys_repress_after_release() {
+    let state = InputState::new();
+    state.press_key(Key::A);
+    state.snapshot();
+    state.release_key(Key::A);
+    state.press_key(Key::A);
+    assert!(state.snapshot().was_pressed(Key::A));
+
+    // losing focus releases everything, so nothing is stuck down
+    state.press_key(Key::S);
+    state.press_button(MouseButton::Right);
+    state.release_all();
+    let input = state.snapshot();
+    assert!(input.keys_down.is_empty() && input.buttons_down.is_empty());
+    assert!(input.was_pressed(Key::S));
+    assert!(!state.is_closed());
+}
+
+#[test]
+fn splitting_input_between_ticks() {
+    let state = InputState::new();
+    state.press_key(Key::D);
+    state.move_mouse(Vec2::new(1.0, 1.0));
+
+    // the first tick before a frame gets the presses, and later ones only what's held
+    let mut tick_input = state.snapshot();
+    assert!(tick_input.was_pressed(Key::D));
+    tick_input.clear_accumulated();
+    assert!(tick_input.is_down(Key::D) && !tick_input.was_pressed(Key::D));
+    assert_eq!(tick_input.mouse_delta, Vec2::zero());
+}
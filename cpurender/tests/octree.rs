@@ -0,0 +1,116 @@
+use cpurender::{
+    octree::VoxelOctree,
+    ray::Ray,
+    voxel::*,
+    re::{
+        rand::{rngs::StdRng, Rng, SeedableRng},
+        vek::*,
+    },
+};
+
+/// Sparse scene with large empty regions: a floor, and some scattered clusters.
+fn scene(size: Vec3<usize>) -> VoxelGrid<u8> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut grid = VoxelGrid::new(size, 0);
+    for x in 0..size.x as i32 {
+        for z in 0..size.z as i32 {
+            grid.set(Vec3::new(x, 0, z), 1);
+        }
+    }
+    for _ in 0..12 {
+        let center = Vec3::new(
+            rng.gen_range(0, size.x as i32),
+            rng.gen_range(0, size.y as i32),
+            rng.gen_range(0, size.z as i32),
+        );
+        for _ in 0..20 {
+            let offset = Vec3::new(rng.gen_range(-3, 4), rng.gen_range(-3, 4), rng.gen_range(-3, 4));
+            if let Some(voxel) = grid.get_mut(center + offset) {
+                *voxel = rng.gen_range(2, 255);
+            }
+        }
+    }
+    grid
+}
+
+fn dense_hits(grid: &VoxelGrid<u8>, ray: Ray) -> Vec<(VoxelHit, u8)> {
+    grid.trace(ray, 1000.0)
+        .filter(|&(_, &v)| v != 0)
+        .map(|(hit, &v)| (hit, v))
+        .collect()
+}
+
+fn octree_hits(octree: &VoxelOctree<u8>, ray: Ray) -> Vec<(VoxelHit, u8)> {
+    octree.trace(ray, 1000.0)
+        .map(|(hit, &v)| (hit, v))
+        .collect()
+}
+
+fn assert_same_hits(dense: &[(VoxelHit, u8)], sparse: &[(VoxelHit, u8)], ray: Ray) {
+    assert_eq!(dense.len(), sparse.len(), "different hit counts for {:?}", ray);
+    for (&(a, av), &(b, bv)) in dense.iter().zip(sparse) {
+        assert_eq!(a.voxel, b.voxel, "{:?}", ray);
+        assert_eq!(a.face, b.face, "{:?}", ray);
+        assert_eq!(a.normal, b.normal, "{:?}", ray);
+        assert!((a.distance - b.distance).abs() < 0.001, "{:?}", ray);
+        assert_eq!(av, bv);
+    }
+}
+
+#[test]
+fn matches_dense_random_rays() {
+    let grid = scene(Vec3::new(40, 30, 50));
+    let octree = VoxelOctree::from_grid(&grid, |&v| v == 0);
+    let mut rng = StdRng::seed_from_u64(1);
+
+    for _ in 0..2000 {
+        let origin = Vec3::new(
+            rng.gen_range(-20.0, 60.0),
+            rng.gen_range(-20.0, 50.0),
+            rng.gen_range(-20.0, 70.0),
+        );
+        let dir = Vec3::new(
+            rng.gen_range(-1.0, 1.0),
+            rng.gen_range(-1.0, 1.0),
+            rng.gen_range(-1.0, 1.0),
+        ).normalized();
+        let ray = Ray::new(origin, dir);
+        assert_same_hits(&dense_hits(&grid, ray), &octree_hits(&octree, ray), ray);
+    }
+}
+
+#[test]
+fn matches_dense_aligned_rays() {
+    let grid = scene(Vec3::new(16, 16, 16));
+    let octree = VoxelOctree::from_grid(&grid, |&v| v == 0);
+
+    let rays = [
+        Ray::new(Vec3::new(0.5, 8.0, 0.5), Vec3::new(1.0, -1.0, 1.0).normalized()),
+        Ray::new(Vec3::new(3.5, 20.0, 3.5), Vec3::new(0.0, -1.0, 0.0)),
+        Ray::new(Vec3::new(-4.0, 4.0, 2.5), Vec3::new(1.0, 0.0, 0.0)),
+        Ray::new(Vec3::new(8.0, 8.0, 8.0), Vec3::new(-1.0, -1.0, -1.0).normalized()),
+        Ray::new(Vec3::new(16.0, 0.5, 16.0), Vec3::new(-1.0, 0.0, -1.0).normalized()),
+    ];
+    for &ray in &rays {
+        assert_same_hits(&dense_hits(&grid, ray), &octree_hits(&octree, ray), ray);
+    }
+}
+
+#[test]
+fn lookup_and_sparsity() {
+    let mut grid = VoxelGrid::new(Vec3::new(64, 64, 64), 0u8);
+    grid.set(Vec3::new(10, 20, 30), 7);
+    let octree = VoxelOctree::from_grid(&grid, |&v| v == 0);
+
+    assert_eq!(octree.get(Vec3::new(10, 20, 30)), Some(&7));
+    assert_eq!(octree.get(Vec3::new(10, 20, 31)), None);
+    assert_eq!(octree.get(Vec3::new(64, 0, 0)), None);
+    // one branch of 8 per level, plus the root
+    assert_eq!(octree.node_count(), 1 + 8 * 6);
+
+    let ray = Ray::new(Vec3::new(10.5, 20.5, -5.0), Vec3::new(0.0, 0.0, 1.0));
+    let (hit, &v) = octree.first_hit(ray, 100.0).unwrap();
+    assert_eq!(hit.voxel, Vec3::new(10, 20, 30));
+    assert_eq!(hit.face, Some(Face::NegZ));
+    assert_eq!(v, 7);
+}
@@ -0,0 +1,53 @@
+use cpurender::{
+    frag::{render, render_packets, render_packets_with, Execution},
+    packet::*,
+    re::vek::{Rgba, Vec2, Vec3},
+};
+
+fn pattern(xy: Vec2<i32>) -> Rgba<u8> {
+    Rgba::new((xy.x * 7) as u8, (xy.y * 13) as u8, (xy.x ^ xy.y) as u8, 255)
+}
+
+#[test]
+fn lanewise_ops() {
+    let a = F32s::<4>::from_fn(|i| i as f32);
+    let b = F32s::splat(2.0);
+    assert_eq!(a + b, Packet([2.0, 3.0, 4.0, 5.0]));
+    assert_eq!(a * 2.0 - b, Packet([-2.0, 0.0, 2.0, 4.0]));
+    assert_eq!((-a).abs(), a);
+    assert_eq!(a.min(b), Packet([0.0, 1.0, 2.0, 2.0]));
+
+    let mask = a.simd_lt(b);
+    assert_eq!(mask, Packet([true, true, false, false]));
+    assert_eq!(mask.count(), 2);
+    assert!(mask.any() && !mask.all());
+    assert_eq!(mask.select(a, b), Packet([0.0, 1.0, 2.0, 2.0]));
+    assert_eq!(!mask & Mask::first(3), Packet([false, false, true, false]));
+
+    // vek's vector operations work on vectors of packets
+    let v = Vec3::new(a, b, F32s::splat(0.0));
+    assert_eq!(v.dot(v).lane(3), 13.0);
+    let n = normalized3(splat3::<4>(Vec3::new(3.0, 0.0, 4.0)));
+    assert_eq!(lane3(n, 1), Vec3::new(0.6, 0.0, 0.8));
+}
+
+#[test]
+fn partial_packets() {
+    let packet = PixelPacket::<8>::new(16, 3, 21);
+    assert_eq!(packet.mask, Mask::first(5));
+    assert_eq!(packet.lane(4), Vec2::new(20, 3));
+    assert_eq!(packet.xy().x.lane(7), 23.0);
+}
+
+#[test]
+fn packets_match_scalar() {
+    let expected = render(37, 23, pattern);
+    let fragment = |p: PixelPacket<8>| {
+        assert!(p.mask.lane(0));
+        std::array::from_fn(|i| pattern(p.lane(i)))
+    };
+    assert_eq!(render_packets(37, 23, fragment), expected);
+    assert_eq!(render_packets_with(37, 23, &Execution::Sequential, fragment), expected);
+    let wide = |p: PixelPacket<16>| std::array::from_fn(|i| pattern(p.lane(i)));
+    assert_eq!(render_packets(37, 23, wide), expected);
+}
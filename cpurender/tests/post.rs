@@ -0,0 +1,95 @@
+use cpurender::{
+    canvas::Canvas,
+    frag::render,
+    golden::Golden,
+    post::*,
+    re::vek::{Rgba, Vec2},
+};
+
+/// A bright disc and a dark diagonal edge on a mid-gray background.
+fn test_pattern(size: usize) -> Canvas {
+    render(size, size, |xy| {
+        let p = xy.map(|c| c as f32 + 0.5) / size as f32;
+        if p.distance(Vec2::new(0.3, 0.7)) < 0.15 {
+            Rgba::new(255, 240, 200, 255)
+        } else if p.x + p.y * 0.6 > 0.9 {
+            Rgba::new(20, 40, 90, 255)
+        } else {
+            Rgba::new(128, 128, 128, 255)
+        }
+    })
+}
+
+#[test]
+fn flat_canvas_unchanged() {
+    let flat = Canvas::filled(9, 7, Rgba::new(100, 150, 200, 90));
+    let effects: Vec<Box<dyn Effect>> = vec![
+        Box::new(GaussianBlur::new(2.0)),
+        Box::new(Fxaa::default()),
+        Box::new(Sharpen::default()),
+        Box::new(ChromaticAberration::default()),
+        Box::new(Convolution::new(3, 1, vec![1.0, 2.0, 1.0]).normalized()),
+    ];
+    for effect in &effects {
+        assert_eq!(effect.apply(&flat), flat, "{:?}", effect);
+    }
+
+    // edge detection finds nothing, and keeps alpha
+    let edges = EdgeDetect::default().apply(&flat);
+    assert!(edges.pixels().iter().all(|&c| c == Rgba::new(0, 0, 0, 90)));
+}
+
+#[test]
+fn blur_spreads_symmetrically() {
+    let mut canvas = Canvas::filled(11, 11, Rgba::new(0, 0, 0, 255));
+    canvas.set(5, 5, Rgba::new(255, 255, 255, 255));
+    let blurred = GaussianBlur::new(1.0).apply(&canvas);
+
+    let center = blurred.get(5, 5).r;
+    assert!(center < 255 && center > blurred.get(6, 5).r);
+    assert_eq!(blurred.get(4, 5), blurred.get(6, 5));
+    assert_eq!(blurred.get(5, 4), blurred.get(5, 6));
+    assert_eq!(blurred.get(4, 5), blurred.get(5, 6));
+    assert_eq!(blurred.get(0, 0).r, 0);
+}
+
+#[test]
+fn convolution_orientation() {
+    // the first kernel row is the top, so this shifts the image down by one pixel
+    let canvas = test_pattern(16);
+    let shift_down = Convolution::new(1, 3, vec![0.0, 0.0, 1.0]);
+    let shifted = shift_down.apply(&canvas);
+    for y in 0..15 {
+        for x in 0..16 {
+            assert_eq!(shifted.get(x, y + 1), canvas.get(x, y));
+        }
+    }
+}
+
+#[test]
+fn chained_effects() {
+    let canvas = test_pattern(32);
+    let chain = Vignette::default().then(Scanlines::default());
+    let expected = Scanlines::default().apply(&Vignette::default().apply(&canvas));
+    assert_eq!(chain.apply(&canvas), expected);
+
+    // scanlines darken every other row, starting from the second row from the top
+    let lines = Scanlines::default().apply(&Canvas::filled(2, 4, Rgba::broadcast(200)));
+    let rows: Vec<u8> = (0..4).map(|y| lines.get(0, y).r).collect();
+    assert_eq!(rows, vec![140, 200, 140, 200]);
+}
+
+/// Every effect chained together on a test pattern.
+#[test]
+fn post_chain() {
+    let chain = Bloom::default()
+        .then(Fxaa::default())
+        .then(Sharpen { amount: 0.3 })
+        .then(ChromaticAberration { offset: 3.0 })
+        .then(Vignette::default())
+        .then(Scanlines::default());
+    Golden::new("post_chain", 64, 64).check(&chain.apply(&test_pattern(64)));
+
+    let edges = EdgeDetect::default().then(GaussianBlur::new(0.7));
+    Golden::new("post_edges", 64, 64).check(&edges.apply(&test_pattern(64)));
+}
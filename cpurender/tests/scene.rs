@@ -0,0 +1,137 @@
+use cpurender::{
+    scene::*,
+    re::vek::{Mat4, Quaternion, Vec3},
+};
+
+use std::f32::{self, consts::{FRAC_PI_4, SQRT_2}};
+
+fn approx_eq(a: Vec3<f32>, b: Vec3<f32>) -> bool {
+    a.distance(b) < 0.0001
+}
+
+fn down_z(x: f32, y: f32) -> Ray {
+    Ray::new(Vec3::new(x, y, -10.0), Vec3::unit_z())
+}
+
+#[test]
+fn sphere() {
+    let sphere = Sphere { center: Vec3::new(0.0, 0.0, 5.0), radius: 2.0 };
+
+    let hit = sphere.intersect(down_z(0.0, 0.0), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 13.0);
+    assert!(approx_eq(hit.normal, -Vec3::unit_z()));
+
+    // from inside, hitting the far side
+    let hit = sphere.intersect(Ray::new(Vec3::new(0.0, 0.0, 5.0), Vec3::unit_x()), 10.0).unwrap();
+    assert_eq!(hit.distance, 2.0);
+    assert!(approx_eq(hit.normal, Vec3::unit_x()));
+    assert!(approx_eq(hit.facing(Vec3::unit_x()), -Vec3::unit_x()));
+
+    assert!(sphere.intersect(down_z(2.1, 0.0), f32::INFINITY).is_none());
+    assert!(sphere.intersect(down_z(0.0, 0.0), 12.0).is_none());
+    // unnormalized directions measure distance in multiples of the direction
+    let hit = sphere.intersect(Ray::new(Vec3::new(0.0, 0.0, -10.0), Vec3::unit_z() * 2.0), 100.0).unwrap();
+    assert_eq!(hit.distance, 6.5);
+}
+
+#[test]
+fn plane_and_disc() {
+    let plane = Plane { point: Vec3::new(0.0, 0.0, 3.0), normal: -Vec3::unit_z() };
+    let hit = plane.intersect(down_z(7.0, -4.0), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 13.0);
+    assert!(approx_eq(hit.point, Vec3::new(7.0, -4.0, 3.0)));
+    assert!(plane.intersect(Ray::new(Vec3::zero(), Vec3::unit_x()), f32::INFINITY).is_none());
+    assert!(plane.intersect(Ray::new(Vec3::zero(), -Vec3::unit_z()), f32::INFINITY).is_none());
+
+    let disc = Disc { center: Vec3::new(0.0, 0.0, 3.0), normal: -Vec3::unit_z(), radius: 1.0 };
+    assert!(disc.intersect(down_z(0.5, 0.5), f32::INFINITY).is_some());
+    assert!(disc.intersect(down_z(0.8, 0.8), f32::INFINITY).is_none());
+}
+
+#[test]
+fn boxes() {
+    let aabb = Aabb { min: Vec3::new(-1.0, -1.0, 2.0), max: Vec3::new(1.0, 1.0, 4.0) };
+    let hit = aabb.intersect(down_z(0.5, 0.5), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 12.0);
+    assert_eq!(hit.normal, -Vec3::unit_z());
+
+    let hit = aabb.intersect(Ray::new(Vec3::new(0.0, 0.0, 3.0), -Vec3::unit_y()), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 1.0);
+    assert_eq!(hit.normal, -Vec3::unit_y());
+    assert!(aabb.intersect(down_z(1.5, 0.0), f32::INFINITY).is_none());
+
+    // rotated 45 degrees about z, so one of its corners points along +x
+    let obb = Obb {
+        center: Vec3::zero(),
+        half_extents: Vec3::broadcast(1.0),
+        orientation: Quaternion::rotation_z(FRAC_PI_4),
+    };
+    let ray = Ray::new(Vec3::new(3.0, 0.2, 0.0), -Vec3::unit_x());
+    let hit = obb.intersect(ray, f32::INFINITY).unwrap();
+    assert!((hit.distance - (3.0 - (SQRT_2 - 0.2))).abs() < 0.0001);
+    assert!(approx_eq(hit.normal, Vec3::new(1.0, 1.0, 0.0).normalized()));
+}
+
+#[test]
+fn triangle() {
+    let triangle = Triangle {
+        a: Vec3::new(0.0, 0.0, 1.0),
+        b: Vec3::new(0.0, 1.0, 1.0),
+        c: Vec3::new(1.0, 0.0, 1.0),
+    };
+    let hit = triangle.intersect(down_z(0.25, 0.25), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 11.0);
+    assert!(approx_eq(hit.normal, -Vec3::unit_z()));
+    assert!(triangle.intersect(down_z(0.6, 0.6), f32::INFINITY).is_none());
+    assert!(triangle.intersect(down_z(-0.1, 0.5), f32::INFINITY).is_none());
+
+    // hit from behind
+    let hit = triangle.intersect(Ray::new(Vec3::new(0.25, 0.25, 5.0), -Vec3::unit_z()), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 4.0);
+}
+
+#[test]
+fn cylinder() {
+    let cylinder = Cylinder { a: Vec3::new(0.0, -1.0, 0.0), b: Vec3::new(0.0, 1.0, 0.0), radius: 0.5 };
+
+    let hit = cylinder.intersect(down_z(0.0, 0.0), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 9.5);
+    assert!(approx_eq(hit.normal, -Vec3::unit_z()));
+    assert!(cylinder.intersect(down_z(0.0, 1.1), f32::INFINITY).is_none());
+
+    let hit = cylinder.intersect(Ray::new(Vec3::new(0.2, 5.0, 0.0), -Vec3::unit_y()), f32::INFINITY).unwrap();
+    assert_eq!(hit.distance, 4.0);
+    assert!(approx_eq(hit.normal, Vec3::unit_y()));
+}
+
+#[test]
+fn transformed() {
+    let sphere = Sphere { center: Vec3::zero(), radius: 1.0 }
+        .transformed(Mat4::<f32>::translation_3d(Vec3::new(0.0, 0.0, 5.0)) * Mat4::scaling_3d(Vec3::new(2.0, 1.0, 1.0)));
+
+    let hit = sphere.intersect(down_z(0.0, 0.0), f32::INFINITY).unwrap();
+    assert!((hit.distance - 14.0).abs() < 0.0001);
+    assert!(approx_eq(hit.point, Vec3::new(0.0, 0.0, 4.0)));
+    assert!(approx_eq(hit.normal, -Vec3::unit_z()));
+
+    let hit = sphere.intersect(Ray::new(Vec3::new(-10.0, 0.0, 5.0), Vec3::unit_x()), f32::INFINITY).unwrap();
+    assert!((hit.distance - 8.0).abs() < 0.0001);
+    assert!(approx_eq(hit.normal, -Vec3::unit_x()));
+}
+
+#[test]
+fn scene_closest() {
+    let scene = Scene::new()
+        .with(Sphere { center: Vec3::new(0.0, 0.0, 10.0), radius: 1.0 }, "far")
+        .with(Sphere { center: Vec3::new(0.0, 0.0, 5.0), radius: 1.0 }, "near")
+        .with(Plane { point: Vec3::new(0.0, 0.0, 20.0), normal: -Vec3::unit_z() }, "wall");
+
+    let (hit, &material) = scene.hit(down_z(0.0, 0.0), f32::INFINITY).unwrap();
+    assert_eq!(material, "near");
+    assert_eq!(hit.distance, 14.0);
+
+    let (_, &material) = scene.hit(down_z(3.0, 0.0), f32::INFINITY).unwrap();
+    assert_eq!(material, "wall");
+    assert!(!scene.occludes(down_z(3.0, 0.0), 10.0));
+    assert!(scene.occludes(down_z(0.0, 0.0), 15.0));
+}
@@ -0,0 +1,106 @@
+use cpurender::{
+    scene::Ray,
+    sdf::*,
+    re::vek::{Quaternion, Vec3},
+};
+
+use std::f32::consts::FRAC_PI_2;
+
+fn approx(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.001
+}
+
+#[test]
+fn primitives() {
+    assert!(approx(sphere(Vec3::new(3.0, 0.0, 0.0), 1.0), 2.0));
+    assert!(approx(sphere(Vec3::zero(), 1.0), -1.0));
+
+    let half = Vec3::new(1.0, 2.0, 3.0);
+    assert!(approx(cuboid(Vec3::new(2.0, 0.0, 0.0), half), 1.0));
+    assert!(approx(cuboid(Vec3::new(2.0, 3.0, 0.0), half), 2f32.sqrt()));
+    assert!(approx(cuboid(Vec3::zero(), half), -1.0));
+    assert!(approx(round_cuboid(Vec3::new(2.0, 0.0, 0.0), half, 0.5), 1.0));
+
+    assert!(approx(plane(Vec3::new(5.0, 2.0, 1.0), Vec3::unit_y()), 2.0));
+    assert!(approx(torus(Vec3::new(3.0, 0.0, 0.0), 2.0, 0.5), 0.5));
+    assert!(approx(torus(Vec3::new(0.0, 1.0, 0.0), 2.0, 0.5), 5f32.sqrt() - 0.5));
+
+    let (a, b) = (Vec3::zero(), Vec3::new(0.0, 4.0, 0.0));
+    assert!(approx(capsule(Vec3::new(1.0, 2.0, 0.0), a, b, 0.5), 0.5));
+    assert!(approx(capsule(Vec3::new(0.0, 6.0, 0.0), a, b, 0.5), 1.5));
+
+    assert!(approx(cylinder(Vec3::new(2.0, 0.0, 0.0), 1.0, 1.0), 1.0));
+    assert!(approx(cylinder(Vec3::new(0.0, 3.0, 0.0), 1.0, 1.0), 2.0));
+    assert!(approx(cylinder(Vec3::zero(), 1.0, 0.5), -0.5));
+}
+
+#[test]
+fn combining() {
+    assert_eq!(union(1.0, 2.0), 1.0);
+    assert_eq!(intersection(1.0, 2.0), 2.0);
+    assert_eq!(subtraction(-1.0, -0.5), 0.5);
+
+    // smooth variants match the sharp ones away from the blend region, and blend inside
+    assert!(approx(smooth_union(1.0, 3.0, 0.5), 1.0));
+    assert!(smooth_union(1.0, 1.0, 0.5) < 1.0);
+    assert!(approx(smooth_intersection(1.0, 3.0, 0.5), 3.0));
+    assert!(smooth_intersection(1.0, 1.0, 0.5) > 1.0);
+    assert!(approx(smooth_subtraction(1.0, -3.0, 0.5), 3.0));
+}
+
+#[test]
+fn domain() {
+    let p = Vec3::new(1.0, 2.0, 3.0);
+    assert_eq!(translate(p, Vec3::one()), Vec3::new(0.0, 1.0, 2.0));
+    let rotated = rotate(Vec3::unit_x(), Quaternion::rotation_y(FRAC_PI_2));
+    assert!(rotated.distance(Vec3::unit_z()) < 0.001);
+    assert!(approx(scale(Vec3::new(6.0, 0.0, 0.0), 2.0, |p| sphere(p, 1.0)), 4.0));
+    assert_eq!(mirror(-p, Vec3::new(true, false, true)), Vec3::new(1.0, -2.0, 3.0));
+
+    let period = Vec3::new(4.0, 0.0, 4.0);
+    assert_eq!(repeat(Vec3::new(9.0, 9.0, -7.0), period), Vec3::new(1.0, 9.0, 1.0));
+    let limit = Vec3::new(1.0, 1.0, 1.0);
+    assert_eq!(repeat_limited(Vec3::new(9.0, 0.0, 1.0), Vec3::broadcast(4.0), limit), Vec3::new(5.0, 0.0, 1.0));
+}
+
+fn scene(p: Vec3<f32>) -> f32 {
+    union(
+        plane(p, Vec3::unit_y()),
+        sphere(translate(p, Vec3::new(0.0, 1.0, 0.0)), 1.0),
+    )
+}
+
+#[test]
+fn tracing() {
+    let tracer = SphereTracer::default();
+
+    let hit = tracer.trace(scene, Ray::new(Vec3::new(0.0, 1.0, -5.0), Vec3::unit_z())).unwrap();
+    assert!(approx(hit.distance, 4.0));
+    assert!(hit.normal.distance(-Vec3::unit_z()) < 0.01);
+
+    // unnormalized directions measure distance in multiples of the direction
+    let hit = tracer.trace(scene, Ray::new(Vec3::new(5.0, 3.0, 0.0), Vec3::new(0.0, -2.0, 0.0))).unwrap();
+    assert!(approx(hit.distance, 1.5));
+    assert!(hit.normal.distance(Vec3::unit_y()) < 0.01);
+
+    assert!(tracer.trace(scene, Ray::new(Vec3::new(0.0, 5.0, 0.0), Vec3::unit_y())).is_none());
+}
+
+#[test]
+fn shading() {
+    let up = Vec3::unit_y();
+
+    // lit in the open, shadowed under the sphere, partially at the shadow's edge
+    let open = soft_shadow(scene, Ray::new(Vec3::new(5.0, 0.0, 0.0), up), 0.01, 20.0, 8.0);
+    let under = soft_shadow(scene, Ray::new(Vec3::new(0.0, 0.0, 0.0), up), 0.01, 20.0, 8.0);
+    let edge = soft_shadow(scene, Ray::new(Vec3::new(1.05, 0.0, 0.0), up), 0.01, 20.0, 8.0);
+    assert!(approx(open, 1.0));
+    assert!(approx(under, 0.0));
+    assert!(edge > 0.0 && edge < 1.0);
+
+    // the floor is occluded more near the sphere
+    let far = ambient_occlusion(scene, Vec3::new(5.0, 0.0, 0.0), up, 5, 0.1);
+    let near = ambient_occlusion(scene, Vec3::new(0.5, 0.0, 0.0), up, 5, 0.1);
+    assert!(approx(far, 1.0));
+    assert!(near < far);
+}
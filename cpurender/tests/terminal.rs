@@ -0,0 +1,90 @@
+use cpurender::{
+    canvas::Canvas,
+    frag::render,
+    re::vek::{Rgba, Vec2},
+    terminal::*,
+};
+
+const TERM: Vec2<usize> = Vec2 { x: 80, y: 24 };
+
+/// Number of cells drawn by some escapes.
+fn cells(escapes: &str) -> usize {
+    escapes.matches('▀').count()
+}
+
+#[test]
+fn downscaling() {
+    // each output pixel averages a 2x2 block, from the bottom-left
+    let canvas = render(4, 6, |xy| {
+        Rgba::new(xy.x as u8 * 60, xy.y as u8 * 40, 0, 255)
+    });
+    let small = downscale(&canvas, Vec2::new(2, 3));
+    assert_eq!(small.size(), Vec2::new(2, 3));
+    assert_eq!(small.get(0, 0), Rgba::new(30, 20, 0, 255));
+    assert_eq!(small.get(1, 0), Rgba::new(150, 20, 0, 255));
+    assert_eq!(small.get(1, 2), Rgba::new(150, 180, 0, 255));
+
+    // uneven blocks cover every pixel at least once
+    let small = downscale(&canvas, Vec2::new(3, 4));
+    assert_eq!(small.get(0, 0), Rgba::new(0, 0, 0, 255));
+    assert_eq!(small.get(2, 3), Rgba::new(150, 180, 0, 255));
+
+    // transparency is composited over black
+    let translucent = Canvas::filled(2, 2, Rgba::new(255, 100, 0, 128));
+    let small = downscale(&translucent, Vec2::new(1, 1));
+    assert_eq!(small.get(0, 0), Rgba::new(128, 50, 0, 255));
+
+    // and an empty canvas is all black
+    let small = downscale(&Canvas::new(0, 5), Vec2::new(2, 1));
+    assert_eq!(small, Canvas::filled(2, 1, Rgba::black()));
+}
+
+#[test]
+fn half_blocks() {
+    // an odd height leaves the bottom half of the last row black
+    let colors = [
+        Rgba::new(255, 0, 0, 255),
+        Rgba::new(0, 255, 0, 255),
+        Rgba::new(0, 0, 255, 255),
+    ];
+    let canvas = render(1, 3, |xy| colors[xy.y as usize]);
+    let escapes = Presenter::new().draw(&canvas, TERM);
+    assert_eq!(escapes, concat!(
+        "\x1b[0m\x1b[2J",
+        "\x1b[1;1H\x1b[38;2;0;0;255m\x1b[48;2;0;255;0m▀",
+        "\x1b[2;1H\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m▀",
+    ));
+
+    // shrunk to fit, leaving the last line free
+    let mut presenter = Presenter::new();
+    let canvas = Canvas::filled(40, 20, Rgba::white());
+    assert_eq!(cells(&presenter.draw(&canvas, Vec2::new(20, 6))), 20 * 5);
+    assert_eq!(presenter.end(), "\x1b[0m\x1b[6;1H\x1b[?25h");
+}
+
+#[test]
+fn only_changes_are_drawn() {
+    let mut presenter = Presenter::new();
+    let mut canvas = Canvas::filled(8, 8, Rgba::new(10, 20, 30, 255));
+    assert_eq!(cells(&presenter.draw(&canvas, TERM)), 8 * 4);
+
+    // an unchanged frame draws nothing
+    assert_eq!(presenter.draw(&canvas, TERM), "");
+
+    // a changed pixel redraws its cell, moving the cursor there
+    canvas.set(3, 0, Rgba::white());
+    let escapes = presenter.draw(&canvas, TERM);
+    assert_eq!(cells(&escapes), 1);
+    assert!(escapes.starts_with("\x1b[4;4H"));
+    assert_eq!(presenter.draw(&canvas, TERM), "");
+
+    // as does anything when the terminal is resized
+    assert_eq!(cells(&presenter.draw(&canvas, Vec2::new(100, 30))), 8 * 4);
+
+    // or to nothing, when the canvas is empty
+    let empty = Canvas::new(0, 8);
+    assert_eq!(presenter.draw(&empty, Vec2::new(100, 30)), "\x1b[0m\x1b[2J");
+    assert_eq!(presenter.draw(&empty, Vec2::new(100, 30)), "");
+    assert_eq!(presenter.end(), "\x1b[0m\x1b[1;1H\x1b[?25h");
+    assert_eq!(Presenter::new().draw(&Canvas::new(3, 0), TERM), "\x1b[0m\x1b[2J");
+}
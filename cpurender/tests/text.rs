@@ -0,0 +1,184 @@
+use cpurender::{
+    re::vek::Vec2,
+    text::*,
+};
+
+/// A PSF1 font of two-row glyphs, where glyph 65 is drawn, with an optional unicode table.
+fn psf1(table: Option<&[u16]>) -> Vec<u8> {
+    let mode = if table.is_some() { 0x02 } else { 0x00 };
+    let mut bytes = vec![0x36, 0x04, mode, 2];
+    for i in 0..256 {
+        bytes.extend_from_slice(if i == 65 { &[0x80, 0x01] } else { &[0x00, 0x00] });
+    }
+    for unit in table.unwrap_or(&[]) {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    bytes
+}
+
+/// A PSF2 header, followed by glyph data.
+fn psf2(count: u32, glyph_len: u32, height: u32, width: u32, glyphs: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x72, 0xB5, 0x4A, 0x86];
+    for field in &[0, 32, 1, count, glyph_len, height, width] {
+        bytes.extend_from_slice(&field.to_le_bytes());
+    }
+    bytes.extend_from_slice(glyphs);
+    bytes
+}
+
+fn assert_malformed(result: Result<Font, FontError>, msg: &str) {
+    match result {
+        Err(FontError::Malformed(e)) => assert!(e.contains(msg), "{:?} is not {:?}", e, msg),
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(_) => panic!("no error, expected {:?}", msg),
+    }
+}
+
+#[test]
+fn psf1_fonts() {
+    // without a table, glyphs are in code point order
+    let font = Font::from_psf(&psf1(None)).unwrap();
+    assert_eq!((font.ascent, font.descent), (2, 0));
+    let glyph = font.glyph('A').unwrap();
+    assert_eq!(glyph.size, Vec2::new(8, 2));
+    assert_eq!(glyph.advance, 8);
+    assert!(glyph.bit(0, 0) && !glyph.bit(1, 0));
+    assert!(glyph.bit(7, 1) && !glyph.bit(0, 1));
+    assert!(!font.glyph('\u{1}').unwrap().bit(0, 0));
+
+    // with a table, glyphs are mapped by it, ignoring combining sequences
+    let mut table = vec![0x263A, 0xFFFF];
+    table.extend_from_slice(&[0x00C5, 0xFFFE, 0x0041, 0x030A, 0xFFFF]);
+    table.extend(std::iter::repeat_n(0xFFFF, 63));
+    table.extend_from_slice(&[0x0391, 0x0041, 0xFFFF]);
+    table.extend(std::iter::repeat_n(0xFFFF, 190));
+    let font = Font::from_psf(&psf1(Some(&table))).unwrap();
+    assert!(font.glyph('A').unwrap().bit(0, 0));
+    assert!(font.glyph('\u{391}').unwrap().bit(7, 1));
+    assert!(!font.glyph('\u{C5}').unwrap().bit(0, 0));
+    assert!(font.glyph('\u{263A}').is_some());
+    assert!(font.glyph('\u{1}').is_none());
+
+    table.truncate(100);
+    assert_malformed(Font::from_psf(&psf1(Some(&table))), "truncated unicode table");
+}
+
+#[test]
+fn psf2_fonts() {
+    // two glyphs, 10 pixels wide so rows take 2 bytes
+    let glyphs = [
+        0xC0, 0x40, 0x00, 0x00, 0x80, 0x00,
+        0x00, 0x00, 0xFF, 0xC0, 0x00, 0x00,
+    ];
+    let mut bytes = psf2(2, 6, 3, 10, &glyphs);
+    bytes.extend_from_slice("é".as_bytes());
+    bytes.extend_from_slice(&[0xFE, b'e', 0xCC, 0x81, 0xFF]);
+    bytes.extend_from_slice(b"-\xE2\x80\x94\xFF");
+    let font = Font::from_psf(&bytes).unwrap();
+    assert_eq!((font.ascent, font.descent), (3, 0));
+
+    let glyph = font.glyph('é').unwrap();
+    assert_eq!(glyph.size, Vec2::new(10, 3));
+    assert_eq!(glyph.advance, 10);
+    assert!(glyph.bit(0, 0) && glyph.bit(1, 0) && glyph.bit(9, 0) && !glyph.bit(8, 0));
+    assert!(glyph.bit(0, 2) && !glyph.bit(0, 1));
+    assert!((0..10).all(|x| font.glyph('—').unwrap().bit(x, 1)));
+    assert!(font.glyph('-').is_some());
+    assert!(font.glyph('e').is_none());
+}
+
+#[test]
+fn malformed_psf() {
+    assert_malformed(Font::from_psf(&[0x36, 0x04, 0x00]), "truncated header");
+    assert_malformed(Font::from_psf(&[0x72, 0xB5, 0x4A, 0x86, 0, 0]), "truncated header");
+    assert_malformed(Font::from_psf(&[0x12, 0x34, 0x56, 0x78]), "magic");
+    assert_malformed(Font::from_psf(&psf2(1, 2, 1, 0, &[0; 2])), "bad glyph dimensions");
+    assert_malformed(Font::from_psf(&psf2(1, 1, 1, 10, &[0; 1])), "bad glyph dimensions");
+    assert_malformed(Font::from_psf(&psf2(4, 2, 1, 10, &[0; 7])), "truncated glyph data");
+    assert_malformed(Font::from_psf(&psf1(None)[..300]), "truncated glyph data");
+}
+
+const BDF: &str = "\
+STARTFONT 2.1
+FONT -test-fixture
+SIZE 8 75 75
+FONTBOUNDINGBOX 4 6 0 -2
+STARTPROPERTIES 2
+FONT_ASCENT 5
+FONT_DESCENT 2
+ENDPROPERTIES
+CHARS 3
+STARTCHAR A
+ENCODING 65
+SWIDTH 500 0
+DWIDTH 5 0
+BITMAP
+60
+90
+F0
+90
+90
+00
+ENDCHAR
+STARTCHAR period
+ENCODING 46
+BBX 1 1 1 0
+BITMAP
+80
+ENDCHAR
+STARTCHAR unencoded
+ENCODING -1
+BITMAP
+F0
+F0
+F0
+F0
+F0
+F0
+ENDCHAR
+ENDFONT
+";
+
+#[test]
+fn bdf_fonts() {
+    let font = Font::from_bdf(BDF).unwrap();
+    assert_eq!((font.ascent, font.descent), (5, 2));
+
+    // the font bounding box is the default for each glyph
+    let a = font.glyph('A').unwrap();
+    assert_eq!(a.size, Vec2::new(4, 6));
+    assert_eq!(a.offset, Vec2::new(0, -2));
+    assert_eq!(a.advance, 5);
+    let row = |y| (0..4).map(|x| a.bit(x, y)).collect::<Vec<bool>>();
+    assert_eq!(row(0), vec![false, true, true, false]);
+    assert_eq!(row(2), vec![true, true, true, true]);
+    assert_eq!(row(5), vec![false; 4]);
+
+    // which its own can override, and the advance defaults to its width
+    let period = font.glyph('.').unwrap();
+    assert_eq!(period.size, Vec2::new(1, 1));
+    assert_eq!(period.offset, Vec2::new(1, 0));
+    assert_eq!(period.advance, 1);
+    assert!(period.bit(0, 0));
+    assert!(font.glyph('B').is_none());
+
+    // without properties, the ascent and descent come from the bounding box
+    let bare = BDF.replace("FONT_ASCENT 5\n", "");
+    let font = Font::from_bdf(&bare).unwrap();
+    assert_eq!((font.ascent, font.descent), (4, 2));
+}
+
+#[test]
+fn malformed_bdf() {
+    assert_malformed(Font::from_bdf(&BDF.replace("\n90\nF0", "\n9Z\nF0")), "bad bitmap row");
+    let too_wide = BDF.replace("80", "1234567890ABCDEF1234567890ABCDEF0");
+    assert_malformed(Font::from_bdf(&too_wide), "bad bitmap row");
+    assert_malformed(Font::from_bdf(&BDF.replace("BBX 1 1 1 0", "BBX 1 x 1 0")), "bad number");
+    assert_malformed(Font::from_bdf(&BDF[..BDF.find("ENDCHAR").unwrap()]), "unterminated");
+
+    let unbounded = BDF.replace("FONTBOUNDINGBOX 4 6 0 -2\n", "");
+    assert_malformed(Font::from_bdf(&unbounded), "bitmap without bounding box");
+    let unbounded = unbounded.replace("FONT_ASCENT 5\n", "");
+    let unbounded = &unbounded[..unbounded.find("STARTCHAR").unwrap()];
+    assert_malformed(Font::from_bdf(unbounded), "missing font bounding box");
+}
@@ -0,0 +1,100 @@
+use cpurender::{
+    re::{
+        image::{self, RgbaImage},
+        vek::{Rgba, Vec2},
+    },
+    texture::*,
+};
+
+/// A texture from a function of texel coordinates to gray levels.
+fn gray(width: u32, height: u32, f: impl Fn(u32, u32) -> u8) -> Texture {
+    Texture::from_image(RgbaImage::from_fn(width, height, |x, y| {
+        let c = f(x, y);
+        image::Rgba([c, c, c, 255])
+    }))
+}
+
+fn assert_gray(color: Rgba<f32>, expected: f32) {
+    assert!((color.r - expected).abs() < 0.002, "{:?} is not {}", color, expected);
+    assert_eq!(color.a, 1.0);
+}
+
+#[test]
+fn wrap_modes() {
+    assert_eq!(Wrap::Repeat.apply(4, 4), 0);
+    assert_eq!(Wrap::Repeat.apply(-1, 4), 3);
+    assert_eq!(Wrap::Repeat.apply(-5, 4), 3);
+    assert_eq!(Wrap::Repeat.apply(-8, 4), 0);
+
+    assert_eq!(Wrap::Clamp.apply(-3, 4), 0);
+    assert_eq!(Wrap::Clamp.apply(9, 4), 3);
+
+    // every other tile is flipped, so the edge texels repeat across tile boundaries
+    assert_eq!(Wrap::Mirror.apply(-1, 4), 0);
+    assert_eq!(Wrap::Mirror.apply(-4, 4), 3);
+    assert_eq!(Wrap::Mirror.apply(-5, 4), 3);
+    assert_eq!(Wrap::Mirror.apply(4, 4), 3);
+    assert_eq!(Wrap::Mirror.apply(7, 4), 0);
+    assert_eq!(Wrap::Mirror.apply(8, 4), 0);
+
+    let texture = gray(2, 1, |x, _| x as u8 * 255).with_wrap(Wrap::Mirror);
+    assert_eq!(texture.texel(Vec2::new(-1, 5)), Rgba::new(0, 0, 0, 255));
+    assert_eq!(texture.texel(Vec2::new(-3, 0)), Rgba::new(255, 255, 255, 255));
+}
+
+#[test]
+fn nearest_and_bilinear() {
+    // black on the left, white on the right
+    let texture = gray(2, 1, |x, _| x as u8 * 255);
+
+    let nearest = texture.clone().with_filter(Filter::Nearest);
+    assert_gray(nearest.sample(Vec2::new(0.3, 0.5)), 0.0);
+    assert_gray(nearest.sample(Vec2::new(0.6, 0.5)), 1.0);
+    assert_gray(nearest.sample(Vec2::new(-0.2, 0.5)), 1.0);
+
+    // exact at texel centers, blended between them
+    assert_gray(texture.sample(Vec2::new(0.25, 0.5)), 0.0);
+    assert_gray(texture.sample(Vec2::new(0.75, 0.5)), 1.0);
+    assert_gray(texture.sample(Vec2::new(0.5, 0.5)), 0.5);
+    assert_gray(texture.sample(Vec2::new(0.375, 0.5)), 0.25);
+
+    // across the edge, blending with the wrapped texel
+    assert_gray(texture.sample(Vec2::new(0.0, 0.5)), 0.5);
+    let clamped = texture.with_wrap(Wrap::Clamp);
+    assert_gray(clamped.sample(Vec2::new(0.0, 0.5)), 0.0);
+
+    // coordinates beyond the range of texel indices don't overflow
+    assert_gray(clamped.sample(Vec2::new(1e30, 0.5)), 1.0);
+    assert_gray(clamped.sample(Vec2::new(-1e30, 1e30)), 0.0);
+    for &c in &[f32::INFINITY, f32::NEG_INFINITY, f32::NAN] {
+        clamped.sample(Vec2::new(c, c));
+        nearest.sample(Vec2::new(c, 0.5));
+    }
+}
+
+#[test]
+fn mip_levels() {
+    // a checkerboard, which averages to gray
+    let texture = gray(4, 4, |x, y| if (x + y) % 2 == 0 { 0 } else { 255 });
+    assert_eq!(texture.mip_levels(), 3);
+    assert_eq!(texture.texel_level(Vec2::new(1, 0), 1), Rgba::new(128, 128, 128, 255));
+    let average = 128.0 / 255.0;
+
+    // the level of detail is ignored unless filtering is trilinear
+    let center = Vec2::broadcast(0.125);
+    assert_gray(texture.sample_lod(center, 1.0), 0.0);
+
+    let texture = texture.with_filter(Filter::Trilinear);
+    assert_gray(texture.sample_lod(center, 0.0), 0.0);
+    assert_gray(texture.sample_lod(center, -1.0), 0.0);
+    assert_gray(texture.sample_lod(center, 0.5), average / 2.0);
+    assert_gray(texture.sample_lod(center, 1.0), average);
+    assert_gray(texture.sample_lod(center, 10.0), average);
+
+    // a texel per pixel is the base level, and two is the next
+    let along = |d: f32| Vec2::new(d, 0.0);
+    assert_gray(texture.sample_grad(center, along(0.25), along(0.0)), 0.0);
+    assert_gray(texture.sample_grad(center, along(0.0), Vec2::new(0.0, 0.5)), average);
+    assert_gray(texture.sample_grad(center, along(0.25 * 2f32.sqrt()), along(0.0)), average / 2.0);
+    assert_gray(texture.sample_grad(center, along(0.0), along(0.0)), 0.0);
+}
@@ -0,0 +1,83 @@
+use cpurender::timestep::{Accumulator, FixedTimestep};
+
+use std::time::Duration;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn accumulates_fractional_ticks() {
+    let mut accumulator = Accumulator::new(FixedTimestep {
+        tick: ms(10),
+        max_ticks_per_frame: 8,
+    });
+
+    assert_eq!(accumulator.advance(ms(4)), 0);
+    assert!((accumulator.alpha() - 0.4).abs() < 1e-6);
+    assert_eq!(accumulator.advance(ms(7)), 1);
+    assert!((accumulator.alpha() - 0.1).abs() < 1e-6);
+    assert_eq!(accumulator.advance(ms(29)), 3);
+    assert_eq!(accumulator.alpha(), 0.0);
+    assert_eq!(accumulator.ticks(), 4);
+    assert_eq!(accumulator.dropped(), Duration::ZERO);
+}
+
+#[test]
+fn drops_ticks_when_behind() {
+    let mut accumulator = Accumulator::new(FixedTimestep {
+        tick: ms(10),
+        max_ticks_per_frame: 3,
+    });
+
+    assert_eq!(accumulator.advance(ms(55)), 3);
+    assert_eq!(accumulator.dropped(), ms(20));
+    assert!((accumulator.alpha() - 0.5).abs() < 1e-6);
+    assert_eq!(accumulator.advance(ms(5)), 1);
+    assert_eq!(accumulator.ticks(), 4);
+
+    // a long stall with a short tick is dropped all at once
+    let mut accumulator = Accumulator::new(FixedTimestep {
+        tick: Duration::from_nanos(3),
+        max_ticks_per_frame: 8,
+    });
+    let stall = Duration::from_secs(60 * 60 * 24 * 365) + Duration::from_nanos(25);
+    assert_eq!(accumulator.advance(stall), 8);
+    assert_eq!(accumulator.dropped(), stall - Duration::from_nanos(25));
+    assert!((accumulator.alpha() - 1.0 / 3.0).abs() < 1e-6);
+}
+
+#[test]
+fn same_total_time_same_ticks() {
+    // however real time is split into frames, the same ticks are run if none are dropped
+    let timestep = FixedTimestep::per_second(60);
+    let frames = [
+        vec![ms(16); 60],
+        vec![ms(33); 29],
+        (0..80).map(|i| ms(i % 7 * 3)).collect(),
+    ];
+    for frame_times in &frames {
+        let mut accumulator = Accumulator::new(timestep);
+        let ticks: u64 = frame_times.iter()
+            .map(|&elapsed| accumulator.advance(elapsed) as u64)
+            .sum();
+        let total: Duration = frame_times.iter().sum();
+        assert_eq!(ticks, (total.as_nanos() / timestep.tick.as_nanos()) as u64);
+        assert_eq!(ticks, accumulator.ticks());
+    }
+}
+
+#[test]
+#[should_panic(expected = "nonzero number of ticks")]
+fn zero_ticks_per_second() {
+    FixedTimestep::per_second(0);
+}
+
+#[test]
+#[should_panic(expected = "tick must be nonzero")]
+fn zero_tick() {
+    Accumulator::new(FixedTimestep {
+        tick: Duration::ZERO,
+        ..FixedTimestep::default()
+    });
+}
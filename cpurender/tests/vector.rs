@@ -0,0 +1,201 @@
+use cpurender::{
+    canvas::Canvas,
+    golden::Golden,
+    re::vek::{Rgba, Vec2},
+    vector::*,
+};
+
+use std::f32::consts::PI;
+
+const WHITE: Rgba<u8> = Rgba { r: 255, g: 255, b: 255, a: 255 };
+
+fn filled(size: usize, path: &Path, rule: FillRule) -> Canvas {
+    let mut canvas = Canvas::new(size, size);
+    fill(&mut canvas, path, rule, &Brush::Solid(WHITE));
+    canvas
+}
+
+fn stroked(size: usize, path: &Path, stroke: &Stroke) -> Canvas {
+    let mut canvas = Canvas::new(size, size);
+    cpurender::vector::stroke(&mut canvas, path, stroke, &Brush::Solid(WHITE));
+    canvas
+}
+
+/// Total coverage, in square pixels.
+fn area(canvas: &Canvas) -> f32 {
+    canvas.pixels().iter().map(|c| c.a as f32 / 255.0).sum()
+}
+
+fn assert_area(canvas: &Canvas, expected: f32) {
+    let area = area(canvas);
+    assert!((area - expected).abs() < expected * 0.005 + 0.05, "area {} != {}", area, expected);
+}
+
+#[test]
+fn exact_coverage() {
+    let rect = Path::rect(Vec2::new(1.5, 1.25), Vec2::new(3.0, 2.5));
+    let canvas = filled(6, &rect, FillRule::NonZero);
+    let alpha = |x, y| canvas.get(x, y).a;
+    assert_eq!(alpha(0, 2), 0);
+    assert_eq!(alpha(2, 2), 255);
+    assert_eq!(alpha(1, 2), 128);
+    assert_eq!(alpha(4, 2), 128);
+    assert_eq!(alpha(2, 1), 191);
+    assert_eq!(alpha(2, 3), 191);
+    assert_eq!(alpha(1, 1), 96);
+    assert_eq!(alpha(4, 3), 96);
+    assert_area(&canvas, 7.5);
+
+    // shapes partly off the canvas are clipped, not distorted
+    let big = Path::rect(Vec2::new(-10.0, -10.0), Vec2::new(13.5, 20.0));
+    let canvas = filled(6, &big, FillRule::NonZero);
+    assert_eq!(canvas.get(0, 0).a, 255);
+    assert_eq!(canvas.get(3, 5).a, 128);
+    assert_eq!(canvas.get(4, 5).a, 0);
+}
+
+#[test]
+fn fill_rules() {
+    // a square inside another, wound the same way
+    let path = Path::rect(Vec2::new(2.0, 2.0), Vec2::new(12.0, 12.0))
+        .move_to(Vec2::new(5.0, 5.0))
+        .line_to(Vec2::new(11.0, 5.0))
+        .line_to(Vec2::new(11.0, 11.0))
+        .line_to(Vec2::new(5.0, 11.0))
+        .close();
+    assert_area(&filled(16, &path, FillRule::NonZero), 144.0);
+    assert_area(&filled(16, &path, FillRule::EvenOdd), 144.0 - 36.0);
+    assert_eq!(filled(16, &path, FillRule::EvenOdd).get(8, 8).a, 0);
+
+    // unclosed subpaths are filled as if closed
+    let triangle = Path::new()
+        .move_to(Vec2::new(1.0, 1.0))
+        .line_to(Vec2::new(9.0, 1.0))
+        .line_to(Vec2::new(1.0, 9.0));
+    assert_area(&filled(16, &triangle, FillRule::NonZero), 32.0);
+}
+
+#[test]
+fn curves_and_arcs() {
+    let circle = Path::circle(Vec2::new(16.0, 16.0), 10.0);
+    assert_area(&filled(32, &circle, FillRule::NonZero), PI * 100.0);
+
+    // the area under a parabola is two thirds of its bounding box
+    let parabola = Path::new()
+        .move_to(Vec2::new(2.0, 2.0))
+        .quad_to(Vec2::new(12.0, 22.0), Vec2::new(22.0, 2.0))
+        .close();
+    assert_area(&filled(32, &parabola, FillRule::NonZero), 2.0 / 3.0 * 20.0 * 10.0);
+
+    // a cubic with its controls a third of the way along is a straight line
+    let cubic = Path::new()
+        .move_to(Vec2::new(2.0, 2.0))
+        .cubic_to(Vec2::new(8.0, 2.0), Vec2::new(14.0, 2.0), Vec2::new(20.0, 2.0))
+        .cubic_to(Vec2::new(20.0, 8.0), Vec2::new(20.0, 14.0), Vec2::new(20.0, 20.0))
+        .close();
+    assert_area(&filled(32, &cubic, FillRule::NonZero), 18.0 * 18.0 / 2.0);
+
+    // a half ellipse, with radii scaled up to reach, and rotated a quarter turn
+    let half_ellipse = Path::new()
+        .move_to(Vec2::new(16.0, 4.0))
+        .arc_to(Vec2::new(2.0, 1.0), PI / 2.0, false, true, Vec2::new(16.0, 28.0))
+        .close();
+    let canvas = filled(32, &half_ellipse, FillRule::NonZero);
+    assert_area(&canvas, PI * 12.0 * 6.0 / 2.0);
+    assert_eq!(canvas.get(12, 16).a, 0);
+    assert_eq!(canvas.get(20, 16).a, 255);
+}
+
+#[test]
+fn strokes() {
+    let line = Path::new()
+        .move_to(Vec2::new(5.0, 8.0))
+        .line_to(Vec2::new(15.0, 8.0));
+    let stroke = Stroke::new(2.0);
+    assert_area(&stroked(20, &line, &stroke), 20.0);
+    assert_area(&stroked(20, &line, &stroke.with_cap(Cap::Square)), 24.0);
+    assert_area(&stroked(20, &line, &stroke.with_cap(Cap::Round)), 20.0 + PI);
+
+    // a right angle, where the segments overlap in a square inside the corner
+    let corner = Path::new()
+        .move_to(Vec2::new(5.0, 5.0))
+        .line_to(Vec2::new(15.0, 5.0))
+        .line_to(Vec2::new(15.0, 15.0));
+    assert_area(&stroked(20, &corner, &stroke), 40.0);
+    assert_area(&stroked(20, &corner, &stroke.with_miter_limit(1.0)), 39.5);
+    assert_area(&stroked(20, &corner, &stroke.with_join(Join::Bevel)), 39.5);
+    assert_area(&stroked(20, &corner, &stroke.with_join(Join::Round)), 39.0 + PI / 4.0);
+
+    // closed subpaths are joined all the way around, with no caps
+    let square = Path::rect(Vec2::new(4.0, 4.0), Vec2::new(10.0, 10.0));
+    let outline = stroked(20, &square, &stroke.with_cap(Cap::Round));
+    assert_area(&outline, 12.0 * 12.0 - 8.0 * 8.0);
+    assert_eq!(outline.get(3, 3).a, 255);
+    assert_eq!(outline.get(9, 9).a, 0);
+}
+
+#[test]
+fn gradients() {
+    let gradient = Gradient::new(&[
+        (1.0, Rgba::new(0, 0, 255, 255)),
+        (0.0, Rgba::new(255, 0, 0, 255)),
+        (0.5, Rgba::new(0, 255, 0, 0)),
+    ]);
+    assert_eq!(gradient.sample(-1.0), Rgba::new(255, 0, 0, 255));
+    assert_eq!(gradient.sample(0.25), Rgba::new(128, 128, 0, 128));
+    assert_eq!(gradient.sample(0.5), Rgba::new(0, 255, 0, 0));
+    assert_eq!(gradient.sample(2.0), Rgba::new(0, 0, 255, 255));
+    assert_eq!(Gradient::new(&[]).sample(0.5), Rgba::zero());
+
+    let linear = Brush::Linear {
+        start: Vec2::new(10.0, 0.0),
+        end: Vec2::new(10.0, 20.0),
+        gradient: gradient.clone(),
+    };
+    assert_eq!(linear.color_at(Vec2::new(-50.0, 5.0)), gradient.sample(0.25));
+    let radial = Brush::Radial {
+        center: Vec2::new(3.0, 3.0),
+        radius: 4.0,
+        gradient: gradient.clone(),
+    };
+    assert_eq!(radial.color_at(Vec2::new(3.0, 1.0)), gradient.sample(0.5));
+}
+
+#[test]
+fn vector_golden() {
+    let mut canvas = Canvas::filled(64, 64, Rgba::new(30, 30, 40, 255));
+
+    // a five-pointed star, whose middle is a hole with the even-odd rule
+    let star = |center: Vec2<f32>| {
+        let point = |i: i32| {
+            let angle = PI / 2.0 + i as f32 * 4.0 * PI / 5.0;
+            center + Vec2::new(angle.cos(), angle.sin()) * 14.0
+        };
+        (1..5).fold(Path::new().move_to(point(0)), |path, i| path.line_to(point(i))).close()
+    };
+    let gold = Brush::Solid(Rgba::new(240, 200, 60, 255));
+    fill(&mut canvas, &star(Vec2::new(16.0, 48.0)), FillRule::NonZero, &gold);
+    fill(&mut canvas, &star(Vec2::new(48.0, 48.0)), FillRule::EvenOdd, &gold);
+
+    // a gradient-filled translucent circle
+    let sunset = Brush::Linear {
+        start: Vec2::new(0.0, 6.0),
+        end: Vec2::new(0.0, 30.0),
+        gradient: Gradient::new(&[
+            (0.0, Rgba::new(250, 80, 40, 255)),
+            (1.0, Rgba::new(120, 60, 220, 160)),
+        ]),
+    };
+    fill(&mut canvas, &Path::circle(Vec2::new(18.0, 18.0), 12.0), FillRule::NonZero, &sunset);
+
+    // a stroked wave, with round joins and caps
+    let wave = Path::new()
+        .move_to(Vec2::new(36.0, 10.0))
+        .cubic_to(Vec2::new(40.0, 30.0), Vec2::new(46.0, 0.0), Vec2::new(50.0, 18.0))
+        .line_to(Vec2::new(58.0, 26.0))
+        .line_to(Vec2::new(58.0, 8.0));
+    let stroke = Stroke::new(3.0).with_join(Join::Round).with_cap(Cap::Round);
+    cpurender::vector::stroke(&mut canvas, &wave, &stroke, &Brush::Solid(WHITE));
+
+    Golden::new("vector", 64, 64).check(&canvas);
+}
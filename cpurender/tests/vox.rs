@@ -0,0 +1,131 @@
+use cpurender::{
+    re::vek::{Rgba, Vec3},
+    vox::*,
+    voxel::VoxelGrid,
+};
+
+fn chunk(id: &[u8; 4], content: &[u8], children: &[u8]) -> Vec<u8> {
+    let mut bytes = id.to_vec();
+    bytes.extend_from_slice(&(content.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&(children.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(content);
+    bytes.extend_from_slice(children);
+    bytes
+}
+
+/// A SIZE chunk, in MagicaVoxel's z-up coordinates.
+fn size(x: u32, y: u32, z: u32) -> Vec<u8> {
+    let content: Vec<u8> = [x, y, z].iter().flat_map(|c| c.to_le_bytes()).collect();
+    chunk(b"SIZE", &content, &[])
+}
+
+/// An XYZI chunk, of voxels in MagicaVoxel's z-up coordinates and their palette indices.
+fn xyzi(voxels: &[[u8; 4]]) -> Vec<u8> {
+    let mut content = (voxels.len() as u32).to_le_bytes().to_vec();
+    content.extend(voxels.iter().flatten());
+    chunk(b"XYZI", &content, &[])
+}
+
+/// A file with these chunks inside its MAIN chunk.
+fn vox(chunks: &[Vec<u8>]) -> Vec<u8> {
+    let mut bytes = b"VOX ".to_vec();
+    bytes.extend_from_slice(&150u32.to_le_bytes());
+    bytes.extend(chunk(b"MAIN", &[], &chunks.concat()));
+    bytes
+}
+
+fn assert_malformed(bytes: &[u8], msg: &str) {
+    match VoxScene::from_bytes(bytes) {
+        Err(VoxError::Malformed(e)) => assert!(e.contains(msg), "{:?} is not {:?}", e, msg),
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(_) => panic!("no error, expected {:?}", msg),
+    }
+}
+
+#[test]
+fn z_up_to_y_up() {
+    let scene = VoxScene::from_bytes(&vox(&[
+        size(2, 3, 4),
+        xyzi(&[[1, 2, 3, 7], [0, 0, 0, 9]]),
+    ])).unwrap();
+    assert_eq!(scene.models.len(), 1);
+
+    let grid = &scene.models[0].grid;
+    assert_eq!(grid.size(), Vec3::new(2, 4, 3));
+    assert_eq!(grid.get(Vec3::new(1, 3, 2)), Some(&7));
+    assert_eq!(grid.get(Vec3::new(0, 0, 0)), Some(&9));
+    assert_eq!(grid.get(Vec3::new(1, 2, 3)), None);
+    assert_eq!(grid.iter().filter(|&(_, &i)| i != 0).count(), 2);
+}
+
+#[test]
+fn palettes() {
+    let model = [size(1, 1, 1), xyzi(&[[0, 0, 0, 1]])];
+
+    // the default palette, shifted up by one like any other
+    let scene = VoxScene::from_bytes(&vox(&model)).unwrap();
+    assert_eq!(scene.palette[0], Rgba::zero());
+    assert_eq!(scene.palette[1], Rgba::new(255, 255, 255, 255));
+    assert_eq!(scene.palette[6], Rgba::new(255, 255, 0, 255));
+    assert_eq!(scene.palette[255], Rgba::new(0x11, 0x11, 0x11, 255));
+
+    // entry i of the RGBA chunk is the color of index i + 1, and the last is unused
+    let rgba: Vec<u8> = (0..=255).flat_map(|i| vec![i, 0, 255 - i, 255]).collect();
+    let chunks = [&model[..], &[chunk(b"RGBA", &rgba, &[])]].concat();
+    let scene = VoxScene::from_bytes(&vox(&chunks)).unwrap();
+    assert_eq!(scene.palette[0], Rgba::zero());
+    assert_eq!(scene.palette[1], Rgba::new(0, 0, 255, 255));
+    assert_eq!(scene.palette[255], Rgba::new(254, 0, 1, 255));
+
+    let colors = scene.models[0].colors(&scene.palette);
+    assert_eq!(colors.get(Vec3::zero()), Some(&Some(Rgba::new(0, 0, 255, 255))));
+    let empty = VoxModel { grid: VoxelGrid::new(Vec3::one(), 0) };
+    assert_eq!(empty.colors(&scene.palette).get(Vec3::zero()), Some(&None));
+}
+
+#[test]
+fn multiple_models() {
+    let scene = VoxScene::from_bytes(&vox(&[
+        chunk(b"PACK", &2u32.to_le_bytes(), &[]),
+        size(1, 2, 3),
+        xyzi(&[[0, 1, 2, 5]]),
+        chunk(b"nTRN", &[0; 12], &[]),
+        size(4, 4, 1),
+        xyzi(&[[3, 3, 0, 6], [0, 3, 0, 8]]),
+    ])).unwrap();
+    assert_eq!(scene.models.len(), 2);
+
+    let first = &scene.models[0].grid;
+    assert_eq!(first.size(), Vec3::new(1, 3, 2));
+    assert_eq!(first.get(Vec3::new(0, 2, 1)), Some(&5));
+
+    let second = &scene.models[1].grid;
+    assert_eq!(second.size(), Vec3::new(4, 1, 4));
+    assert_eq!(second.get(Vec3::new(3, 0, 3)), Some(&6));
+    assert_eq!(second.get(Vec3::new(0, 0, 3)), Some(&8));
+}
+
+#[test]
+fn malformed_files() {
+    let model = vox(&[size(2, 2, 2), xyzi(&[[1, 1, 1, 1]])]);
+    let mut bad_magic = model.clone();
+    bad_magic[0] = b'B';
+    assert_malformed(&bad_magic, "bad magic number");
+    assert_malformed(&b"VOX "[..], "unexpected end of data");
+    assert_malformed(&[&model[..8], &chunk(b"PACK", &[], &[])].concat(), "missing MAIN");
+
+    // chunks and their contents cut short
+    assert_malformed(&model[..model.len() - 1], "unexpected end of data");
+    let short_count = chunk(b"XYZI", &3u32.to_le_bytes(), &[]);
+    assert_malformed(&vox(&[size(2, 2, 2), short_count]), "unexpected end of data");
+    assert_malformed(&vox(&[chunk(b"SIZE", &[1, 0, 0, 0], &[])]), "unexpected end of data");
+    assert_malformed(&vox(&[chunk(b"RGBA", &[0; 1020], &[])]), "unexpected end of data");
+
+    assert_malformed(&vox(&[xyzi(&[[0, 0, 0, 1]])]), "without preceding SIZE");
+    assert_malformed(&vox(&[size(1, 1, 1), xyzi(&[]), xyzi(&[])]), "without preceding SIZE");
+    assert_malformed(&vox(&[size(2, 2, 2), xyzi(&[[0, 0, 2, 1]])]), "voxel outside of model");
+
+    // sizes which would take too much memory are rejected before allocating
+    assert_malformed(&vox(&[size(256, 257, 256)]), "too large");
+    assert_malformed(&vox(&[size(u32::MAX, u32::MAX, u32::MAX)]), "too large");
+}
@@ -0,0 +1,183 @@
+use cpurender::{
+    ray::Ray,
+    voxel::*,
+    re::vek::*,
+};
+
+fn voxels(ray: Ray, max_distance: f32) -> Vec<Vec3<i32>> {
+    traverse(ray, max_distance).map(|hit| hit.voxel).collect()
+}
+
+fn approx_eq(a: f32, b: f32) -> bool {
+    (a - b).abs() < 0.0001
+}
+
+#[test]
+fn axis_aligned_steps() {
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+    let hits: Vec<VoxelHit> = traverse(ray, 3.0).collect();
+
+    assert_eq!(hits.len(), 4);
+    assert_eq!(hits[0].voxel, Vec3::new(0, 0, 0));
+    assert_eq!(hits[0].face, None);
+    assert_eq!(hits[0].normal, Vec3::zero());
+    for (i, hit) in hits.iter().enumerate().skip(1) {
+        assert_eq!(hit.voxel, Vec3::new(i as i32, 0, 0));
+        assert!(approx_eq(hit.distance, i as f32 - 0.5));
+        assert_eq!(hit.face, Some(Face::NegX));
+        assert_eq!(hit.normal, Vec3::new(-1, 0, 0));
+    }
+}
+
+#[test]
+fn negative_direction() {
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, 0.0, -1.0));
+    let hits: Vec<VoxelHit> = traverse(ray, 2.0).collect();
+
+    assert_eq!(
+        hits.iter().map(|hit| hit.voxel).collect::<Vec<_>>(),
+        vec![Vec3::new(0, 0, 0), Vec3::new(0, 0, -1), Vec3::new(0, 0, -2)],
+    );
+    assert_eq!(hits[1].face, Some(Face::PosZ));
+    assert_eq!(hits[1].normal, Vec3::new(0, 0, 1));
+}
+
+#[test]
+fn integer_origin_positive_direction() {
+    let ray = Ray::new(Vec3::new(2.0, 0.5, 0.5), Vec3::new(1.0, 0.0, 0.0));
+    let hits: Vec<VoxelHit> = traverse(ray, 1.5).collect();
+
+    assert_eq!(hits[0].voxel, Vec3::new(2, 0, 0));
+    assert_eq!(hits[1].voxel, Vec3::new(3, 0, 0));
+    assert!(approx_eq(hits[1].distance, 1.0));
+}
+
+#[test]
+fn integer_origin_negative_direction() {
+    // starting on a boundary while moving negatively begins in the voxel below it
+    let ray = Ray::new(Vec3::new(2.0, 0.5, 0.5), Vec3::new(-1.0, 0.0, 0.0));
+    let hits: Vec<VoxelHit> = traverse(ray, 1.5).collect();
+
+    assert_eq!(hits[0].voxel, Vec3::new(1, 0, 0));
+    assert_eq!(hits[1].voxel, Vec3::new(0, 0, 0));
+    assert!(approx_eq(hits[1].distance, 1.0));
+}
+
+#[test]
+fn integer_origin_all_axes() {
+    let ray = Ray::new(Vec3::new(-5.0, 5.0, -5.0), Vec3::new(1.0, -1.0, 2.0).normalized());
+    let first = traverse(ray, 1.0).next().unwrap();
+    assert_eq!(first.voxel, Vec3::new(-5, 4, -5));
+}
+
+#[test]
+fn edge_crossing_is_merged() {
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(1.0, 1.0, 0.0).normalized());
+    let hits: Vec<VoxelHit> = traverse(ray, 3.0).collect();
+
+    // passes through (1, 1) without visiting (1, 0) or (0, 1)
+    assert_eq!(hits[1].voxel, Vec3::new(1, 1, 0));
+    assert_eq!(hits[1].normal, Vec3::new(-1, -1, 0));
+    assert_eq!(hits[1].face, Some(Face::NegX));
+    assert_eq!(hits[2].voxel, Vec3::new(2, 2, 0));
+    for hit in &hits[1..] {
+        assert_eq!(hits.iter().filter(|h| h.voxel == hit.voxel).count(), 1);
+    }
+}
+
+#[test]
+fn corner_crossing_is_merged() {
+    let ray = Ray::new(Vec3::new(0.25, 0.25, 0.25), Vec3::new(-1.0, -1.0, -1.0).normalized());
+    let hits: Vec<VoxelHit> = traverse(ray, 2.0).collect();
+
+    assert_eq!(hits[1].voxel, Vec3::new(-1, -1, -1));
+    assert_eq!(hits[1].normal, Vec3::new(1, 1, 1));
+    assert!(approx_eq(hits[1].distance, 0.25 * 3.0f32.sqrt()));
+}
+
+#[test]
+fn nearly_equal_crossings_are_merged() {
+    let ray = Ray::new(
+        Vec3::new(0.5, 0.5, 0.5),
+        Vec3::new(1.0, 1.000001, 0.0).normalized(),
+    );
+    let hits: Vec<VoxelHit> = traverse(ray, 3.0).collect();
+    assert_eq!(hits[1].voxel, Vec3::new(1, 1, 0));
+}
+
+#[test]
+fn previous_voxel_is_behind_normal() {
+    let ray = Ray::new(Vec3::new(0.3, 0.7, 0.1), Vec3::new(0.3, -0.8, 0.5).normalized());
+    let hits: Vec<VoxelHit> = traverse(ray, 20.0).collect();
+    assert!(hits.len() > 10);
+    for pair in hits.windows(2) {
+        assert_eq!(pair[1].voxel + pair[1].normal, pair[0].voxel);
+        assert!(pair[1].distance >= pair[0].distance);
+    }
+}
+
+#[test]
+fn max_distance() {
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::new(0.0, 1.0, 0.0));
+    assert_eq!(voxels(ray, 0.4).len(), 1);
+    assert_eq!(voxels(ray, 0.6).len(), 2);
+    assert_eq!(voxels(ray, 10.0).len(), 11);
+}
+
+#[test]
+fn zero_direction() {
+    let ray = Ray::new(Vec3::new(0.5, 0.5, 0.5), Vec3::zero());
+    assert_eq!(voxels(ray, 10.0), vec![Vec3::new(0, 0, 0)]);
+}
+
+#[test]
+fn grid_access() {
+    let mut grid = VoxelGrid::from_fn(Vec3::new(2, 3, 4), |xyz| xyz.sum());
+    assert_eq!(grid.get(Vec3::new(1, 2, 3)), Some(&6));
+    assert_eq!(grid.get(Vec3::new(2, 0, 0)), None);
+    assert_eq!(grid.get(Vec3::new(0, -1, 0)), None);
+
+    grid.set(Vec3::new(1, 1, 1), 100);
+    assert_eq!(grid.get(Vec3::new(1, 1, 1)), Some(&100));
+    assert!(grid.iter().all(|(xyz, &v)| v == 100 || v == xyz.sum()));
+    assert_eq!(grid.iter().count(), 24);
+}
+
+#[test]
+fn grid_trace_from_outside() {
+    let grid = VoxelGrid::new(Vec3::new(5, 5, 5), ());
+    let ray = Ray::new(Vec3::new(-10.0, 2.5, 2.5), Vec3::new(1.0, 0.0, 0.0));
+    let hits: Vec<VoxelHit> = grid.trace(ray, 100.0).map(|(hit, _)| hit).collect();
+
+    assert_eq!(hits.len(), 5);
+    assert_eq!(hits[0].voxel, Vec3::new(0, 2, 2));
+    assert_eq!(hits[0].face, Some(Face::NegX));
+    assert!(approx_eq(hits[0].distance, 10.0));
+    assert_eq!(hits[4].voxel, Vec3::new(4, 2, 2));
+}
+
+#[test]
+fn grid_trace_from_above() {
+    // enters exactly on an integer plane while moving negatively
+    let grid = VoxelGrid::new(Vec3::new(5, 5, 5), ());
+    let ray = Ray::new(Vec3::new(2.5, 8.0, 2.5), Vec3::new(0.0, -1.0, 0.0));
+    let hits: Vec<VoxelHit> = grid.trace(ray, 100.0).map(|(hit, _)| hit).collect();
+
+    assert_eq!(hits.len(), 5);
+    assert_eq!(hits[0].voxel, Vec3::new(2, 4, 2));
+    assert_eq!(hits[0].face, Some(Face::PosY));
+    assert_eq!(hits[0].normal, Vec3::new(0, 1, 0));
+}
+
+#[test]
+fn grid_trace_inside_and_miss() {
+    let grid = VoxelGrid::new(Vec3::new(5, 5, 5), ());
+
+    let inside = Ray::new(Vec3::new(2.5, 2.5, 2.5), Vec3::new(0.0, 0.0, 1.0));
+    let hits: Vec<VoxelHit> = grid.trace(inside, 100.0).map(|(hit, _)| hit).collect();
+    assert_eq!(hits.len(), 3);
+    assert_eq!(hits[0].face, None);
+
+    let miss = Ray::new(Vec3::new(-1.0, 2.5, 2.5), Vec3::new(-1.0, 0.0, 0.0));
+    assert_eq!(grid.trace(miss, 100.0).count(), 0);
+}
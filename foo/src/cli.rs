@@ -0,0 +1,322 @@
+use std::{
+    path::PathBuf,
+    str::FromStr,
+};
+
+use cpurender::re::vek::*;
+
+pub const USAGE: &str = "\
+usage: foo [OPTIONS] [MODEL]
+
+Renders a voxel scene: a checkerboard lattice, or a MagicaVoxel .vox model.
+
+options:
+    --size WxH          resolution (default 1000x1000)
+    --camera-pos X,Y,Z  camera position (default depends on the scene)
+    --camera-dir X,Y,Z  camera direction (default depends on the scene)
+    --fov DEGREES       vertical field of view (default 100)
+    --iterations N      maximum voxels stepped through per ray (default 50)
+    --grid N|XxYxZ      size of the checkerboard lattice (default 5)
+    --model PATH        render a .vox model instead of the lattice
+    --threads N         number of render threads (default one per core)
+    --sequential        render one fragment at a time, in a fixed order
+    --mode MODE         window, terminal, headless, png or bench (default window)
+    --output PATH       image to write in png mode (default foo.png)
+    --frames N          frames to render in bench mode (default 10)
+    -h, --help          print this message
+";
+
+/// Where the voxels come from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SceneSource {
+    /// Checkerboard lattice of some size.
+    Grid(Vec3<usize>),
+    /// MagicaVoxel model file.
+    Model(PathBuf),
+}
+
+/// What to do with the rendered frames.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mode {
+    /// Display in a window.
+    Window,
+    /// Display in the terminal.
+    Terminal,
+    /// Render a single frame and report the time taken.
+    Headless,
+    /// Render a single frame to an image file.
+    Png(PathBuf),
+    /// Render some number of frames and report timing statistics.
+    Bench { frames: usize },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Options {
+    pub size: Vec2<usize>,
+    pub camera_pos: Option<Vec3<f32>>,
+    pub camera_dir: Option<Vec3<f32>>,
+    /// Vertical field of view in degrees.
+    pub fov: f32,
+    /// Maximum voxels stepped through per ray.
+    pub iterations: usize,
+    pub scene: SceneSource,
+    pub threads: Option<usize>,
+    pub sequential: bool,
+    pub mode: Mode,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            size: Vec2::new(1000, 1000),
+            camera_pos: None,
+            camera_dir: None,
+            fov: 100.0,
+            iterations: 50,
+            scene: SceneSource::Grid(Vec3::broadcast(5)),
+            threads: None,
+            sequential: false,
+            mode: Mode::Window,
+        }
+    }
+}
+
+/// Result of parsing the command line.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    Run(Options),
+    Help,
+}
+
+/// Parse command line arguments, not including the program name.
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut options = Options::default();
+    let mut mode = "window".to_owned();
+    let mut output = PathBuf::from("foo.png");
+    let mut frames = 10;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            return Ok(Command::Help);
+        }
+        if arg == "--sequential" {
+            options.sequential = true;
+            continue;
+        }
+        if !arg.starts_with('-') {
+            // positional model path
+            options.scene = SceneSource::Model(arg.into());
+            continue;
+        }
+
+        // support both `--flag value` and `--flag=value`
+        let (flag, inline_value) = match arg.find('=') {
+            Some(i) => (arg[..i].to_owned(), Some(arg[i + 1..].to_owned())),
+            None => (arg.clone(), None),
+        };
+        let mut value = || inline_value.clone()
+            .or_else(|| args.next())
+            .ok_or_else(|| format!("missing value for {}", flag));
+
+        match flag.as_str() {
+            "--size" => options.size = parse_vec2(&value()?, 'x', &flag)?,
+            "--camera-pos" => options.camera_pos = Some(parse_vec3(&value()?, ',', &flag)?),
+            "--camera-dir" => options.camera_dir = Some(parse_vec3(&value()?, ',', &flag)?),
+            "--fov" => options.fov = parse_value(&value()?, &flag)?,
+            "--iterations" => options.iterations = parse_value(&value()?, &flag)?,
+            "--grid" => {
+                let value = value()?;
+                let size = match parse_value::<usize>(&value, &flag) {
+                    Ok(n) => Vec3::broadcast(n),
+                    Err(_) => parse_vec3(&value, 'x', &flag)?,
+                };
+                options.scene = SceneSource::Grid(size);
+            },
+            "--model" => options.scene = SceneSource::Model(value()?.into()),
+            "--threads" => options.threads = Some(parse_value(&value()?, &flag)?),
+            "--mode" => mode = value()?,
+            "--output" => output = value()?.into(),
+            "--frames" => frames = parse_value(&value()?, &flag)?,
+            _ => return Err(format!("unknown option {}", flag)),
+        }
+    }
+
+    options.mode = match mode.as_str() {
+        "window" => Mode::Window,
+        "terminal" => Mode::Terminal,
+        "headless" => Mode::Headless,
+        "png" => Mode::Png(output),
+        "bench" => Mode::Bench { frames },
+        _ => return Err(format!("unknown mode {:?}", mode)),
+    };
+    if options.size.product() == 0 {
+        return Err("--size must be nonzero".to_owned());
+    }
+    if options.threads == Some(0) {
+        return Err("--threads must be nonzero".to_owned());
+    }
+    if frames == 0 {
+        return Err("--frames must be nonzero".to_owned());
+    }
+    if let Some(pos) = options.camera_pos {
+        if !pos.iter().all(|c| c.is_finite()) {
+            return Err("--camera-pos must be finite".to_owned());
+        }
+    }
+    if let Some(dir) = options.camera_dir {
+        if !dir.iter().all(|c| c.is_finite()) || dir == Vec3::zero() {
+            return Err("--camera-dir must be finite and nonzero".to_owned());
+        }
+    }
+    if !(options.fov > 0.0 && options.fov < 180.0) {
+        return Err("--fov must be between 0 and 180".to_owned());
+    }
+    Ok(Command::Run(options))
+}
+
+fn parse_value<T: FromStr>(s: &str, flag: &str) -> Result<T, String> {
+    s.trim().parse().map_err(|_| format!("invalid value {:?} for {}", s, flag))
+}
+
+/// Parse separated components, such as `1000x800` or `1.5,2,-3`.
+fn parse_list<T: FromStr>(
+    s: &str,
+    separator: char,
+    n: usize,
+    flag: &str,
+) -> Result<Vec<T>, String> {
+    let list = s.split(separator)
+        .map(|c| parse_value(c, flag))
+        .collect::<Result<Vec<T>, String>>()?;
+    if list.len() != n {
+        return Err(format!("expected {} values separated by {:?} for {}", n, separator, flag));
+    }
+    Ok(list)
+}
+
+fn parse_vec2<T: FromStr + Copy>(s: &str, separator: char, flag: &str) -> Result<Vec2<T>, String> {
+    let c = parse_list(s, separator, 2, flag)?;
+    Ok(Vec2::new(c[0], c[1]))
+}
+
+fn parse_vec3<T: FromStr + Copy>(s: &str, separator: char, flag: &str) -> Result<Vec3<T>, String> {
+    let c = parse_list(s, separator, 3, flag)?;
+    Ok(Vec3::new(c[0], c[1], c[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(args: &[&str]) -> Result<Options, String> {
+        match parse(args.iter().map(|&s| s.to_owned()))? {
+            Command::Run(options) => Ok(options),
+            Command::Help => panic!("unexpected help"),
+        }
+    }
+
+    fn error(args: &[&str]) -> String {
+        run(args).expect_err("expected an error")
+    }
+
+    #[test]
+    fn defaults() {
+        assert_eq!(run(&[]), Ok(Options::default()));
+        let options = Options::default();
+        assert_eq!(options.size, Vec2::new(1000, 1000));
+        assert_eq!(options.iterations, 50);
+        assert_eq!(options.mode, Mode::Window);
+        assert_eq!(options.scene, SceneSource::Grid(Vec3::broadcast(5)));
+    }
+
+    #[test]
+    fn every_flag() {
+        let options = run(&[
+            "--size", "640x480",
+            "--camera-pos=1,2.5,-3",
+            "--camera-dir", "0,0,1",
+            "--fov", "75",
+            "--iterations=200",
+            "--grid", "2x3x4",
+            "--threads", "3",
+            "--sequential",
+        ]).unwrap();
+        assert_eq!(options, Options {
+            size: Vec2::new(640, 480),
+            camera_pos: Some(Vec3::new(1.0, 2.5, -3.0)),
+            camera_dir: Some(Vec3::new(0.0, 0.0, 1.0)),
+            fov: 75.0,
+            iterations: 200,
+            scene: SceneSource::Grid(Vec3::new(2, 3, 4)),
+            threads: Some(3),
+            sequential: true,
+            mode: Mode::Window,
+        });
+
+        assert_eq!(run(&["--grid", "7"]).unwrap().scene, SceneSource::Grid(Vec3::broadcast(7)));
+
+        // the last scene given wins, whether a flag or positional
+        let model = SceneSource::Model("a.vox".into());
+        assert_eq!(run(&["--model", "a.vox"]).unwrap().scene, model);
+        assert_eq!(run(&["--grid=3", "a.vox"]).unwrap().scene, model);
+        let grid = SceneSource::Grid(Vec3::broadcast(3));
+        assert_eq!(run(&["a.vox", "--grid=3"]).unwrap().scene, grid);
+
+        // help wins over anything after it
+        for help in &["-h", "--help"] {
+            let args = vec!["--fov=90".to_owned(), help.to_string(), "--bogus".to_owned()];
+            assert_eq!(parse(args), Ok(Command::Help));
+        }
+    }
+
+    #[test]
+    fn modes() {
+        assert_eq!(run(&["--mode", "window"]).unwrap().mode, Mode::Window);
+        assert_eq!(run(&["--mode", "terminal"]).unwrap().mode, Mode::Terminal);
+        assert_eq!(run(&["--mode", "headless"]).unwrap().mode, Mode::Headless);
+        assert_eq!(run(&["--mode", "png"]).unwrap().mode, Mode::Png("foo.png".into()));
+        assert_eq!(run(&["--mode", "bench"]).unwrap().mode, Mode::Bench { frames: 10 });
+
+        // mode options may come before or after the mode, and are ignored by other modes
+        let png = run(&["--output", "out.png", "--mode=png"]).unwrap();
+        assert_eq!(png.mode, Mode::Png("out.png".into()));
+        let bench = run(&["--mode=bench", "--frames", "3"]).unwrap();
+        assert_eq!(bench.mode, Mode::Bench { frames: 3 });
+        assert_eq!(run(&["--frames", "3", "--output=x.png"]).unwrap().mode, Mode::Window);
+
+        assert_eq!(error(&["--mode", "vr"]), "unknown mode \"vr\"");
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert_eq!(error(&["--fov"]), "missing value for --fov");
+        assert_eq!(error(&["--size"]), "missing value for --size");
+        assert_eq!(error(&["--frobnicate", "1"]), "unknown option --frobnicate");
+        assert_eq!(error(&["--threads", "many"]), "invalid value \"many\" for --threads");
+        assert_eq!(error(&["--iterations=-1"]), "invalid value \"-1\" for --iterations");
+        assert_eq!(error(&["--grid", "2x3"]), "expected 3 values separated by 'x' for --grid");
+        assert_eq!(error(&["--camera-pos", "1,2"]),
+            "expected 3 values separated by ',' for --camera-pos");
+        assert_eq!(error(&["--size", "10x10x10"]),
+            "expected 2 values separated by 'x' for --size");
+
+        // zeros which would make nothing to render, or divide by zero
+        assert_eq!(error(&["--size", "0x100"]), "--size must be nonzero");
+        assert_eq!(error(&["--threads", "0"]), "--threads must be nonzero");
+        assert_eq!(error(&["--mode=bench", "--frames=0"]), "--frames must be nonzero");
+
+        // cameras which would cast degenerate rays
+        let pos = "--camera-pos must be finite";
+        assert_eq!(error(&["--camera-pos", "0,inf,0"]), pos);
+        assert_eq!(error(&["--camera-pos", "NaN,0,0"]), pos);
+        let dir = "--camera-dir must be finite and nonzero";
+        assert_eq!(error(&["--camera-dir", "0,0,0"]), dir);
+        assert_eq!(error(&["--camera-dir", "1,-inf,0"]), dir);
+        assert_eq!(error(&["--camera-dir", "NaN,1,0"]), dir);
+        for fov in &["0", "-30", "180", "270", "NaN", "inf"] {
+            assert_eq!(error(&["--fov", fov]), "--fov must be between 0 and 180");
+        }
+        assert_eq!(run(&["--fov", "179.5"]).unwrap().fov, 179.5);
+    }
+}
@@ -1,297 +1,223 @@
 #![allow(unused_imports)]
 #![allow(unused_parens)]
-#![feature(clamp)]
 
 extern crate cpurender;
 
-use std::{f32, f64, u32, u8};
 use std::mem;
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use std::env;
+use std::process;
 
 use cpurender::*;
 use cpurender::frag::*;
 use cpurender::re::vek::*;
+use cpurender::camera::Camera;
+use cpurender::voxel::VoxelGrid;
+use cpurender::lighting::{Light, VoxelLighting};
+use cpurender::vox::VoxScene;
+
+use cli::{Command, Mode, SceneSource};
+
+/// Command line parsing.
+mod cli;
 
 // trick to allow us to easily toggle fp precision
 #[allow(non_camel_case_types)]
 type float = f32;
-const NAN: float = f32::NAN;
-
-/// Rust's float::sign implementation simply extracts the sign bit, which will
-/// consider signum(+0.0)=1.0 and signum(-0.0)=-1.0
-fn zero_respecting_signum(n: float) -> float {
-    n.signum() * (n != 0.0) as i32 as float
-}
 
 /// Float equality within epsilon.
 fn approx_eq(a: float, b: float) -> bool {
     (a - b).abs() < 0.00001
 }
 
-/// Power of 2.
-fn square(x: float) -> float {
-    x * x
-}
-
-/// UB if None.
-#[cfg(debug_assertions)]
-unsafe fn unwrap_unchecked<T>(opt: Option<T>) -> T {
-    opt.unwrap()
-}
-
-/// UB if None.
-#[cfg(not(debug_assertions))]
-unsafe fn unwrap_unchecked<T>(opt: Option<T>) -> T {
-    match opt {
-        Some(t) => t,
-        None => std::hint::unreachable_unchecked(),
-    }
-}
-
-macro_rules! unroll {
-    {
-        for $var:pat in [
-            $(
-                $elem:expr
-            ),*
-            $(,)?
-        ] $body:block
-    } => {{
-        $(
-        {
-            let $var = $elem;
-            $body;
-        }
-        )*
-    }};
+/// Everything a fragment needs.
+struct State {
+    camera: Camera,
+    grid: VoxelGrid<Option<Rgba<u8>>>,
+    lighting: VoxelLighting,
+    /// Camera movement speed, in voxels per second.
+    speed: float,
 }
 
 fn main() {
-    let x_len = 1000;
-    let y_len = 1000;
-
-    struct State {
-        cam_pos: Vec3<float>,
-        cam_dir: Vec3<float>,
-        cam_fov: float,
-    }
-
-    let state = State {
-        cam_dir: Vec3::new(1.0, -1.0, 2.0).normalized(),
-        cam_pos: Vec3::new(-5.0, 5.0, -5.0),
-        cam_fov: (100.0 as float).to_radians(),
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        },
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        },
     };
 
-    fragment_stateful(
-        x_len,
-        y_len,
-        state,
-        move |xy, state| {
-            // convert xy from [0, (x|y)_len] to [-1, 1]
-            let xy_balanced: Vec2<float> = (
-                (
-                    unsafe { unwrap_unchecked(xy.numcast::<float>()) }
-                        / unsafe { unwrap_unchecked(Vec2::new(x_len, y_len).numcast::<float>()) }
-                        * 2.0
-                ) - Vec2::one()
-            );
-            debug_assert!(xy_balanced
-                .map(|c| c >= -1.0 && c <= 1.0)
-                .reduce_and());
-
-            // calculate ray direction for this fragment
-            let mut direction: Vec3<float> = {
-                let fov_coef = (state.cam_fov / 2.0).tan() / (45.0 as float).to_radians().tan();
-                let perspective: Quaternion<float> = Quaternion::rotation_from_to_3d(
-                    Vec3::new(0.0, 0.0, 1.0),
-                    Vec3::new(
-                        xy_balanced.x * fov_coef,
-                        xy_balanced.y * fov_coef,
-                        1.0,
-                    ).normalized(),
-                );
-
-                let view: Quaternion<float> = {
-                    // see: https://math.stackexchange.com/questions/470112/calculate-camera-pitch-yaw-to-face-point
-                    let yaw: float = state.cam_dir.z.atan2(state.cam_dir.x);
-                    let pitch: float = state.cam_dir.y.atan2(
-                        (square(state.cam_dir.x) + square(state.cam_dir.z)).sqrt()
-                    );
-                    Quaternion::rotation_y(yaw) * Quaternion::rotation_x(-pitch)
-                };
-
-                view * perspective * Vec3::forward_lh()
-            };
-            debug_assert!(approx_eq(direction.magnitude(), 1.0));
+    let execution = match options.threads {
+        _ if options.sequential => Execution::Sequential,
+        Some(threads) => Execution::Threads(threads).resolve(),
+        None => Execution::Global,
+    };
 
-            // calculate voxel coordinate and ingress
-            let (mut voxel, mut ingress) = {
-                let voxel_float: Vec3<float> = state.cam_pos.floor();
-                let ingress: Vec3<float> = (
-                    (
-                        state.cam_pos
-                    ) - (
-                        voxel_float
-                    ) + (
-                        // consider the following on all axis:
-                        //
-                        // if direction is negative, then the collision plane will be at
-                        // 0. if the camera position is a multiple or 1, then
-                        // `cam_pos - floor(cam_pos)` will also equal zero. that would cause
-                        // the ingress point to lie in the plane it is casting to intersect
-                        // with, which would ruin the math. so, in that situation, we
-                        // need to set the ingress value to 1.
-                        state.cam_pos.map2(
-                            direction,
-                            |p, d| (p % 1.0 == 0.0 && d < 0.0) as i32 as float
-                        )
-                    )
-                );
-                (unsafe { unwrap_unchecked(voxel_float.numcast::<i32>()) }, ingress)
+    let x_len = options.size.x;
+    let y_len = options.size.y;
+
+    let grid: VoxelGrid<Option<Rgba<u8>>> = match &options.scene {
+        SceneSource::Model(path) => {
+            let scene = match VoxScene::open(path) {
+                Ok(scene) => scene,
+                Err(e) => {
+                    eprintln!("error: failed to load {:?}: {}", path, e);
+                    process::exit(1);
+                },
             };
-            debug_assert!(ingress
-                .map(|c| c >= 0.0 && c <= 1.0)
-                .reduce_and());
-
-            let mut hits = 0;
-
-            // raytrace loop
-            'outer_loop: for iteration in 0..50 {
-
-                let planes: Vec3<float> = direction.ceil();
-                debug_assert!(planes
-                    .map(|c| c == 0.0 || c == 1.0)
-                    .reduce_and());
-
-                let mut distances: [float; 3] = [NAN; 3];
-
-                unroll! { for a in [0, 1, 2] {
-                    debug_assert!(planes[a] != ingress[a] || direction[a] == 0.0);
-
-                    distances[a] = (
-                        (
-                            (
-                                planes[a] - ingress[a]
-                            ) / (
-                                direction[a] + (direction[a] == 0.0) as i32 as float
-                            )
-                        ) * (
-                            (direction[a] != 0.0) as i32 as float
-                        )
-                    );
-
-                    debug_assert!(distances[a] >= 0.0);
-                }};
-
-                let mut seq_distance: [float; 3] = [0.0; 3];
-                let mut seq_voxel_delta: [Vec3<i32>; 3] = [Vec3::zero(); 3];
-
-                // sort seq, with equal-distance merging
-                unroll!{ for (a, b, c) in [(0, 1, 2), (1, 2, 0), (2, 0, 1)] {
-                    let index: usize = (
-                        (distances[a] > distances[b]) as usize
-                            + (distances[a] > distances[c]) as usize
-                    );
-
-                    seq_distance[index] = distances[a];
-
-                    seq_voxel_delta[index][a] += zero_respecting_signum(direction[a]) as i32;
-
-                    debug_assert_eq!(
-                        direction[a] == 0.0,
-                        distances[a] == 0.0,
-                    );
-                }};
-
-                let mut hit_index: usize = 2;
-
-                unroll!{ for a in [1, 0] {
-                    let b: usize = a + 1;
-
-                    let dedup_epsilon: float = 0.00001;
-
-                    let a_present: bool = seq_distance[a] != 0.0;
-                    let should_merge: bool = (
-                        a_present
-                            & (
-                            (seq_distance[a] - seq_distance[b]).abs() < dedup_epsilon
-                        )
-                    );
-
-                    hit_index -= a_present as usize;
-                    seq_voxel_delta[a] += (
-                        seq_voxel_delta[b] * (
-                            should_merge as usize as i32
-                        )
-                    );
-                    seq_distance[a] = seq_distance[(
-                        a + should_merge as usize
-                    )];
-                }};
-
-                debug_assert!(seq_distance[hit_index] != 0.0);
-
-                // the following code becomes much simpler, now that we have eliminated the
-                // inner loop.
-                ingress = (
-                    (
-                        ingress + (direction * seq_distance[hit_index])
-                    ) - (
-                        unsafe { unwrap_unchecked(seq_voxel_delta[hit_index].numcast::<float>()) }
-                    )
-                );
-
-                voxel += seq_voxel_delta[hit_index];
+            match scene.models.first() {
+                Some(model) => model.colors(&scene.palette),
+                None => {
+                    eprintln!("error: no models in {:?}", path);
+                    process::exit(1);
+                },
+            }
+        },
+        &SceneSource::Grid(size) => {
+            // checkerboard lattice, colored by position
+            let max = size.map(|c| c.saturating_sub(1).max(1) as i32);
+            VoxelGrid::from_fn(size, |xyz| {
+                match xyz.sum() % 2 {
+                    0 => Some(Rgba::from_opaque((xyz * 0xFF / max).map(|c| c as u8))),
+                    _ => None,
+                }
+            })
+        },
+    };
 
-                debug_assert!(ingress
-                    .map(|c| approx_eq(c, 0.0) || approx_eq(c, 1.0))
-                    .reduce_or());
-                debug_assert!(ingress
-                    .map(|c| c >= -0.00001 && c <= 1.00001)
-                    .reduce_and());
+    // by default, look from above one corner, at the model's center or across the lattice
+    let size: Vec3<float> = grid.size().map(|c| c as float);
+    let position = options.camera_pos
+        .unwrap_or(Vec3::new(-1.0, 1.0, -1.0) * size.reduce_partial_max());
+    let direction = options.camera_dir.unwrap_or_else(|| match options.scene {
+        SceneSource::Model(_) => size / 2.0 - position,
+        SceneSource::Grid(_) => Vec3::new(1.0, -1.0, 2.0),
+    });
+    let camera = Camera::perspective(x_len, y_len, options.fov.to_radians())
+        .at(position)
+        .looking_in(direction, Vec3::unit_y());
+
+    let lighting = VoxelLighting {
+        lights: vec![
+            Light::Directional {
+                direction: Vec3::new(-0.4, -1.0, 0.6),
+                color: Rgb::broadcast(0.7),
+            },
+            Light::Point {
+                position: size * Vec3::new(0.5, 1.5, -0.5),
+                color: Rgb::new(6.0, 5.0, 4.0),
+            },
+        ],
+        max_steps: options.iterations,
+        ..VoxelLighting::default()
+    };
 
-                // collision
-                fn in_grid(v: Vec3<i32>) -> bool {
-                    v.partial_cmpge(&Vec3::new(0, 0, 0)).reduce_and()
-                        && v.partial_cmplt(&Vec3::new(5, 5, 5)).reduce_and()
-                }
+    let state = State {
+        camera,
+        grid,
+        lighting,
+        speed: size.reduce_partial_max() / 4.0,
+    };
 
-                if in_grid(voxel) {
+    match options.mode {
+        Mode::Window => {
+            let options = FragmentOptions {
+                execution,
+                ..FragmentOptions::default()
+            };
+            fragment_update_with(x_len, y_len, state, options, fly, shade);
+        },
+        Mode::Terminal => open_terminal(x_len, y_len, move |queue| {
+            render_with(x_len, y_len, &execution, |xy| shade(xy, &state)).paint(&queue);
+        }),
+        Mode::Headless => {
+            let start = Instant::now();
+            render_with(x_len, y_len, &execution, |xy| shade(xy, &state));
+            println!("rendered {}x{} in {:?}", x_len, y_len, start.elapsed());
+        },
+        Mode::Png(path) => {
+            let canvas = render_with(x_len, y_len, &execution, |xy| shade(xy, &state));
+            if let Err(e) = canvas.to_image().save(&path) {
+                eprintln!("error: failed to write {:?}: {}", path, e);
+                process::exit(1);
+            }
+            println!("wrote {}", path.display());
+        },
+        Mode::Bench { frames } => {
+            let times: Vec<Duration> = (0..frames)
+                .map(|_| {
+                    let start = Instant::now();
+                    render_with(x_len, y_len, &execution, |xy| shade(xy, &state));
+                    start.elapsed()
+                })
+                .collect();
+            let total: Duration = times.iter().sum();
+            let mean = total / frames as u32;
+            let min = times.iter().min().copied().unwrap_or_default();
+            let max = times.iter().max().copied().unwrap_or_default();
+            let mpx = (x_len * y_len * frames) as f64 / total.as_secs_f64() / 1e6;
+            println!(
+                "{} frames of {}x{}: mean {:?}, min {:?}, max {:?}, {:.2} Mpx/s",
+                frames, x_len, y_len, mean, min, max, mpx,
+            );
+        },
+    }
+}
 
-                    // this could be better optimized, but it's to only count external
-                    // edges once
-                    let incr = match in_grid(voxel - seq_voxel_delta[hit_index]) {
-                        true => {
-                            ingress
-                                .map(|c| match approx_eq(c, 0.0) || approx_eq(c, 1.0) {
-                                    true => 1,
-                                    false => 0,
-                                })
-                                .reduce(|a, b| a + b)
-                        },
-                        false => 1,
-                    };
+/// Move the camera: WASD to move, space and shift to rise and fall, and arrow keys or
+/// dragging with the left mouse button to look around.
+fn fly(state: &mut State, frame: &FrameContext) {
+    let input = &frame.input;
+    let dt = frame.delta.as_secs_f32();
+    let key_axis = |positive, negative| {
+        input.is_down(positive) as i32 as float - input.is_down(negative) as i32 as float
+    };
 
-                    hits += incr;
+    // move relative to the direction we're facing, but keep rising and falling vertical
+    let camera = &state.camera;
+    let flat_forward = Vec3::new(camera.forward().x, 0.0, camera.forward().z)
+        .try_normalized()
+        .unwrap_or_else(Vec3::unit_z);
+    let flat_right = Vec3::unit_y().cross(flat_forward);
+    let movement = flat_forward * key_axis(VirtualKeyCode::W, VirtualKeyCode::S)
+        + flat_right * key_axis(VirtualKeyCode::D, VirtualKeyCode::A)
+        + Vec3::unit_y() * key_axis(VirtualKeyCode::Space, VirtualKeyCode::LShift);
+    let position = camera.position() + movement * state.speed * dt;
+
+    // radians
+    let mut yaw = key_axis(VirtualKeyCode::Right, VirtualKeyCode::Left) * 1.5 * dt;
+    let mut pitch = key_axis(VirtualKeyCode::Up, VirtualKeyCode::Down) * 1.5 * dt;
+    if input.is_button_down(MouseButton::Left) {
+        yaw += input.mouse_delta.x * 0.005;
+        pitch -= input.mouse_delta.y * 0.005;
+    }
+    let orientation = Quaternion::rotation_y(yaw)
+        * camera.orientation()
+        * Quaternion::rotation_x(-pitch);
 
-                    for _ in 0..incr {
-                        //direction = Quaternion::rotation_x((-5.0 as float).to_radians()) * direction;
-                    }
+    state.camera = state.camera
+        .at(position)
+        .oriented(orientation);
+}
 
-                }
-            }
+/// Compute the color of a fragment.
+fn shade(xy: Vec2<i32>, state: &State) -> Rgba<u8> {
+    // calculate ray for this fragment
+    let ray = state.camera.ray_for_pixel(xy);
+    debug_assert!(approx_eq(ray.dir.magnitude(), 1.0));
 
-            // compute color
-            let rgb: Rgb<float> = Rgb {
-                r: hits as float / 15.0,
-                g: hits as float / 10.0,
-                b: hits as float / 5.0,
-            };
+    // shade the first solid voxel
+    let rgb: Rgb<float> = state.lighting.shade(&state.grid, ray, |voxel| {
+        voxel.map(|color| color.rgb().map(|c| c as float / 0xFF as float))
+    });
 
-            Rgba::<float>::from_opaque(rgb)
-                .map(|c| c.clamp(0.0, 1.0))
-                .map(|c| (c * 0xFF as float) as u8)
-        },
-    );
+    Rgba::<float>::from_opaque(rgb)
+        .map(|c| c.clamp(0.0, 1.0))
+        .map(|c| (c * 0xFF as float) as u8)
 }